@@ -0,0 +1,126 @@
+//! physics.rs benchmark
+//!
+//! Criterion benchmarks of the per-frame physics passes (see src/objects/physics.rs),
+//! independent of Bevy and of rendering. Every universe is generated from a fixed seed
+//! so that runs are reproducible and optimizations can be compared against a baseline.
+//!
+//! Run with `cargo bench`
+
+use black_hole_universe::objects::gamestate::{UNIVERSE_SIZE, UniverseConfig};
+use black_hole_universe::objects::movables::{GravityMatrix, Movable, ObjectType, PhysicsConfig};
+use black_hole_universe::objects::physics::{collision_pass, gravity_pass};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+
+/// universe populations to benchmark
+const SIZES: [usize; 4] = [10, 50, 100, 200];
+
+/// time step of a 60 fps frame, in seconds
+const FRAME: f32 = 1.0 / 60.0;
+
+/// fn seeded_universe returns n black holes with reproducible positions, velocities
+/// and masses. The previous positions are set one frame back along the velocity so
+/// that the collision sweep sees the objects as moving
+fn seeded_universe(n: usize) -> Vec<Movable> {
+    let mut rng = StdRng::seed_from_u64(n as u64);
+    let half = UNIVERSE_SIZE / 2.0;
+
+    (0..n)
+        .map(|_| {
+            let mut movable = Movable::new(&ObjectType::BlackHole)
+                .set_position(rng.random_range(-half..half), rng.random_range(-half..half))
+                .set_velocity(
+                    rng.random_range(-1_000.0..1_000.0),
+                    rng.random_range(-1_000.0..1_000.0),
+                )
+                .set_mass(rng.random_range(2.0..20.0))
+                .build();
+            movable.position.x_prev = movable.position.x - movable.velocity.vx * FRAME;
+            movable.position.y_prev = movable.position.y - movable.velocity.vy * FRAME;
+            movable
+        })
+        .collect()
+}
+
+/// fn bench_gravity times the acceleration / velocity pass
+fn bench_gravity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gravity_pass");
+
+    for n in SIZES {
+        let universe = seeded_universe(n);
+        let refs: Vec<&Movable> = universe.iter().collect();
+
+        group.bench_with_input(BenchmarkId::from_parameter(n), &refs, |b, refs| {
+            b.iter(|| {
+                gravity_pass(
+                    black_box(refs),
+                    FRAME,
+                    &GravityMatrix::default(),
+                    &PhysicsConfig::default(),
+                    &UniverseConfig::default(),
+                )
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// fn bench_collisions times the pairwise collision detection pass
+fn bench_collisions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collision_pass");
+
+    for n in SIZES {
+        let universe = seeded_universe(n);
+        let refs: Vec<&Movable> = universe.iter().collect();
+
+        group.bench_with_input(BenchmarkId::from_parameter(n), &refs, |b, refs| {
+            b.iter(|| collision_pass(black_box(refs)))
+        });
+    }
+
+    group.finish();
+}
+
+/// fn touching_pairs returns n black holes in n / 2 touching pairs, spread out so that
+/// no two pairs touch: a frame of n / 2 independent collisions
+fn touching_pairs(n: usize) -> Vec<Movable> {
+    let spacing = UNIVERSE_SIZE / n as f32;
+
+    (0..n)
+        .map(|i| {
+            let mut movable = Movable::new(&ObjectType::BlackHole)
+                .set_position(
+                    spacing * (i / 2) as f32 - UNIVERSE_SIZE / 2.0,
+                    (i % 2) as f32,
+                )
+                .set_velocity(100.0, 0.0)
+                .set_mass(5.0)
+                .build();
+            movable.position.x_prev = movable.position.x - movable.velocity.vx * FRAME;
+            movable
+        })
+        .collect()
+}
+
+/// fn bench_resolve times the resolution of a frame of independent collisions
+fn bench_resolve(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collision_resolve");
+
+    for n in SIZES {
+        let universe = touching_pairs(n);
+        let refs: Vec<&Movable> = universe.iter().collect();
+        let (_, frame) = collision_pass(&refs);
+
+        group.bench_with_input(BenchmarkId::from_parameter(n), &frame, |b, frame| {
+            b.iter(|| black_box(frame).collect(0.0, &UniverseConfig::default()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_gravity, bench_collisions, bench_resolve);
+criterion_main!(benches);
@@ -0,0 +1,14 @@
+//! Copyright © 2025 Sean Springer
+//! [This program is licensed under the "MIT License"]
+//! Please see the file LICENSE in the source
+//! distribution of this software for license terms.
+//!
+//! Library half of Black Hole Universe. Exposes the simulation so that the
+//! binary (main.rs), the benchmarks (benches/) and tests can all share it.
+//! The Bevy plugin lives at `objects::BlackHoleUniverse`; the frame-by-frame
+//! physics is available without Bevy in `objects::physics`, and the swept collision
+//! test, which works for any type implementing its trait, in `collisions`
+
+pub mod objects;
+
+pub use objects::traits::collisions;
@@ -4,8 +4,9 @@
 //! distribution of this software for license terms.
 
 use bevy::prelude::*;
-
-mod objects;
+use black_hole_universe::objects;
+use black_hole_universe::objects::recording::{Recording, Replay};
+use black_hole_universe::objects::theme::Theme;
 
 /// Main Entry Point
 ///
@@ -13,9 +14,34 @@ mod objects;
 /// and chains plugins. The body of this code all
 /// exists as the `objects::BlackHoleUniverse` plugin.
 /// See plugins.rs in the objects subdirectory
+///
+/// An optional first argument is the path to a recording (saved with the `R` key)
+/// to replay, e.g. `cargo run -- universe-1234.rec`
+///
+/// The color theme is chosen with the BLACK_HOLE_THEME environment variable, e.g.
+/// `BLACK_HOLE_THEME=high-contrast cargo run` (see theme.rs for the presets)
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .add_plugins(objects::BlackHoleUniverse)
-        .run();
+    let mut app = App::new();
+    let mut universe = objects::BlackHoleUniverse::default();
+
+    if let Ok(name) = std::env::var("BLACK_HOLE_THEME") {
+        match Theme::named(&name) {
+            Some(theme) => universe = universe.with_theme(theme),
+            None => eprintln!("unknown theme {name}: expected Classic, High Contrast or Solarized"),
+        }
+    }
+
+    if let Some(path) = std::env::args().nth(1) {
+        match Recording::load(&path) {
+            Ok(recording) => {
+                //the field is built at startup from the plugin: size it like the recording
+                universe = universe
+                    .with_universe_size(recording.universe.width, recording.universe.height);
+                app.insert_resource(Replay(Some(recording)));
+            }
+            Err(error) => eprintln!("could not load recording {path}: {error}"),
+        }
+    }
+
+    app.add_plugins(DefaultPlugins).add_plugins(universe).run();
 }
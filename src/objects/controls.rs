@@ -0,0 +1,153 @@
+//! Controls.rs
+//!
+//! The controls module defines constants and resources shared by the different
+//! input methods (mouse, gamepad, touch) used to place and launch the planet
+
+use crate::objects::gamestate::UNIVERSE_SIZE;
+use crate::objects::hud::REFERENCE_WINDOW_HEIGHT;
+use crate::objects::sliders::Range;
+use bevy::prelude::*;
+
+/// Launch Sensitivity Range:
+/// Slider.min = lower, Slider.max = upper. The slider's midpoint is the
+/// historical default sensitivity of 10.0
+pub const LAUNCH_SENSITIVITY_RNG: Range<f32> = Range {
+    lower: 1.0,
+    upper: 19.0,
+};
+
+/// Speed of the gamepad cursor at full stick deflection, in world units per second
+pub const GAMEPAD_CURSOR_SPEED: f32 = 0.5 * UNIVERSE_SIZE;
+
+/// Radius of the ring drawn to show the gamepad cursor, in world units
+pub const GAMEPAD_CURSOR_RADIUS: f32 = 150.0;
+
+/// Virtual drag speed of the right stick at full deflection, in pixels per second.
+/// The result is scaled by LaunchConfig just like a mouse drag
+pub const GAMEPAD_DRAG_SPEED: f32 = 400.0;
+
+/// GamepadCursor struct: Resource
+///
+/// Stores the location, in World coordinates, of the cursor driven by the
+/// gamepad's left stick. Defaults to the center of the universe
+#[derive(Resource, Default)]
+pub struct GamepadCursor {
+    pub position: Vec2,
+}
+
+/// TouchLaunch struct: Resource
+///
+/// Stores the id of the finger that placed the planet on a touch screen.
+/// Only this finger may aim and launch the planet; None when no touch
+/// placement is in progress
+#[derive(Resource, Default)]
+pub struct TouchLaunch {
+    pub finger: Option<u64>,
+}
+
+/// MouseLaunch struct: Resource
+///
+/// Mouse counterpart to TouchLaunch. Aiming and launching are separate gestures so the
+/// planet can be re-aimed any number of times before committing:
+/// - armed = set by a press that placed the planet or landed on it and cleared by the
+///   following release, so a release (or drag) that began elsewhere is ignored
+/// - on_planet = the armed press landed on an already placed planet
+/// - dragged = the armed press has been dragged, i.e. it is aiming
+///
+/// A drag sets the launch vector afresh; only a click on the planet (pressed and
+/// released without dragging) launches it, see fn release
+#[derive(Resource, Default)]
+pub struct MouseLaunch {
+    pub armed: bool,
+    pub on_planet: bool,
+    pub dragged: bool,
+}
+
+impl MouseLaunch {
+    /// fn press arms a press which placed the planet (on_planet false) or landed on it
+    pub fn press(&mut self, on_planet: bool) {
+        self.armed = true;
+        self.on_planet = on_planet;
+        self.dragged = false;
+    }
+
+    /// fn disarm forgets the current press, e.g. after a failed placement
+    pub fn disarm(&mut self) {
+        *self = MouseLaunch::default();
+    }
+
+    /// fn release
+    ///
+    /// ends the current press, returning true if it should launch the planet: an armed
+    /// click on the planet. A drag only aims, and the click placing the planet never launches
+    pub fn release(&mut self) -> bool {
+        let launch = self.armed && self.on_planet && !self.dragged;
+        self.disarm();
+        launch
+    }
+}
+
+/// LaunchConfig struct: Resource
+///
+/// Scaling applied to a drag (in pixels) to produce the planet's launch velocity.
+/// The sensitivity is normalized by the window height (see fn scale) so that a
+/// drag across the same fraction of the screen launches equally fast on any display
+#[derive(Resource)]
+pub struct LaunchConfig {
+    pub sensitivity: f32,
+}
+
+impl LaunchConfig {
+    /// fn from_slider returns a LaunchConfig whose sensitivity is the Launch
+    /// slider's value [0-1] mapped onto LAUNCH_SENSITIVITY_RNG
+    pub fn from_slider(value: f32) -> Self {
+        LaunchConfig {
+            sensitivity: LAUNCH_SENSITIVITY_RNG.lower
+                + value * (LAUNCH_SENSITIVITY_RNG.upper - LAUNCH_SENSITIVITY_RNG.lower),
+        }
+    }
+
+    /// fn scale returns the factor by which a drag delta (in logical pixels) is
+    /// multiplied to produce velocity. Without a window the sensitivity is used as is
+    pub fn scale(&self, window: Option<&Window>) -> f32 {
+        match window {
+            Some(window) if window.resolution.height() > 0.0 => {
+                self.sensitivity * REFERENCE_WINDOW_HEIGHT / window.resolution.height()
+            }
+            _ => self.sensitivity,
+        }
+    }
+}
+
+/// Default launch sensitivity is arbitrary scaling that feels good
+impl Default for LaunchConfig {
+    fn default() -> Self {
+        LaunchConfig { sensitivity: 10.0 }
+    }
+}
+
+/// fn test_mouse_launch ensures placing and dragging only aim the planet, any number of
+/// times, and that only a click on the placed planet launches it
+#[test]
+fn test_mouse_launch() {
+    let mut mouse = MouseLaunch::default();
+    assert!(!mouse.release());
+
+    //place, then aim with a drag from the placement
+    mouse.press(false);
+    assert!(!mouse.release());
+    mouse.press(false);
+    mouse.dragged = true;
+    assert!(!mouse.release());
+
+    //re-aim from the planet, then confirm with a click on it
+    mouse.press(true);
+    mouse.dragged = true;
+    assert!(!mouse.release());
+    mouse.press(true);
+    assert!(mouse.release());
+
+    mouse.press(true);
+    mouse.disarm();
+    assert!(!mouse.release());
+}
@@ -0,0 +1,196 @@
+//! Edges.rs
+//!
+//! The edges module makes the spherical wrap of the universe visible. An object close
+//! to an edge is announced by a faint arrow on the opposite edge, where it will appear
+//! once it crosses, and two objects pulling on each other through the seam (see
+//! Movable::calculate_acceleration) are joined by a faint line running out of each
+//! edge toward the other. Drawn with gizmos by plugins.rs
+//!
+//! The whole universe fits in the window, so the range is a tenth of the universe's
+//! shorter side rather than a screen width
+
+use crate::objects::gamestate::UniverseConfig;
+use crate::objects::movables::Movable;
+use bevy::prelude::*;
+
+/// fn edge_indicator_range
+///
+/// objects within this distance of an edge of universe are announced on the opposite
+/// edge, and pairs closer than twice this distance through the seam are joined by a
+/// line: a tenth of the shorter side, so that a long thin universe is not all edge
+pub fn edge_indicator_range(universe: &UniverseConfig) -> f32 {
+    universe.shorter_side() / 10.0
+}
+
+/// The length of the arrow drawn for an object right at the edge. A more distant
+/// object gets a shorter arrow
+pub const EDGE_MARKER_LENGTH: f32 = 600.0;
+
+/// EdgeIndicators struct: Resource
+///
+/// Toggled with the `E` key. While true, the edge markers and wrapped pulls are drawn
+#[derive(Resource)]
+pub struct EdgeIndicators(pub bool);
+
+/// Default draws the indicators
+impl Default for EdgeIndicators {
+    fn default() -> Self {
+        EdgeIndicators(true)
+    }
+}
+
+/// EdgeMarker struct
+///
+/// - position = the point on the edge where the object will appear
+/// - direction = the unit vector pointing into the universe from that edge
+/// - strength = how close the object is to crossing, from 0.0 (edge_indicator_range
+///   away) to 1.0 (at the edge)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeMarker {
+    pub position: Vec2,
+    pub direction: Vec2,
+    pub strength: f32,
+}
+
+/// fn edge_markers
+///
+/// returns a marker on the opposite edge of universe for every edge the object at position
+/// is within edge_indicator_range of: none in the middle of the universe, two in a corner
+pub fn edge_markers(position: Vec2, universe: &UniverseConfig) -> Vec<EdgeMarker> {
+    let mut markers = Vec::new();
+    let range = edge_indicator_range(universe);
+
+    for (axis, coordinate, half) in [
+        (Vec2::X, position.x, universe.half().x),
+        (Vec2::Y, position.y, universe.half().y),
+    ] {
+        let distance = half - coordinate.abs();
+        if distance >= range {
+            continue;
+        }
+
+        //heading for the edge on the positive side it comes back in on the negative side
+        let side = coordinate.signum();
+        let across = position - axis * coordinate;
+        markers.push(EdgeMarker {
+            position: across - axis * side * half,
+            direction: axis * side,
+            strength: 1.0 - distance.max(0.0) / range,
+        });
+    }
+
+    markers
+}
+
+/// fn wrapped_pull
+///
+/// returns the two segments of the line joining one and two through the seam, each
+/// running from an object to the edge it pulls the other across, if the shortest path
+/// between them wraps around the universe and is shorter than twice edge_indicator_range.
+/// Otherwise (they pull each other along the visual straight line, or only weakly) None
+pub fn wrapped_pull(
+    one: &Movable,
+    two: &Movable,
+    universe: &UniverseConfig,
+) -> Option<[(Vec2, Vec2); 2]> {
+    let (dx, dy) = one.shortest_offset(two, universe);
+    let offset = Vec2::new(dx, dy);
+    let start = Vec2::new(one.position.x, one.position.y);
+    let end = Vec2::new(two.position.x, two.position.y);
+
+    if (start + offset - end).length() < 1.0
+        || offset.length() >= 2.0 * edge_indicator_range(universe)
+    {
+        return None;
+    }
+
+    Some([
+        (start, to_edge(start, offset, universe)),
+        (end, to_edge(end, -offset, universe)),
+    ])
+}
+
+/// A helper function which returns where the segment from start along offset leaves
+/// the universe, or its end if it stays inside
+fn to_edge(start: Vec2, offset: Vec2, universe: &UniverseConfig) -> Vec2 {
+    let exit = |from: f32, along: f32, half: f32| {
+        if along == 0.0 {
+            1.0
+        } else {
+            ((along.signum() * half - from) / along).clamp(0.0, 1.0)
+        }
+    };
+
+    let half = universe.half();
+    start + offset * exit(start.x, offset.x, half.x).min(exit(start.y, offset.y, half.y))
+}
+
+/// fn test_edge_markers ensures objects near an edge are announced on the opposite
+/// edge, brighter the closer they are, and objects in the middle are not. The range
+/// follows the shorter side of a rectangular universe
+#[test]
+fn test_edge_markers() {
+    use crate::objects::gamestate::UNIVERSE_SIZE;
+
+    let universe = UniverseConfig::default();
+    let half = universe.half().x;
+    let range = edge_indicator_range(&universe);
+
+    assert!(edge_markers(Vec2::ZERO, &universe).is_empty());
+    assert!(edge_markers(Vec2::new(half - range - 1.0, 0.0), &universe).is_empty());
+
+    let near = edge_markers(Vec2::new(half - range / 2.0, 300.0), &universe);
+    assert_eq!(near.len(), 1);
+    assert_eq!(near[0].position, Vec2::new(-half, 300.0));
+    assert_eq!(near[0].direction, Vec2::X);
+    assert!((near[0].strength - 0.5).abs() < 1e-4);
+
+    let corner = edge_markers(Vec2::new(-half + 10.0, -half + 20.0), &universe);
+    assert_eq!(corner.len(), 2);
+    assert_eq!(corner[0].position, Vec2::new(half, -half + 20.0));
+    assert_eq!(corner[0].direction, -Vec2::X);
+    assert_eq!(corner[1].position, Vec2::new(-half + 10.0, half));
+    assert!(corner[1].strength > near[0].strength);
+
+    let narrow = UniverseConfig {
+        width: UNIVERSE_SIZE / 2.0,
+        height: 2.0 * UNIVERSE_SIZE,
+    };
+    assert_eq!(edge_indicator_range(&narrow), UNIVERSE_SIZE / 20.0);
+    let outside = Vec2::new(0.0, narrow.half().y - 1.5 * edge_indicator_range(&narrow));
+    assert!(edge_markers(outside, &narrow).is_empty());
+}
+
+/// fn test_wrapped_pull ensures a pair close through the seam is joined by two segments
+/// ending on the edges, while a pair pulling along the straight line is not
+#[test]
+fn test_wrapped_pull() {
+    use crate::objects::movables::ObjectType;
+
+    let universe = UniverseConfig::default();
+    let half = universe.half().x;
+    let at = |x: f32, y: f32| {
+        Movable::new(&ObjectType::BlackHole)
+            .set_position(x, y)
+            .build()
+    };
+
+    let [(from, to), (back, edge)] =
+        wrapped_pull(&at(half - 100.0, 0.0), &at(-half + 200.0, 50.0), &universe)
+            .expect("the pair is close through the right edge");
+    assert_eq!(from, Vec2::new(half - 100.0, 0.0));
+    assert!((to.x - half).abs() < 1e-2);
+    assert!(to.y > 0.0 && to.y < 50.0);
+    assert_eq!(back, Vec2::new(-half + 200.0, 50.0));
+    assert!((edge.x + half).abs() < 1e-2);
+
+    assert!(wrapped_pull(&at(-500.0, 0.0), &at(500.0, 0.0), &universe).is_none());
+    assert!(
+        wrapped_pull(
+            &at(half - 10.0, 0.0),
+            &at(-half + 2.0 * edge_indicator_range(&universe), 0.0),
+            &universe
+        )
+        .is_none()
+    );
+}
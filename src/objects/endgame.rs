@@ -0,0 +1,161 @@
+//! Endgame.rs
+//!
+//! The endgame module detects the imminent final merge of a two-body universe: the
+//! merge which leaves a single object and so ends the game. Used by plugins.rs to
+//! show a HUD notice just before the universe reaches heat death and, in bullet time,
+//! to play that merge in slow motion
+
+use crate::objects::gamestate::UniverseConfig;
+use crate::objects::movables::Movable;
+use crate::objects::traits::collisions::CollisionDetection;
+use bevy::prelude::*;
+
+/// The final merge is only announced once the two objects are within this
+/// multiple of their contact distance
+pub const FINAL_MERGE_RANGE_FACTOR: f32 = 5.0;
+
+/// The relative speed of the virtual clock while bullet time plays the final merge
+pub const BULLET_TIME_SCALE: f32 = 0.2;
+
+/// The real seconds over which bullet time ramps between normal speed and BULLET_TIME_SCALE
+pub const BULLET_TIME_RAMP_SECS: f32 = 0.5;
+
+/// FinalMergeNotice struct: Component
+///
+/// Used for querying the Bevy Text entity which announces the imminent final merge
+#[derive(Component)]
+pub struct FinalMergeNotice;
+
+/// fn final_merge_imminent
+///
+/// returns true if one and two are about to collide: they are within
+/// FINAL_MERGE_RANGE_FACTOR contact distances, their separation is shrinking and,
+/// continuing in a straight line, their closest approach is within contact distance.
+/// Gravity only bends their paths closer together, so a straight line hit is certain,
+/// while objects in a stable orbit (whose separation is not shrinking, or whose
+/// straight line paths would miss) are not announced. The separation is measured along
+/// the shortest path in the spherical universe (see Movable::shortest_offset), so a merge
+/// across an edge is announced as well
+pub fn final_merge_imminent(one: &Movable, two: &Movable, universe: &UniverseConfig) -> bool {
+    let contact = one.contact_distance(two);
+    let offset = Vec2::from(one.shortest_offset(two, universe));
+    if offset.length() > FINAL_MERGE_RANGE_FACTOR * contact {
+        return false;
+    }
+
+    let closing = Vec2::new(
+        two.velocity.vx - one.velocity.vx,
+        two.velocity.vy - one.velocity.vy,
+    );
+
+    //the separation shrinks while the relative velocity points back toward one
+    let approach = offset.dot(closing);
+    if approach >= 0.0 {
+        return false;
+    }
+
+    //time of the straight line closest approach and the separation at that time
+    let time = -approach / closing.length_squared();
+    (offset + closing * time).length() <= contact
+}
+
+/// BulletTime struct: Resource
+///
+/// The automatic slow motion of the final merge, toggled with the `B` key and off by
+/// default. The virtual clock drives the fixed physics clock, so slowing it slows the
+/// HUD clocks along with the motion while every physics step stays the same
+/// - enabled = whether the final merge plays in slow motion
+/// - scale = the slow down of the virtual clock's relative speed, 1.0 at normal speed
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct BulletTime {
+    pub enabled: bool,
+    pub scale: f32,
+}
+
+/// Default is off, at normal speed
+impl Default for BulletTime {
+    fn default() -> Self {
+        BulletTime {
+            enabled: false,
+            scale: 1.0,
+        }
+    }
+}
+
+impl BulletTime {
+    /// fn update
+    ///
+    /// given whether the final merge is imminent and the real seconds elapsed since the
+    /// last update, ramps scale toward BULLET_TIME_SCALE (while enabled and imminent) or
+    /// back toward 1.0 (otherwise, e.g. once the merge has resolved) and returns it
+    pub fn update(&mut self, imminent: bool, real_delta: f32) -> f32 {
+        let target = if self.enabled && imminent {
+            BULLET_TIME_SCALE
+        } else {
+            1.0
+        };
+        let step = (1.0 - BULLET_TIME_SCALE) * real_delta / BULLET_TIME_RAMP_SECS;
+        self.scale = if self.scale > target {
+            (self.scale - step).max(target)
+        } else {
+            (self.scale + step).min(target)
+        };
+        self.scale
+    }
+}
+
+/// fn test_final_merge_imminent ensures a closing pair is announced, also across an edge
+/// of the universe, while a receding pair, a distant pair and a pair in a circular orbit
+/// are not
+#[test]
+fn test_final_merge_imminent() {
+    use crate::objects::movables::ObjectType;
+
+    let universe = UniverseConfig::default();
+    let pair_at = |still_x: f32, x: f32, vx: f32, vy: f32| {
+        let still = Movable::new(&ObjectType::BlackHole)
+            .set_position(still_x, 0.0)
+            .set_size(10.0, 30.0)
+            .build();
+        let moving = Movable::new(&ObjectType::BlackHole)
+            .set_position(x, 0.0)
+            .set_velocity(vx, vy)
+            .set_size(10.0, 30.0)
+            .build();
+        final_merge_imminent(&still, &moving, &universe)
+    };
+    let pair = |x: f32, vx: f32, vy: f32| pair_at(0.0, x, vx, vy);
+
+    //200 apart across the right edge, i.e. moving sits 200 to the right of still
+    let edge = universe.width / 2.0;
+    assert!(pair_at(edge - 50.0, 150.0 - edge, -100.0, 0.0));
+    assert!(!pair_at(edge - 50.0, 150.0 - edge, 100.0, 0.0));
+
+    assert!(pair(200.0, -100.0, 0.0));
+    assert!(pair(200.0, -100.0, 20.0));
+    assert!(!pair(200.0, 100.0, 0.0));
+    assert!(!pair(FINAL_MERGE_RANGE_FACTOR * 60.0 + 1.0, -100.0, 0.0));
+    assert!(!pair(200.0, 0.0, 100.0));
+    assert!(!pair(200.0, -10.0, 100.0));
+}
+
+/// fn test_bullet_time ensures the clock only slows while enabled and the final merge is
+/// imminent, ramping down to BULLET_TIME_SCALE and back up to normal speed afterwards
+#[test]
+fn test_bullet_time() {
+    let mut bullet = BulletTime::default();
+    assert_eq!(bullet.update(true, 1.0), 1.0);
+
+    bullet.enabled = true;
+    let early = bullet.update(true, 0.5 * BULLET_TIME_RAMP_SECS);
+    assert!(early < 1.0 && early > BULLET_TIME_SCALE);
+    assert_eq!(
+        bullet.update(true, BULLET_TIME_RAMP_SECS),
+        BULLET_TIME_SCALE
+    );
+    assert_eq!(bullet.update(true, 1.0), BULLET_TIME_SCALE);
+
+    //the merge resolved
+    assert!(bullet.update(false, 0.5 * BULLET_TIME_RAMP_SECS) < 1.0);
+    assert_eq!(bullet.update(false, BULLET_TIME_RAMP_SECS), 1.0);
+}
@@ -0,0 +1,100 @@
+//! Entropy.rs
+//!
+//! The entropy module measures the universe's progress toward heat death: the
+//! state of maximal entropy in which a single black hole remains. Used by
+//! plugins.rs to fill the entropy bar in the HUD
+
+use crate::objects::gamestate::MINIMUM_OBJECTS;
+use crate::objects::snapshot::UniverseSnapshot;
+use bevy::prelude::*;
+
+/// The share of the entropy meter driven by the loss of kinetic energy. The
+/// remainder is driven by the number of merges that have taken place
+pub const HEAT_WEIGHT: f32 = 0.2;
+
+/// EntropyBar struct: Component
+///
+/// Used for querying the Bevy Node whose width fills with the current entropy
+#[derive(Component)]
+pub struct EntropyBar;
+
+/// EntropyMeter struct: Resource
+///
+/// The universe at launch, against which entropy is measured
+/// - initial_count = number of objects at launch
+/// - initial_energy = total kinetic energy at launch
+#[derive(Resource, Default)]
+pub struct EntropyMeter {
+    pub initial_count: usize,
+    pub initial_energy: f32,
+}
+
+impl EntropyMeter {
+    /// fn baseline
+    ///
+    /// records the given snapshot as the starting point of the measurement.
+    /// Called every frame prior to launch so the baseline is the launched universe
+    pub fn baseline(&mut self, snapshot: &UniverseSnapshot) {
+        self.initial_count = snapshot.body_count;
+        self.initial_energy = snapshot.kinetic_energy;
+    }
+
+    /// fn fill
+    ///
+    /// returns the entropy of the snapshot between 0.0 and 1.0. The merge term is
+    /// 1 - (count - 1) / (initial_count - 1) and the heat term is the fraction of the
+    /// initial kinetic energy which has been lost. Exactly 1.0 is only reached once
+    /// fewer than MINIMUM_OBJECTS remain, which is when the game is over
+    pub fn fill(&self, snapshot: &UniverseSnapshot) -> f32 {
+        if self.initial_count < MINIMUM_OBJECTS {
+            return 0.0;
+        }
+        if snapshot.body_count < MINIMUM_OBJECTS {
+            return 1.0;
+        }
+
+        let remaining = (snapshot.body_count - 1) as f32 / (self.initial_count - 1) as f32;
+        let merged = (1.0 - remaining).clamp(0.0, 1.0);
+        let cooled = if self.initial_energy > 0.0 {
+            (1.0 - snapshot.kinetic_energy / self.initial_energy).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        //merged < 1.0 while at least two objects remain, so the blend stays below 1.0
+        merged * (1.0 - HEAT_WEIGHT) + cooled * HEAT_WEIGHT
+    }
+}
+
+/// fn test_fill ensures the meter starts empty, climbs with each merge and
+/// only reaches full once a single object remains
+#[test]
+fn test_fill() {
+    let snapshot = |body_count, kinetic_energy| UniverseSnapshot {
+        body_count,
+        black_hole_count: body_count,
+        planet_count: 0,
+        total_mass: 0.0,
+        center_of_mass: Vec2::ZERO,
+        total_momentum: Vec2::ZERO,
+        kinetic_energy,
+        elapsed: 0.0,
+    };
+
+    let mut meter = EntropyMeter::default();
+    assert_eq!(meter.fill(&snapshot(5, 100.0)), 0.0);
+
+    meter.baseline(&snapshot(5, 100.0));
+    assert_eq!(meter.fill(&snapshot(5, 100.0)), 0.0);
+
+    let mut previous = 0.0;
+    for count in (2..5).rev() {
+        let fill = meter.fill(&snapshot(count, 100.0));
+        assert!(fill > previous && fill < 1.0);
+        previous = fill;
+    }
+
+    //losing every bit of kinetic energy still leaves the meter short of full
+    assert!(meter.fill(&snapshot(2, 0.0)) < 1.0);
+    assert_eq!(meter.fill(&snapshot(1, 100.0)), 1.0);
+}
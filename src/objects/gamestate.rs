@@ -4,11 +4,103 @@
 
 use bevy::prelude::*;
 
-/// The width and height of the universe grid. Used
-/// to devide the world into grid points as well as set the
-/// orthographic projection of the camera
+/// The width and height of the default (square) universe grid, see UniverseConfig.
+/// Universes are generated at this size and stretched to fit the configured one
 pub const UNIVERSE_SIZE: f32 = 25_000.0f32;
 
+/// UniverseConfig struct
+///
+/// The dimensions of the spherical universe, centered on the origin. Each axis wraps
+/// on its own: an object leaving past the right edge comes back in on the left, and
+/// past the top on the bottom
+/// - width = the size of the field along x
+/// - height = the size of the field along y
+///
+/// Default is a square UNIVERSE_SIZE on a side. Set with `BlackHoleUniverse::with_universe_size`
+/// and kept on the UniverseLayout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UniverseConfig {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Default is the square UNIVERSE_SIZE universe
+impl Default for UniverseConfig {
+    fn default() -> Self {
+        UniverseConfig {
+            width: UNIVERSE_SIZE,
+            height: UNIVERSE_SIZE,
+        }
+    }
+}
+
+impl UniverseConfig {
+    /// fn size returns the width and height of the universe
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height)
+    }
+
+    /// fn half returns the distance from the center to the right and top edges
+    pub fn half(&self) -> Vec2 {
+        self.size() / 2.0
+    }
+
+    /// fn contains returns true if position lies on the field (edges included)
+    pub fn contains(&self, position: Vec2) -> bool {
+        let half = self.half();
+        position.x.abs() <= half.x && position.y.abs() <= half.y
+    }
+
+    /// fn wrap returns position wrapped back onto the field if it lies past one of the
+    /// edges. The wrap is modular, so a position any number of universe widths past an
+    /// edge still lands on the field. Positions on the field are returned untouched
+    pub fn wrap(&self, position: Vec2) -> Vec2 {
+        //positions on the field are left untouched: the modulo would round them
+        let wrapped = |value: f32, size: f32| {
+            let boundary = 0.5 * size;
+            if (-boundary..=boundary).contains(&value) {
+                value
+            } else {
+                (value + boundary).rem_euclid(size) - boundary
+            }
+        };
+
+        Vec2::new(
+            wrapped(position.x, self.width),  //off to the right or left
+            wrapped(position.y, self.height), //off to the top or bottom
+        )
+    }
+
+    /// fn fit stretches a position in the square UNIVERSE_SIZE universe onto this one
+    pub fn fit(&self, position: Vec2) -> Vec2 {
+        position * self.size() / UNIVERSE_SIZE
+    }
+
+    /// fn shorter_side returns the smaller of the width and height. Lengths which must
+    /// fit the universe along either axis (e.g. see fn max_radius) are derived from it
+    pub fn shorter_side(&self) -> f32 {
+        self.width.min(self.height)
+    }
+
+    /// fn max_radius returns the largest radius an object may have in this universe,
+    /// MAX_RADIUS_FRACTION of its shorter side (see Movable::clamp_radius)
+    pub fn max_radius(&self) -> f32 {
+        MAX_RADIUS_FRACTION * self.shorter_side()
+    }
+}
+
+/// No object may have a radius larger than this fraction of the shorter side of the universe
+pub const MAX_RADIUS_FRACTION: f32 = 0.25f32;
+
+/// The minimum gap between the placed planet's hitbox and any black hole's
+/// hitbox at launch. Black holes inside this bubble are nudged outward so
+/// the simulation does not begin with an instant collision
+pub const PLANET_CLEARANCE: f32 = 1_000.0f32;
+
+/// The game is over once the universe holds fewer than this many objects
+/// and so a launch requires at least this many
+pub const MINIMUM_OBJECTS: usize = 2;
+
 /// ThePlanet struct: Component
 ///
 /// Bevy component for tracking and querying the user-placed
@@ -16,6 +108,208 @@ pub const UNIVERSE_SIZE: f32 = 25_000.0f32;
 #[derive(Component)]
 pub struct ThePlanet;
 
+/// Ghost struct: Component
+///
+/// Marks an entity which draws a copy of the parent object offset by one universe
+/// width/height, so that an object straddling an edge of the spherical universe is
+/// visible on both sides of that edge. Ghosts are purely visual: they carry no Movable.
+/// Ghost is a relationship to its parent so that despawning the parent despawns its ghosts
+#[derive(Component)]
+#[relationship(relationship_target = Ghosts)]
+pub struct Ghost {
+    #[relationship]
+    pub parent: Entity,
+    pub offset: Vec2,
+}
+
+/// Ghosts struct: Component
+///
+/// Automatically maintained on every object which has ghosts: the Ghost entities drawn for it.
+/// linked_spawn despawns the ghosts along with the object (see fn destroy_object in plugins.rs)
+#[derive(Component)]
+#[relationship_target(relationship = Ghost, linked_spawn)]
+pub struct Ghosts(Vec<Entity>);
+
+/// LaunchHint struct: Component
+///
+/// Bevy component for tracking and querying the HUD text which explains why
+/// a launch is currently not possible
+#[derive(Component)]
+pub struct LaunchHint;
+
+/// LaunchPrompt struct: Component
+///
+/// Bevy component for tracking and querying the HUD text which tells the player
+/// the next step required to start the simulation (see GameState::prompt)
+#[derive(Component)]
+pub struct LaunchPrompt;
+
+/// How long, in seconds, the placement failure notice remains on screen
+pub const PLACEMENT_NOTICE_SECS: f64 = 2.0;
+
+/// PlacementNotice struct: Component
+///
+/// Bevy component for tracking and querying the HUD text which tells the player
+/// that a click or tap could not place the planet and should be tried again
+#[derive(Component)]
+pub struct PlacementNotice;
+
+/// PlacementFeedback struct: Resource
+///
+/// - failed = a placement failed since the notice was last updated
+/// - hide_at = elapsed seconds at which the PlacementNotice should be hidden
+#[derive(Resource, Default)]
+pub struct PlacementFeedback {
+    pub failed: bool,
+    pub hide_at: f64,
+}
+
+impl PlacementFeedback {
+    /// fn fail logs a failed placement; the notice is shown on its next update
+    pub fn fail(&mut self) {
+        warn!("the planet could not be placed: the click is outside of the camera's view");
+        self.failed = true;
+    }
+
+    /// fn update returns whether the notice should be shown at time now, restarting
+    /// the notice's PLACEMENT_NOTICE_SECS if a placement has failed since the last update
+    pub fn update(&mut self, now: f64) -> bool {
+        if self.failed {
+            self.failed = false;
+            self.hide_at = now + PLACEMENT_NOTICE_SECS;
+        }
+
+        now < self.hide_at
+    }
+}
+
+/// BlackHoleThreshold struct: Resource
+///
+/// The minimum mass a merge product needs to form (or remain) a black hole.
+/// Lighter merge products become planets instead. Defaults to 0.0 so that
+/// every merge produces a black hole
+#[derive(Resource, Default)]
+pub struct BlackHoleThreshold(pub f32);
+
+/// InfluenceRings struct: Resource
+///
+/// Toggled with the `I` key. While true, every black hole is outlined by a ring
+/// at the distance from its center at which it would collide with the planet
+#[derive(Resource, Default)]
+pub struct InfluenceRings(pub bool);
+
+/// SandboxMode struct: Resource
+///
+/// Toggled with the `S` key. While true the game never ends: the universe keeps
+/// running down to (and past) a single object, and after launch clicking into the
+/// universe adds a black hole (left click) or a planet (right click)
+#[derive(Resource, Default)]
+pub struct SandboxMode(pub bool);
+
+/// SandboxLabel struct: Component
+///
+/// Bevy component for tracking and querying the HUD text which displays the SandboxMode
+#[derive(Component)]
+pub struct SandboxLabel;
+
+/// PlanetConsumed struct: Message
+///
+/// Written by the collision system whenever a merge swallows the player's planet (see
+/// ThePlanet): fragments and sandbox planets are not followed
+/// - time = seconds the planet survived, measured from launch
+/// - by_mass = the combined mass of the black holes which consumed it
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct PlanetConsumed {
+    pub time: f64,
+    pub by_mass: f32,
+}
+
+/// PlanetPlaced struct: Message
+///
+/// Written whenever the planet is placed (or re-placed) prior to launch, by any input method
+/// - position = where the planet was placed, in World coordinates
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct PlanetPlaced {
+    pub position: Vec2,
+}
+
+/// GameStarted struct: Message
+///
+/// Written once per run when the planet is launched and the simulation begins
+/// - time = the fixed physics clock at launch, see GameState::start_time
+/// - position / velocity = the planet's launch, as kept in GameState::launch
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct GameStarted {
+    pub time: f64,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// Merge struct: Message
+///
+/// Written by the collision system for the object a merge produces. A collision which
+/// splits or bounces its objects apart writes none (see CollisionOutcome)
+/// - result_mass = the mass of the produced object
+/// - location = where it was produced, in World coordinates
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct Merge {
+    pub result_mass: f32,
+    pub location: Vec2,
+}
+
+/// GameOver struct: Message
+///
+/// Written once per run when fewer than MINIMUM_OBJECTS remain
+/// - survival_time = seconds from launch until the game ended
+/// - peak_count = the most objects on the field at once during the run
+/// - planet_survived = whether the planet outlasted the universe (see PlanetFate otherwise)
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct GameOver {
+    pub survival_time: f64,
+    pub peak_count: usize,
+    pub planet_survived: bool,
+}
+
+/// PlanetFate struct: Resource
+///
+/// The last PlanetConsumed of the current run, kept past the lifetime of the message
+/// so the game over display can describe the planet's demise. Cleared on restart
+#[derive(Resource, Default)]
+pub struct PlanetFate(pub Option<PlanetConsumed>);
+
+impl PlanetFate {
+    /// fn describe
+    ///
+    /// the game over summary of the planet: survived (world_alive) or how it was consumed
+    pub fn describe(&self, world_alive: bool) -> String {
+        match (world_alive, self.0) {
+            (true, _) => String::from("Your planet survived to the end!"),
+            (false, Some(consumed)) => format!(
+                "Your planet survived {:.1}s before being consumed by a mass-{:.0} black hole",
+                consumed.time, consumed.by_mass
+            ),
+            (false, None) => String::from("Your planet was lost"),
+        }
+    }
+}
+
+/// PlanetFateLabel struct: Component
+///
+/// Used for querying the Bevy Text entity which displays PlanetFate::describe at game over
+#[derive(Component)]
+pub struct PlanetFateLabel;
+
+/// RestartChoice enum: Component
+///
+/// Which universe a game over button restarts the simulation with
+/// 1. Same - retries the identical universe: the same seed and settings, to practice a route
+/// 2. New - rerolls the seed for a fresh universe generated from the current settings
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartChoice {
+    Same,
+    New,
+}
+
 /// GameState struct: Resource
 ///
 /// GameState contains the overall state of the simulation. Because this
@@ -25,6 +319,9 @@ pub struct ThePlanet;
 /// 3. game_started - user must place a planet and flick it to give it velocity to start simulation
 /// 4. planet_placed - once user places planet, the flick motion will be captured to give it velocity
 /// 5. start_time - seconds marker initiating the beginning of the simulation for calc elapsed times
+///    (measured on the fixed physics clock, Time<Fixed>, so elapsed times are reproducible)
+/// 6. launch - the planet's (position, velocity) at launch, kept for saving a Recording
+/// 7. restart_clicked - which game over button was pressed, if any (see fn check_for_restart)
 #[derive(Resource)]
 pub struct GameState {
     pub world_alive: bool,
@@ -32,7 +329,8 @@ pub struct GameState {
     pub game_started: bool,
     pub planet_placed: bool,
     pub start_time: f64,
-    pub restart_clicked: bool,
+    pub restart_clicked: Option<RestartChoice>,
+    pub launch: Option<(Vec2, Vec2)>,
 }
 
 /// Standard constructor provide only which defaults to the pre-started game state
@@ -44,7 +342,22 @@ impl GameState {
             game_started: false,
             planet_placed: false,
             start_time: 0.0,
-            restart_clicked: false,
+            restart_clicked: None,
+            launch: None,
+        }
+    }
+
+    /// fn prompt
+    ///
+    /// the instruction for the player's next step prior to launch, or None
+    /// once the simulation has started
+    pub fn prompt(&self) -> Option<&'static str> {
+        if self.game_started {
+            None
+        } else if self.planet_placed {
+            Some("Drag to aim your planet (again to re-aim), then click it to launch")
+        } else {
+            Some("Click to place your planet, then drag to aim")
         }
     }
 
@@ -54,6 +367,63 @@ impl GameState {
         self.game_started = false;
         self.planet_placed = false;
         self.start_time = 0.0;
-        self.restart_clicked = false;
+        self.restart_clicked = None;
+        self.launch = None;
+    }
+}
+
+/// Default is the pre-started game state, see GameState::new
+impl Default for GameState {
+    fn default() -> Self {
+        GameState::new()
     }
 }
+
+/// fn test_ghosts_despawned ensures that re-placing a planet many times never leaves
+/// behind the ghosts of previous planets
+#[test]
+fn test_ghosts_despawned() {
+    let mut world = World::new();
+
+    for _ in 0..10 {
+        let planet = world.spawn(ThePlanet).id();
+        world.spawn(Ghost {
+            parent: planet,
+            offset: Vec2::new(UNIVERSE_SIZE, 0.0),
+        });
+        world.despawn(planet);
+    }
+
+    assert_eq!(world.query::<&Ghost>().iter(&world).count(), 0);
+    assert_eq!(world.query::<&ThePlanet>().iter(&world).count(), 0);
+}
+
+/// fn test_placement_feedback ensures a failed placement shows the notice for
+/// PLACEMENT_NOTICE_SECS and no longer
+#[test]
+fn test_placement_feedback() {
+    let mut feedback = PlacementFeedback::default();
+    assert!(!feedback.update(1.0));
+
+    feedback.fail();
+    assert!(feedback.update(1.0));
+    assert!(feedback.update(1.0 + PLACEMENT_NOTICE_SECS / 2.0));
+    assert!(!feedback.update(1.0 + PLACEMENT_NOTICE_SECS));
+}
+
+/// fn test_planet_fate ensures the game over summary reports the last consumption
+#[test]
+fn test_planet_fate() {
+    let mut fate = PlanetFate::default();
+    assert_eq!(fate.describe(false), "Your planet was lost");
+
+    fate.0 = Some(PlanetConsumed {
+        time: 42.33,
+        by_mass: 38.2,
+    });
+    assert_eq!(
+        fate.describe(false),
+        "Your planet survived 42.3s before being consumed by a mass-38 black hole"
+    );
+    assert_eq!(fate.describe(true), "Your planet survived to the end!");
+}
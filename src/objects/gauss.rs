@@ -5,8 +5,12 @@
 //! - setting the mean and stdev of the Normal distribution
 //! - randomly sample the built Normal distribution
 //! - enfouce boundary conditions on the sampled value
+//! - reproduce the same sequence of samples from a seed
+//!
+//! GaussMixture extends this to 2D points drawn from several Normal clusters
 
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use rand_distr::{Distribution, Normal};
 
 /// GaussBoundary Enum
@@ -33,13 +37,13 @@ pub enum GaussBoundary {
 /// Gauss Struct
 ///
 /// Main object of this module and is used to setup and generate Normal distribution sampling
-/// - generator = rand::rngs::thread::ThreadRng used to reference the local random number generator
+/// - generator = rand::rngs::StdRng random number generator, either seeded (reproducible) or from OS entropy
 /// - distrubtion = Normal<f32> gauss function of form: a*exp(- x^2 / (2*std^2))
 /// - boundary = GaussBoundary defining limits and how to enforce the boundary conditions
 ///
 /// Note: all members are private; use impl methods to interact
 pub struct Gauss {
-    generator: ThreadRng,
+    generator: StdRng,
     distribution: Normal<f32>,
     boundary: GaussBoundary,
 }
@@ -55,10 +59,21 @@ impl Gauss {
     /// - std dicates the standard deviation (width) of the Normal distribution
     /// - boundary defines an allowed range and how to handle values sampled outside of said range
     pub fn new(mean: f32, std: f32, boundary: GaussBoundary) -> Gauss {
+        Gauss::with_generator(mean, std, boundary, StdRng::from_os_rng())
+    }
+
+    /// fn seeded returns a Gauss struct exactly like fn new, except that the samples
+    /// are drawn from a generator seeded with seed: the same seed always reproduces
+    /// the same sequence of samples
+    pub fn seeded(mean: f32, std: f32, boundary: GaussBoundary, seed: u64) -> Gauss {
+        Gauss::with_generator(mean, std, boundary, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_generator(mean: f32, std: f32, boundary: GaussBoundary, generator: StdRng) -> Gauss {
         assert!(std > 0.0);
 
         Gauss {
-            generator: rand::rng(),
+            generator,
             distribution: Normal::new(mean, std).unwrap(),
             boundary,
         }
@@ -68,7 +83,15 @@ impl Gauss {
     /// conditions enforced, if applicable.
     pub fn sample(&mut self) -> f32 {
         let value = self.distribution.sample(&mut self.generator);
-        match self.boundary {
+        self.boundary.enforce(value)
+    }
+}
+
+/// impl GaussBoundary block
+impl GaussBoundary {
+    /// fn enforce returns value with the boundary conditions applied
+    pub fn enforce(&self, value: f32) -> f32 {
+        match *self {
             GaussBoundary::None => value,
             GaussBoundary::Lower(n) => value.max(n),
             GaussBoundary::Upper(n) => value.min(n),
@@ -89,6 +112,66 @@ impl Gauss {
     }
 }
 
+/// GaussMixture Struct
+///
+/// Samples 2D points from a mixture of equally weighted Normal clusters
+/// - generator = either seeded (reproducible) or from OS entropy, shared by the cluster choice and offsets
+/// - centers = the (x, y) center of every cluster
+/// - distribution = Normal<f32> offset of a point from its cluster's center, on each axis
+/// - boundary = GaussBoundary enforced on each coordinate of the sampled point
+///
+/// With a single center no cluster choice is drawn, so the samples are exactly those of a
+/// Gauss with the same seed sampled twice per point
+pub struct GaussMixture {
+    generator: StdRng,
+    centers: Vec<(f32, f32)>,
+    distribution: Normal<f32>,
+    boundary: GaussBoundary,
+}
+
+/// impl GaussMixture block
+impl GaussMixture {
+    /// fn seeded returns a GaussMixture of clusters around each of centers, each with
+    /// standard deviation std. The same seed always reproduces the same sequence of points
+    pub fn seeded(
+        centers: Vec<(f32, f32)>,
+        std: f32,
+        boundary: GaussBoundary,
+        seed: u64,
+    ) -> GaussMixture {
+        assert!(std > 0.0 && !centers.is_empty());
+
+        GaussMixture {
+            generator: StdRng::seed_from_u64(seed),
+            centers,
+            distribution: Normal::new(0.0, std).unwrap(),
+            boundary,
+        }
+    }
+
+    /// fn sample returns an (x, y) point from a randomly chosen cluster, with boundary
+    /// conditions enforced, if applicable.
+    pub fn sample(&mut self) -> (f32, f32) {
+        self.sample_with_center().0
+    }
+
+    /// fn sample_with_center is fn sample which also returns the center of the cluster
+    /// the point was drawn from: ((x, y), (cx, cy)). Both draw the same sequence of points
+    pub fn sample_with_center(&mut self) -> ((f32, f32), (f32, f32)) {
+        let (cx, cy) = match self.centers.len() {
+            1 => self.centers[0],
+            n => self.centers[self.generator.random_range(0..n)],
+        };
+        let x = cx + self.distribution.sample(&mut self.generator);
+        let y = cy + self.distribution.sample(&mut self.generator);
+
+        (
+            (self.boundary.enforce(x), self.boundary.enforce(y)),
+            (cx, cy),
+        )
+    }
+}
+
 /// fn test_boundaries runs a series of test to ensure the proper functionality of the
 /// gauss sampler boundary enforcement.
 ///
@@ -107,3 +190,38 @@ fn test_boundaries() {
         assert!(wrap_g.sample() >= -0.1 && wrap_g.sample() <= 0.1);
     }
 }
+
+/// fn test_seeded ensures that two samplers built from the same seed produce
+/// identical sequences, the basis of reproducible universes
+#[test]
+fn test_seeded() {
+    let mut one = Gauss::seeded(0.0, 1.0, GaussBoundary::None, 42);
+    let mut two = Gauss::seeded(0.0, 1.0, GaussBoundary::None, 42);
+
+    for _ in 0..100 {
+        assert_eq!(one.sample(), two.sample());
+    }
+}
+
+/// fn test_mixture ensures a single cluster mixture reproduces Gauss exactly and that
+/// the points of a tight mixture land near one of its centers, the one they were drawn from
+#[test]
+fn test_mixture() {
+    let mut single = GaussMixture::seeded(vec![(0.0, 0.0)], 1.0, GaussBoundary::None, 9);
+    let mut gauss = Gauss::seeded(0.0, 1.0, GaussBoundary::None, 9);
+    for _ in 0..100 {
+        assert_eq!(single.sample(), (gauss.sample(), gauss.sample()));
+    }
+
+    let centers = vec![(-100.0, 0.0), (100.0, 0.0)];
+    let mut mixture = GaussMixture::seeded(centers.clone(), 1.0, GaussBoundary::None, 9);
+    let mut hits = [0; 2];
+    for _ in 0..100 {
+        let ((x, y), center) = mixture.sample_with_center();
+        let nearest = if x < 0.0 { 0 } else { 1 };
+        assert!((x - centers[nearest].0).abs() < 10.0 && y.abs() < 10.0);
+        assert_eq!(center, centers[nearest]);
+        hits[nearest] += 1;
+    }
+    assert!(hits[0] > 0 && hits[1] > 0);
+}
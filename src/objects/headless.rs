@@ -0,0 +1,106 @@
+//! Headless.rs
+//!
+//! The headless module runs the simulation without a window or a GPU, for integration
+//! tests on CI. HeadlessUniverse drives the PhysicsPlugin (see plugins.rs) on Bevy's
+//! MinimalPlugins: its clock advances by exactly one physics step per update, so a test
+//! can insert objects, run a fixed number of frames and assert on the resulting Movables.
+//! It can also run at any other display rate, as a real window would (see fn at_frame_rate)
+
+use crate::objects::PhysicsPlugin;
+use crate::objects::gamestate::GameState;
+use crate::objects::movables::Movable;
+use crate::objects::physics::PHYSICS_HZ;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+
+/// HeadlessUniverse struct
+///
+/// - app = a Bevy App of MinimalPlugins and the PhysicsPlugin, whose clock
+///   is stepped by hand. Exposed for anything the methods below do not cover, e.g.
+///   reading the MergeHistory resource
+///
+/// The universe is launched with exactly the objects given (see fn launch): nothing is
+/// generated from the sliders, which do not exist without the HUD
+pub struct HeadlessUniverse {
+    pub app: App,
+}
+
+impl HeadlessUniverse {
+    /// fn new
+    ///
+    /// builds the App around physics, e.g. `PhysicsPlugin::default()` or
+    /// `BlackHoleUniverse::default().with_softening(500.0).physics_plugin()`, and runs its
+    /// first update, which only starts the clock
+    pub fn new(physics: PhysicsPlugin) -> Self {
+        HeadlessUniverse::at_frame_rate(physics, PHYSICS_HZ)
+    }
+
+    /// fn at_frame_rate
+    ///
+    /// is fn new with each update advancing the clock by 1 / fps seconds, as a display
+    /// running at fps would: FixedUpdate then runs as many physics steps per update as
+    /// the time accumulated calls for (see fn fixed_elapsed)
+    pub fn at_frame_rate(physics: PhysicsPlugin, fps: f64) -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(physics)
+            //at PHYSICS_HZ the same Duration as Time::<Fixed>::from_hz, so no update is
+            //ever a step short
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                1.0 / fps,
+            )));
+        //without the render plugins the merge products are spawned into bare mesh and
+        //material storage, which nothing ever draws
+        app.init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<ColorMaterial>>();
+        app.update();
+
+        HeadlessUniverse { app }
+    }
+
+    /// fn launch: chain
+    ///
+    /// spawns objects onto the field and starts the game, as launching the planet would
+    pub fn launch(&mut self, objects: impl IntoIterator<Item = Movable>) -> &mut Self {
+        let world = self.app.world_mut();
+        for object in objects {
+            world.spawn((
+                Transform::from_xyz(object.position.x, object.position.y, 0.0),
+                object,
+            ));
+        }
+
+        let now = world.resource::<Time<Fixed>>().elapsed_secs_f64();
+        let mut state = world.resource_mut::<GameState>();
+        state.game_started = true;
+        state.start_time = now;
+        self
+    }
+
+    /// fn advance: chain
+    ///
+    /// runs frames updates of the App, each of which runs exactly one physics step when
+    /// built with fn new
+    pub fn advance(&mut self, frames: usize) -> &mut Self {
+        for _ in 0..frames {
+            self.app.update();
+        }
+        self
+    }
+
+    /// fn fixed_elapsed returns the time the physics (fixed) clock has run for: a whole
+    /// number of physics steps
+    pub fn fixed_elapsed(&self) -> Duration {
+        self.app.world().resource::<Time<Fixed>>().elapsed()
+    }
+
+    /// fn objects returns copies of the objects on the field, in id order
+    pub fn objects(&mut self) -> Vec<Movable> {
+        let world = self.app.world_mut();
+        let mut query = world.query::<&Movable>();
+        let mut objects: Vec<Movable> = query.iter(world).map(Movable::build).collect();
+        objects.sort_by_key(Movable::get_id);
+        objects
+    }
+}
@@ -0,0 +1,178 @@
+//! History.rs
+//!
+//! The history module records the merge tree of a run: every collision product along
+//! with the ids of the objects which formed it. Read back, the records form a forest
+//! whose roots are the objects left at the end and whose leaves are the objects the
+//! universe was launched with. Filled by the collision system in plugins.rs and
+//! logged at game over
+
+use bevy::prelude::*;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// MergeRecord struct
+///
+/// One collision product
+/// - parent_ids = ids of the objects which collided, in id order
+/// - child_id = id of the object produced. A collision which splits records one
+///   MergeRecord per fragment, all with the same parents
+/// - time = seconds since launch
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeRecord {
+    pub parent_ids: Vec<u64>,
+    pub child_id: u64,
+    pub time: f64,
+}
+
+/// MergeHistory struct: Resource
+///
+/// Every MergeRecord of the current run, in the order they happened. Cleared on restart
+#[derive(Resource, Debug, Default)]
+pub struct MergeHistory {
+    pub records: Vec<MergeRecord>,
+}
+
+impl MergeHistory {
+    /// fn record
+    ///
+    /// records that the objects parent_ids collided time seconds after launch,
+    /// producing child_id
+    pub fn record(&mut self, parent_ids: &[u64], child_id: u64, time: f64) {
+        self.records.push(MergeRecord {
+            parent_ids: parent_ids.to_vec(),
+            child_id,
+            time,
+        });
+    }
+
+    /// fn clear forgets every record, ready for the next run
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    /// fn parents
+    ///
+    /// returns the ids of the objects which formed id, or None if id was never produced
+    /// by a collision (it was there from the launch, or placed in the sandbox)
+    pub fn parents(&self, id: u64) -> Option<&[u64]> {
+        self.records
+            .iter()
+            .find(|record| record.child_id == id)
+            .map(|record| record.parent_ids.as_slice())
+    }
+
+    /// fn originals
+    ///
+    /// returns the ids of the objects never produced by a collision which ended up in id,
+    /// i.e. the leaves of id's merge tree, in id order. An original object is its own leaf
+    pub fn originals(&self, id: u64) -> Vec<u64> {
+        let mut leaves = BTreeSet::new();
+        let mut pending = vec![id];
+
+        while let Some(next) = pending.pop() {
+            match self.parents(next) {
+                Some(parents) => pending.extend_from_slice(parents),
+                None => {
+                    leaves.insert(next);
+                }
+            }
+        }
+
+        leaves.into_iter().collect()
+    }
+
+    /// fn finals
+    ///
+    /// returns the ids of the products which never collided again, i.e. the roots of
+    /// the merge forest, in id order
+    pub fn finals(&self) -> Vec<u64> {
+        let parents: BTreeSet<u64> = self
+            .records
+            .iter()
+            .flat_map(|record| record.parent_ids.iter().copied())
+            .collect();
+
+        self.records
+            .iter()
+            .map(|record| record.child_id)
+            .filter(|id| !parents.contains(id))
+            .collect::<BTreeSet<u64>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// fn dump
+    ///
+    /// returns the history as text, one record per line, e.g. `12.50s: 3 + 7 -> 15`
+    pub fn dump(&self) -> String {
+        let mut text = String::new();
+        for record in &self.records {
+            let parents: Vec<String> = record.parent_ids.iter().map(u64::to_string).collect();
+            let _ = writeln!(
+                text,
+                "{:.2}s: {} -> {}",
+                record.time,
+                parents.join(" + "),
+                record.child_id
+            );
+        }
+        text
+    }
+}
+
+/// fn test_merge_history ensures a scripted three-body merge is recorded with all three
+/// parents, and that a later merge of its product builds a tree whose leaves are the
+/// original objects
+#[test]
+fn test_merge_history() {
+    use crate::objects::gamestate::UniverseConfig;
+    use crate::objects::movables::{Movable, ObjectType};
+    use crate::objects::physics::collision_pass;
+    use crate::objects::traits::outcomes::CollisionOutcome;
+
+    let objects: Vec<Movable> = [-5.0, 0.0, 5.0]
+        .into_iter()
+        .map(|x| {
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(x, 0.0)
+                .set_mass(10.0)
+                .build()
+        })
+        .collect();
+    let ids: Vec<u64> = objects.iter().map(Movable::get_id).collect();
+
+    let movables: Vec<&Movable> = objects.iter().collect();
+    let (_, frame) = collision_pass(&movables);
+    let lineage = frame.collect_lineage(0.0, &UniverseConfig::default());
+
+    let mut history = MergeHistory::default();
+    assert_eq!(lineage.len(), 1);
+    assert_eq!(lineage[0].1, CollisionOutcome::Merge);
+    for (parents, _, products) in &lineage {
+        for product in products {
+            history.record(parents, product.get_id(), 1.5);
+        }
+    }
+
+    assert_eq!(history.records.len(), 1);
+    let merged = history.records[0].child_id;
+    assert_eq!(history.parents(merged), Some(ids.as_slice()));
+    assert_eq!(history.parents(ids[0]), None);
+
+    //the product then swallows a fourth, original object
+    let straggler = Movable::new(&ObjectType::BlackHole).build();
+    let last = Movable::new(&ObjectType::BlackHole).build();
+    history.record(&[merged, straggler.get_id()], last.get_id(), 4.0);
+
+    let mut expected = ids.clone();
+    expected.push(straggler.get_id());
+    assert_eq!(history.originals(last.get_id()), expected);
+    assert_eq!(history.finals(), vec![last.get_id()]);
+    assert_eq!(
+        history.dump().lines().next(),
+        Some(format!("1.50s: {} + {} + {} -> {merged}", ids[0], ids[1], ids[2]).as_str())
+    );
+
+    history.clear();
+    assert!(history.records.is_empty());
+}
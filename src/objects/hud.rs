@@ -0,0 +1,30 @@
+//! Hud.rs
+//!
+//! The hud module sizes the HUD to the window. The HUD is laid out for a window
+//! REFERENCE_WINDOW_HEIGHT pixels tall; Bevy's UiScale then scales every font size and
+//! offset by the ratio of the actual window height to that reference
+
+/// The window height, in logical pixels, the interface is designed for: the height of
+/// Bevy's default window. The HUD font sizes and offsets are laid out for it and a launch
+/// sensitivity applies unchanged at it (see LaunchConfig::scale), so both follow a
+/// resized window alike
+pub const REFERENCE_WINDOW_HEIGHT: f32 = 720.0;
+
+/// The UiScale is kept within these bounds so the HUD stays legible on tiny
+/// windows and does not crowd out the universe on huge ones
+pub const HUD_SCALE_MIN: f32 = 0.5;
+pub const HUD_SCALE_MAX: f32 = 2.0;
+
+/// fn hud_scale returns the UiScale for a window of the given logical height
+pub fn hud_scale(window_height: f32) -> f32 {
+    (window_height / REFERENCE_WINDOW_HEIGHT).clamp(HUD_SCALE_MIN, HUD_SCALE_MAX)
+}
+
+/// fn test_hud_scale ensures the scale follows the window height within its bounds
+#[test]
+fn test_hud_scale() {
+    assert_eq!(hud_scale(REFERENCE_WINDOW_HEIGHT), 1.0);
+    assert_eq!(hud_scale(540.0), 0.75);
+    assert_eq!(hud_scale(100.0), HUD_SCALE_MIN);
+    assert_eq!(hud_scale(10_000.0), HUD_SCALE_MAX);
+}
@@ -0,0 +1,57 @@
+//! Inspector.rs
+//!
+//! The inspector module backs the hover tooltip: moving the mouse over a black hole
+//! or the planet shows its id, mass, radius and speed. Used by plugins.rs, which
+//! attaches the pointer observers to every spawned object and updates the HUD text
+
+use crate::objects::movables::{Movable, ObjectType};
+use bevy::prelude::*;
+
+/// Offset, in pixels, of the tooltip from the mouse cursor so it never hides the object
+pub const INSPECTOR_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+
+/// Inspector struct: Component
+///
+/// Used for querying the Bevy Text entity which displays the hovered object's stats
+#[derive(Component)]
+pub struct Inspector;
+
+/// Hovered struct: Resource
+///
+/// The object currently underneath the mouse cursor, None if there is none
+#[derive(Resource, Default)]
+pub struct Hovered(pub Option<Entity>);
+
+/// fn describe
+///
+/// formats the live stats of an object for the inspector tooltip
+pub fn describe(movable: &Movable) -> String {
+    let kind = match movable.otype {
+        ObjectType::BlackHole => "Black Hole",
+        ObjectType::World => "Planet",
+    };
+
+    format!(
+        "{kind} #{}\nmass: {:.1}\nradius: {:.1}\nspeed: {:.1}",
+        movable.get_id(),
+        movable.size.mass,
+        movable.size.radius,
+        movable.speed()
+    )
+}
+
+/// fn test_describe ensures the tooltip reports the object's live stats
+#[test]
+fn test_describe() {
+    let black_hole = Movable::new(&ObjectType::BlackHole)
+        .set_velocity(30.0, 40.0)
+        .set_mass(10.0)
+        .build();
+
+    let text = describe(&black_hole);
+
+    assert!(text.starts_with(&format!("Black Hole #{}", black_hole.get_id())));
+    assert!(text.contains("mass: 10.0"));
+    assert!(text.contains(&format!("radius: {:.1}", black_hole.size.radius)));
+    assert!(text.contains("speed: 50.0"));
+}
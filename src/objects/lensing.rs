@@ -0,0 +1,69 @@
+//! Lensing.rs
+//!
+//! The lensing module defines a purely cosmetic gravitational lensing halo: heavy
+//! black holes are surrounded by a faint glow of light bent around them, drawn as a
+//! stack of translucent discs which brighten toward the event horizon. The halo is
+//! a child of its black hole so it follows it, and is replaced along with it on a merge
+
+use bevy::prelude::*;
+
+/// Black holes at least this heavy are surrounded by a lensing halo
+pub const LENSING_MIN_MASS: f32 = 10.0;
+
+/// The outer edge of the halo, as a multiple of the black hole's radius
+pub const LENS_EXTENT: f32 = 3.0;
+
+/// The number of stacked discs forming the halo's gradient
+pub const LENS_LAYERS: usize = 4;
+
+/// The opacity of each disc: the innermost part of the halo, covered by every
+/// disc, is LENS_LAYERS times as bright as its outer edge
+pub const LENS_LAYER_ALPHA: f32 = 0.05;
+
+/// Lens struct: Component
+///
+/// Marks one disc of a black hole's lensing halo
+#[derive(Component)]
+pub struct Lens;
+
+/// Lensing struct: Resource
+///
+/// Toggled with the `L` key. While true, the lensing halos are drawn
+#[derive(Resource)]
+pub struct Lensing(pub bool);
+
+/// Default draws the halos
+impl Default for Lensing {
+    fn default() -> Self {
+        Lensing(true)
+    }
+}
+
+/// fn lens_layers
+///
+/// returns the radius of every disc of the halo around a black hole of the given mass
+/// and radius, innermost first. Black holes lighter than LENSING_MIN_MASS have no halo
+pub fn lens_layers(mass: f32, radius: f32) -> Vec<f32> {
+    if mass < LENSING_MIN_MASS {
+        return Vec::new();
+    }
+
+    (1..=LENS_LAYERS)
+        .map(|layer| radius * (1.0 + (LENS_EXTENT - 1.0) * layer as f32 / LENS_LAYERS as f32))
+        .collect()
+}
+
+/// fn test_lens_layers ensures only heavy black holes have a halo, which grows with
+/// the black hole and reaches LENS_EXTENT radii
+#[test]
+fn test_lens_layers() {
+    assert!(lens_layers(LENSING_MIN_MASS / 2.0, 15.0).is_empty());
+
+    let small = lens_layers(LENSING_MIN_MASS, 30.0);
+    let large = lens_layers(2.0 * LENSING_MIN_MASS, 60.0);
+
+    assert_eq!(small.len(), LENS_LAYERS);
+    assert!(small.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(*small.last().unwrap(), LENS_EXTENT * 30.0);
+    assert!(small.iter().zip(large.iter()).all(|(s, l)| s < l));
+}
@@ -5,11 +5,29 @@
 
 pub mod button;
 pub mod clocks;
+pub mod controls;
+pub mod edges;
+pub mod endgame;
+pub mod entropy;
 pub mod gamestate;
 pub mod gauss;
+pub mod headless;
+pub mod history;
+pub mod hud;
+pub mod inspector;
+pub mod lensing;
 pub mod movables;
+pub mod physics;
 pub mod plugins;
+pub mod recording;
+pub mod settings;
+pub mod shield;
 pub mod sliders;
+pub mod slingshot;
+pub mod snapshot;
+pub mod stats;
+pub mod theme;
 pub mod traits;
+pub mod universe;
 
-pub use self::plugins::BlackHoleUniverse;
+pub use self::plugins::{BlackHoleUniverse, PhysicsPlugin};
@@ -8,20 +8,24 @@
 //! 2. Physics calculations for gravity and acceleration
 //! 3. Collision logic and helper data structures
 
-use crate::objects::gamestate::UNIVERSE_SIZE;
+use crate::objects::gamestate::UniverseConfig;
 use crate::objects::traits::collisions::{CollisionDetection, Position, Shapes};
+use crate::objects::traits::outcomes::{CollisionBehavior, CollisionOutcome, group_outcome};
 use bevy::math::FloatPow;
 use bevy::prelude::*;
+use rayon::prelude::*;
 use std::cmp::{Eq, Ord, Ordering, PartialOrd};
 use std::collections::BTreeSet;
 use std::default::Default;
-use std::f32::consts::FRAC_PI_4;
-use std::sync::atomic::{AtomicU32, Ordering::SeqCst};
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU64, Ordering::SeqCst};
 
-/// these atomics are used to track the number of spawned objects
-/// and assign unique IDs for lookup evaluations to each new spawned object
-static BLACKHOLECOUNT: AtomicU32 = AtomicU32::new(0);
-static WORLDCOUNT: AtomicU32 = AtomicU32::new(0);
+/// this atomic is used to track the number of spawned objects
+/// and assign unique IDs for lookup evaluations to each new spawned object.
+/// A single counter is shared by all object types so that IDs are unique across
+/// types, and it is 64 bit so that it can not wrap around even in very long runs
+/// full of merges (each of which spawns a new object)
+static OBJECTCOUNT: AtomicU64 = AtomicU64::new(0);
 
 /// ObjectType enum: Component
 ///
@@ -32,6 +36,118 @@ pub enum ObjectType {
     World,
 }
 
+/// GravityMatrix struct: Resource
+///
+/// Which pairs of ObjectTypes attract each other, read as `source_on_target`: e.g.
+/// planet_on_black_hole is whether a planet's gravity pulls on a black hole.
+/// Disabled pairs are skipped by Movable::update_velocity. Defaults to every pair
+/// attracting, i.e. full n-body gravity
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GravityMatrix {
+    pub black_hole_on_black_hole: bool,
+    pub black_hole_on_planet: bool,
+    pub planet_on_black_hole: bool,
+    pub planet_on_planet: bool,
+}
+
+impl Default for GravityMatrix {
+    fn default() -> Self {
+        GravityMatrix {
+            black_hole_on_black_hole: true,
+            black_hole_on_planet: true,
+            planet_on_black_hole: true,
+            planet_on_planet: true,
+        }
+    }
+}
+
+impl GravityMatrix {
+    /// fn attracts returns true if the gravity of a source object pulls on a target object
+    pub fn attracts(&self, source: ObjectType, target: ObjectType) -> bool {
+        match (source, target) {
+            (ObjectType::BlackHole, ObjectType::BlackHole) => self.black_hole_on_black_hole,
+            (ObjectType::BlackHole, ObjectType::World) => self.black_hole_on_planet,
+            (ObjectType::World, ObjectType::BlackHole) => self.planet_on_black_hole,
+            (ObjectType::World, ObjectType::World) => self.planet_on_planet,
+        }
+    }
+}
+
+/// PhysicsConfig struct: Resource
+///
+/// Tunable parameters of the gravity calculation
+/// - softening = added to the squared separation of two objects when calculating their
+///   attraction (Plummer-like softening), which also prevents a divide by zero. Lowering it
+///   sharpens close passes (and makes them more prone to numerical blow up), raising it
+///   smooths them. Defaults to 1000.0
+/// - gravity_constant = G in Netwon's law of gravity: scales every pull. Defaults to 1.0E8
+/// - integrator = how each physics step advances the objects (see IntegratorMode)
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsConfig {
+    pub softening: f32,
+    pub gravity_constant: f32,
+    pub integrator: IntegratorMode,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        PhysicsConfig {
+            softening: 1000.0,
+            gravity_constant: 100_000_000.0,
+            integrator: IntegratorMode::default(),
+        }
+    }
+}
+
+/// IntegratorMode enum
+///
+/// how a physics step of t seconds advances the objects
+/// - Euler = semi-implicit Euler, the default: the velocities are kicked by the gravity
+///   of the current positions and the objects then drift the whole step at their new velocity
+/// - Verlet = position Verlet (drift, kick, drift): the objects drift half the step, are
+///   kicked by the gravity at that midpoint and drift the second half at their new velocity.
+///   Second order and time reversible, so orbits hold their energy far better
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntegratorMode {
+    #[default]
+    Euler,
+    Verlet,
+}
+
+impl IntegratorMode {
+    /// fn toggle returns the other integrator
+    pub fn toggle(&self) -> Self {
+        match self {
+            IntegratorMode::Euler => IntegratorMode::Verlet,
+            IntegratorMode::Verlet => IntegratorMode::Euler,
+        }
+    }
+
+    /// fn first_drift is how long the objects drift before their velocities are kicked
+    /// during a step of time seconds: the rest of the step is drifted after the kick
+    pub fn first_drift(&self, time: f32) -> f32 {
+        match self {
+            IntegratorMode::Euler => 0.0,
+            IntegratorMode::Verlet => time / 2.0,
+        }
+    }
+
+    /// fn name is the name of the integrator, as saved in a Recording
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntegratorMode::Euler => "Euler",
+            IntegratorMode::Verlet => "Verlet",
+        }
+    }
+
+    /// fn from_name is the inverse of fn name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [IntegratorMode::Euler, IntegratorMode::Verlet]
+            .into_iter()
+            .find(|integrator| integrator.name() == name)
+    }
+}
+
 /// Acceleration struct: Component
 ///
 /// used within the Movable struct to define the object's
@@ -68,14 +184,16 @@ pub struct Size {
 /// used within the Movable struct to define the object's
 /// unique identifier (to prevent self-evaluation, for example)
 #[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
-pub struct ID(u32);
+pub struct ID(u64);
 
 /// Movable struct: Component
 ///
 /// this is the main struct of the simulation and is passed
 /// around and manipulated in many different places. You can expect
 /// that this structs is updated for every object in the universe at
-/// every frame
+/// every frame.
+///
+/// atmosphere scales a planet's hitbox relative to its visual radius (see get_hitbox)
 #[derive(Component, Debug)]
 pub struct Movable {
     id: ID,
@@ -83,6 +201,7 @@ pub struct Movable {
     pub position: Position,
     pub velocity: Velocity,
     pub size: Size,
+    pub atmosphere: f32,
 }
 
 /// CollisionResult enum
@@ -105,7 +224,7 @@ pub enum CollisionResult {
 /// collided within the last frame. Most of this abstraction is built around
 /// handling muliple / chain reaction collisions properly. Note the private
 /// internal data structure: interactions are limited to impl'd methods
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Default)]
 pub struct CollisionSet<'a> {
     data: BTreeSet<&'a Movable>,
 }
@@ -123,8 +242,17 @@ impl<'a> CollisionSet<'a> {
     /// fn append
     ///
     /// Append wraps the insert method of the BTreeSet and was named `append` bc
-    /// it felt more approiate than insert in this context
+    /// it felt more approiate than insert in this context. Members are told apart by
+    /// id alone (see impl Ord for Movable), so appending a second, distinct object
+    /// with the id of a member would silently drop it: debug builds panic instead
     pub fn append(&mut self, other: &'a Movable) -> bool {
+        debug_assert!(
+            self.data
+                .get(other)
+                .is_none_or(|member| std::ptr::eq(*member, other)),
+            "two distinct objects share the id {}",
+            other.get_id()
+        );
         self.data.insert(other)
     }
 
@@ -165,6 +293,22 @@ impl<'a> CollisionSet<'a> {
         self.data.len()
     }
 
+    /// fn ids
+    ///
+    /// returns the ids of the Movables in this set, in id order
+    pub fn ids(&self) -> Vec<u64> {
+        self.data.iter().map(|m| m.get_id()).collect()
+    }
+
+    /// fn outcome
+    ///
+    /// returns what colliding this set produces (see group_outcome): Merge for a single
+    /// merged object, Bounce or Split for several
+    pub fn outcome(&self) -> CollisionOutcome {
+        let kinds: Vec<ObjectType> = self.data.iter().map(|m| m.otype).collect();
+        group_outcome(&kinds)
+    }
+
     /// fn is_empty
     ///
     /// returns true if the number of Movable references in this
@@ -199,8 +343,10 @@ impl<'a> CollisionSet<'a> {
     ///
     /// fn collide physically produces the action of the collision of all the Movables
     /// within this set. This is performed by collecting the Movables into a vector and
-    /// calling the static Movable::process_collisions method on the collection
-    pub fn collide(&self) -> CollisionResult {
+    /// calling the static Movable::process_collisions method on the collection.
+    /// bh_threshold is the minimum mass of a merge product to remain a black hole
+    /// and universe is the field the products are wrapped onto
+    pub fn collide(&self, bh_threshold: f32, universe: &UniverseConfig) -> CollisionResult {
         let count = self.len();
 
         //gotta have 2 obj to collide
@@ -211,7 +357,7 @@ impl<'a> CollisionSet<'a> {
                 v.push(item);
             }
 
-            return Movable::process_collisions(&v);
+            return Movable::process_collisions(&v, bh_threshold, universe);
         }
 
         CollisionResult::None
@@ -223,7 +369,7 @@ impl<'a> CollisionSet<'a> {
 /// A CollisionFrame is a collection of CollisionSets (defined above) representing
 /// all collisions determined during the current frame. Note, the internal data structure
 /// is private: all interaction with this object should be performed via its methods
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Default)]
 pub struct CollisionFrame<'a> {
     array: Vec<CollisionSet<'a>>,
 }
@@ -241,23 +387,31 @@ impl<'a> CollisionFrame<'a> {
     /// fn push
     ///
     /// when appending, this function merges intersecting CollisionSets because if
-    /// 0 collides with 2 and 1 collides with 2, then 0-1-2 all collide together
+    /// 0 collides with 2 and 1 collides with 2, then 0-1-2 all collide together.
+    /// The new set is merged with every set it intersects, not just the first: a set
+    /// bridging two others (5 collides with 6 after 0-5 and 1-6) joins all three into
+    /// one, so the CollisionSets stay disjoint. The merged set takes the place of the
+    /// first set it intersected.
     /// returns indicator: true means the push-ed CollisionSet was merged,
     /// false means that it was a new unique collision
     pub fn push(&mut self, new: CollisionSet<'a>) -> bool {
-        let mut found = false;
-
-        for item in &mut self.array {
-            if let Some(n) = CollisionSet::merge_intersection(item, &new) {
-                *item = n;
-                found = true;
-                break;
+        let mut merged = new;
+        let mut first = None;
+        let mut kept = Vec::<CollisionSet<'a>>::with_capacity(self.array.len() + 1);
+
+        for item in self.array.drain(..) {
+            match CollisionSet::merge_intersection(&item, &merged) {
+                Some(n) => {
+                    merged = n;
+                    first.get_or_insert(kept.len());
+                }
+                None => kept.push(item),
             }
         }
 
-        if !found {
-            self.array.push(new);
-        }
+        let found = first.is_some();
+        kept.insert(first.unwrap_or(kept.len()), merged);
+        self.array = kept;
 
         found
     }
@@ -268,23 +422,61 @@ impl<'a> CollisionFrame<'a> {
     /// 1. CollisionResult::None => no collisions during this frame
     /// 2. CollisionResult::Single(n) => all collisions resulted in a single resultant object, n
     /// 3. CollisionResult::NSize(Vec!) => collisions resulted in 2+ resultant objects
-    pub fn collect(&self) -> CollisionResult {
-        let mut ret = Vec::<Movable>::new(); //flatten
-
+    ///
+    /// bh_threshold is the minimum mass of a merge product to remain a black hole and
+    /// universe is the field the products are wrapped onto.
+    /// See collect_lineage, which also reports which objects formed each product
+    pub fn collect(&self, bh_threshold: f32, universe: &UniverseConfig) -> CollisionResult {
         if self.array.is_empty() {
             return CollisionResult::None;
         }
 
+        CollisionResult::NSize(
+            self.collect_lineage(bh_threshold, universe)
+                .into_iter()
+                .flat_map(|(_, _, products)| products)
+                .collect(),
+        )
+    }
+
+    /// fn collect_lineage
+    ///
+    /// performs the collisions for all the objects of the frame like collect, returning
+    /// for every CollisionSet the ids of its members (the parents), its CollisionOutcome
+    /// (see CollisionSet::outcome) and the objects it produced: the merged object or, if
+    /// it bounced or split, every object it left
+    ///
+    /// The CollisionSets are disjoint, so they are collided in parallel with rayon. Products
+    /// draw their IDs in whatever order the threads run, so they are renumbered in set order
+    /// afterwards: the result (and so every later, ID ordered, frame) is the same as
+    /// colliding the sets one at a time
+    pub fn collect_lineage(
+        &self,
+        bh_threshold: f32,
+        universe: &UniverseConfig,
+    ) -> Vec<(Vec<u64>, CollisionOutcome, Vec<Movable>)> {
         //all CollisionSets in self.array are now guaranteed to be unique collisions
-        for item in &self.array {
-            match item.collide() {
-                CollisionResult::Single(n) => ret.push(n),
-                CollisionResult::NSize(mut n) => ret.append(&mut n),
-                CollisionResult::None => {}
+        let results: Vec<CollisionResult> = self
+            .array
+            .par_iter()
+            .map(|item| item.collide(bh_threshold, universe))
+            .collect();
+
+        let mut lineage = Vec::<(Vec<u64>, CollisionOutcome, Vec<Movable>)>::new();
+        for (item, result) in self.array.iter().zip(results) {
+            let mut products = match result {
+                CollisionResult::Single(n) => vec![n],
+                CollisionResult::NSize(n) => n,
+                CollisionResult::None => continue,
+            };
+
+            for product in &mut products {
+                product.renew_id();
             }
+            lineage.push((item.ids(), item.outcome(), products));
         }
 
-        CollisionResult::NSize(ret)
+        lineage
     }
 }
 
@@ -293,10 +485,10 @@ impl<'a> CollisionFrame<'a> {
 impl Movable {
     /// Constant vars used for boundaries or calculations
     const MINIMUM_RADIUS: f32 = 1.0f32;
-    const G: f32 = 100_000_000.0;
-    const EPSILON: f32 = 1000.0; //to pad on radius to prevent divide by zero possibilities
     const MAXACCELERATION: f32 = 1.0E4;
-    const MAXVELOCITY: f32 = 10_000.0; //that would mean travel the length of the universe in 1 second
+    pub const MAXVELOCITY: f32 = 10_000.0; //that would mean travel the length of the universe in 1 second
+    const PLANET_ATMOSPHERE: f32 = 1.0; //planets collide at their visual radius unless set_atmosphere is used
+    const SPLIT_CLEARANCE: f32 = 1.05; //split fragments start 5% further apart than touching
 
     /// Constructor
     ///
@@ -306,10 +498,7 @@ impl Movable {
     ///
     /// This method is intended to be chained with the following intialization methods
     pub fn new(otype: &ObjectType) -> Self {
-        let id = match otype {
-            ObjectType::BlackHole => BLACKHOLECOUNT.fetch_add(1, SeqCst),
-            ObjectType::World => WORLDCOUNT.fetch_add(1, SeqCst),
-        };
+        let id = OBJECTCOUNT.fetch_add(1, SeqCst);
 
         Movable {
             id: ID(id),
@@ -330,20 +519,24 @@ impl Movable {
 
     /// fn set_velocity: chain
     ///
-    /// updates the objects x and y velocity in the universe, bounded by Movable::MAXVELOCITY constant
+    /// updates the objects x and y velocity in the universe. The speed (the magnitude of the
+    /// velocity, not each axis) is bounded by the Movable::MAXVELOCITY constant: a faster velocity
+    /// keeps its heading and is scaled down to MAXVELOCITY, which is logged at debug level
     /// This method is intended to be chained with the following intialization methods
     pub fn set_velocity(&mut self, vx: f32, vy: f32) -> &mut Self {
-        if vx < 0.0 {
-            self.velocity.vx = vx.max(-Movable::MAXVELOCITY);
+        let speed = vx.hypot(vy);
+        let scale = if speed > Movable::MAXVELOCITY {
+            debug!(
+                "speed {speed} exceeds the maximum of {}: clamped",
+                Movable::MAXVELOCITY
+            );
+            Movable::MAXVELOCITY / speed
         } else {
-            self.velocity.vx = vx.min(Movable::MAXVELOCITY);
-        }
+            1.0
+        };
 
-        if vx < 0.0 {
-            self.velocity.vy = vy.max(-Movable::MAXVELOCITY);
-        } else {
-            self.velocity.vy = vy.min(Movable::MAXVELOCITY);
-        }
+        self.velocity.vx = vx * scale;
+        self.velocity.vy = vy * scale;
 
         self
     }
@@ -372,19 +565,29 @@ impl Movable {
             } //https://blackholes.stardate.org/resources/article-structure-of-a-black-hole.html
             ObjectType::World => {
                 //https://www.aanda.org/articles/aa/full_html/2024/06/aa48690-23/aa48690-23.html#F1, Eq5
-                if mass <= 5.0 {
-                    self.size.radius = (1.02f32 * mass.powf(0.27)).max(Movable::MINIMUM_RADIUS);
-                } else if mass <= 127.0 {
-                    self.size.radius = (18.6f32 * mass.powf(-0.06)).max(Movable::MINIMUM_RADIUS);
-                } else {
-                    self.size.radius = (0.56 * mass.powf(0.67)).max(Movable::MINIMUM_RADIUS);
-                }
+                //the rocky branch up to where the transitional branch overtakes it (~4.48),
+                //so the radius grows continuously with the mass. The giant branch, whose
+                //radius shrinks as the mass grows, is left out: it has no inverse
+                let rocky = 1.02f32 * mass.powf(0.27);
+                let transitional = 0.56f32 * mass.powf(0.67);
+                self.size.radius = rocky.max(transitional).max(Movable::MINIMUM_RADIUS);
             }
         }
 
         self
     }
 
+    /// fn set_atmosphere: chain
+    ///
+    /// updates the factor by which a planet's hitbox extends past its visual radius,
+    /// e.g. 1.2 gives a planet an atmosphere 20% of its radius deep. Black holes
+    /// always collide at their event horizon and ignore this factor.
+    /// This method is intended to be chained with the following intialization methods
+    pub fn set_atmosphere(&mut self, factor: f32) -> &mut Self {
+        self.atmosphere = factor;
+        self
+    }
+
     /// fn set_radius: chain
     ///
     /// inverse function of above: updates the objects radius and mass by calculating the mass from the supplied radius.
@@ -398,20 +601,38 @@ impl Movable {
             } //https://blackholes.stardate.org/resources/article-structure-of-a-black-hole.html
             ObjectType::World => {
                 //https://www.aanda.org/articles/aa/full_html/2024/06/aa48690-23/aa48690-23.html#F1, Eq5
-
-                if radius < 1.575151 {
-                    self.size.mass = (radius / 1.02).powf(100.0 / 27.0); //(1.02f32 * mass.powf(0.27)).max(Movable::MINIMUM_RADIUS);
-                } else if radius < 13.90864 {
-                    self.size.mass = (radius / 18.6).powf(-100.0 / 6.0); //(18.6f32 * mass.powf(-0.06)).max(Movable::MINIMUM_RADIUS);
-                } else {
-                    self.size.mass = (radius / 0.56).powf(100.0 / 67.0) //(0.56 * mass.powf(0.67)).max(Movable::MINIMUM_RADIUS);
-                }
+                //both branches grow with the mass, so the larger radius of set_mass is the
+                //branch giving the smaller mass here
+                let rocky = (radius / 1.02).powf(100.0 / 27.0);
+                let transitional = (radius / 0.56).powf(100.0 / 67.0);
+                self.size.mass = rocky.min(transitional);
             }
         }
 
         self
     }
 
+    /// fn clamp_radius: chain
+    ///
+    /// an object comparable in size to the universe would break the wrap logic and
+    /// collide with everything, so the radius is clamped to the maximum of universe (see
+    /// UniverseConfig::max_radius), keeping the mass. Every object entering the field is
+    /// clamped: merge products, generated black holes and presets. A warning is logged
+    /// whenever the clamp is applied.
+    /// This method is intended to be chained with the following intialization methods
+    pub fn clamp_radius(&mut self, universe: &UniverseConfig) -> &mut Self {
+        let max_radius = universe.max_radius();
+
+        if self.size.radius > max_radius {
+            warn!(
+                "radius {} exceeds the maximum of {max_radius}: clamped",
+                self.size.radius
+            );
+            self.size.radius = max_radius;
+        }
+        self
+    }
+
     /// fn build: chain
     ///
     /// ends the chaining process by returning a new object containing the
@@ -434,29 +655,150 @@ impl Movable {
                 radius: self.size.radius,
                 mass: self.size.mass,
             },
+            atmosphere: self.atmosphere,
         }
     }
 
+    /// fn speed
+    ///
+    /// returns the magnitude of the objects velocity
+    pub fn speed(&self) -> f32 {
+        (self.velocity.vx.squared() + self.velocity.vy.squared()).sqrt()
+    }
+
     /// fn get_id
     ///
     /// exterior getter for the ID parameter
-    pub fn get_id(&self) -> u32 {
+    pub fn get_id(&self) -> u64 {
         self.id.0
     }
 
-    /// fn calculate_acceleration
+    /// fn renew_id
     ///
-    /// calculates the x and y component of acceleration induced on self by other
-    /// using Netwon's equations of motion and gravity. Note that because this
-    /// universe is spherical (it wraps around on itself), this function will
-    /// choose the proper direction of acceleration by using the shortest distance between
-    /// self and other: either the visual straight line, or the wrapped around line
-    pub fn calculate_acceleration(&self, other: &Self) -> Acceleration {
+    /// assigns the object a fresh ID, ordering it after every object created so far.
+    /// Objects are processed in ID order, so this lets an object kept across a
+    /// regeneration of the universe take the place it would have had if created afterwards
+    pub fn renew_id(&mut self) {
+        self.id = ID(OBJECTCOUNT.fetch_add(1, SeqCst));
+    }
+
+    /// fn consumed_planet
+    ///
+    /// given the parents and products of one collision (see CollisionFrame::collect_lineage),
+    /// returns the combined mass of the black holes among the parents if the planet with
+    /// id planet is one of them and the collision produced a single black hole, i.e. the
+    /// collision swallows that planet, and None otherwise. A planet merging with black
+    /// holes too light to stay one (see bh_threshold) is reborn as a planet and so was not
+    /// consumed
+    pub fn consumed_planet(planet: u64, parents: &[&Movable], products: &[Movable]) -> Option<f32> {
+        let became_black_hole =
+            matches!(products, [product] if product.otype == ObjectType::BlackHole);
+        let has_planet = parents.iter().any(|m| m.get_id() == planet);
+        let black_hole_mass: f32 = parents
+            .iter()
+            .filter(|m| m.otype == ObjectType::BlackHole)
+            .map(|m| m.size.mass)
+            .sum();
+
+        (became_black_hole && has_planet && black_hole_mass > 0.0).then_some(black_hole_mass)
+    }
+
+    /// fn wrap
+    ///
+    /// enforces the spherical universe by wrapping the current position
+    /// back onto the playing field of universe if it has drifted past one of the edges
+    /// (see UniverseConfig::wrap): even a step at MAXVELOCITY lands on the field in one
+    /// step. Each axis wraps at its own size. The previous position moves by the same offset,
+    /// so the swept segment the collisions are measured along stays the step actually
+    /// taken (just across the edge) rather than spanning the whole field
+    pub fn wrap(&mut self, universe: &UniverseConfig) {
+        let wrapped = universe.wrap(Vec2::new(self.position.x, self.position.y));
+
+        self.position.x_prev += wrapped.x - self.position.x;
+        self.position.y_prev += wrapped.y - self.position.y;
+        self.position.x = wrapped.x;
+        self.position.y = wrapped.y;
+    }
+
+    /// fn wrap_offsets
+    ///
+    /// returns the offsets at which copies (ghosts) of self must be drawn so that an
+    /// object straddling an edge of the spherical universe appears on both sides of
+    /// that edge of universe. 0, 1 (one edge) or 3 (a corner) offsets are returned
+    pub fn wrap_offsets(&self, universe: &UniverseConfig) -> Vec<Vec2> {
+        let r = self.size.radius;
+
+        let wrapped = |value: f32, size: f32| {
+            let boundary = 0.5 * size;
+            if value + r > boundary {
+                Some(-size) //drawn off the right/top edge: copy on the left/bottom
+            } else if value - r < -boundary {
+                Some(size) //drawn off the left/bottom edge: copy on the right/top
+            } else {
+                None
+            }
+        };
+
+        let mut offsets = Vec::<Vec2>::new();
+        let x = wrapped(self.position.x, universe.width);
+        let y = wrapped(self.position.y, universe.height);
+
+        if let Some(dx) = x {
+            offsets.push(Vec2::new(dx, 0.0));
+        }
+        if let Some(dy) = y {
+            offsets.push(Vec2::new(0.0, dy));
+        }
+        if let (Some(dx), Some(dy)) = (x, y) {
+            offsets.push(Vec2::new(dx, dy));
+        }
+
+        offsets
+    }
+
+    /// fn clear_bubble
+    ///
+    /// pushes self radially away from center until the gap between the two hitboxes
+    /// is at least clearance. Both the current and previous positions are moved so
+    /// that the nudge is not mistaken for motion by the collision sweep. Coincident
+    /// objects are pushed along +x so the result is deterministic. A push past an edge
+    /// wraps around universe. Returns true if self had to be moved
+    pub fn clear_bubble(
+        &mut self,
+        center: &Movable,
+        clearance: f32,
+        universe: &UniverseConfig,
+    ) -> bool {
+        let min_distance = self.contact_distance(center) + clearance;
+        let dx = self.position.x - center.position.x;
+        let dy = self.position.y - center.position.y;
+        let distance = (dx.squared() + dy.squared()).sqrt();
+
+        if distance >= min_distance {
+            return false;
+        }
+
+        let theta = if distance == 0.0 { 0.0 } else { dy.atan2(dx) };
+        self.position.x = center.position.x + min_distance * theta.cos();
+        self.position.y = center.position.y + min_distance * theta.sin();
+        self.wrap(universe);
+        self.position.x_prev = self.position.x;
+        self.position.y_prev = self.position.y;
+
+        true
+    }
+
+    /// fn shortest_offset
+    ///
+    /// returns the (dx, dy) from self to other along the shortest path in the
+    /// spherical universe: either the visual straight line, or the wrapped around line.
+    /// Each axis picks its shorter direction on its own
+    pub fn shortest_offset(&self, other: &Self, universe: &UniverseConfig) -> (f32, f32) {
         let dx_straight = other.position.x - self.position.x;
-        let wrap_dx = UNIVERSE_SIZE - dx_straight.abs();
+        let wrap_dx = universe.width - dx_straight.abs();
 
         let dy_straight = other.position.y - self.position.y;
-        let wrap_dy = UNIVERSE_SIZE - dy_straight.abs();
+        let wrap_dy = universe.height - dy_straight.abs();
 
         let mut dx = dx_straight;
         let mut dy = dy_straight;
@@ -478,10 +820,35 @@ impl Movable {
             }
         }
 
+        (dx, dy)
+    }
+
+    /// fn calculate_acceleration
+    ///
+    /// calculates the x and y component of acceleration induced on self by other
+    /// using Netwon's equations of motion and gravity. Note that because this
+    /// universe is spherical (it wraps around on itself), this function will
+    /// choose the proper direction of acceleration by using the shortest distance between
+    /// self and other: either the visual straight line, or the wrapped around line (see
+    /// shortest_offset). The squared separation is padded by PhysicsConfig::softening
+    /// and the pull is scaled by PhysicsConfig::gravity_constant.
+    /// Coincident objects have no direction between them and so exert no pull: they merge
+    /// on the next collision pass instead (see CollisionDetection::minimum_distance)
+    pub fn calculate_acceleration(
+        &self,
+        other: &Self,
+        config: &PhysicsConfig,
+        universe: &UniverseConfig,
+    ) -> Acceleration {
+        let (dx, dy) = self.shortest_offset(other, universe);
+        if dx == 0.0 && dy == 0.0 {
+            return Acceleration { ax: 0.0, ay: 0.0 };
+        }
+
         let r = dx.squared() + dy.squared();
 
-        let a =
-            (Movable::G * other.size.mass / (r + Movable::EPSILON)).min(Movable::MAXACCELERATION);
+        let a = (config.gravity_constant * other.size.mass / (r + config.softening))
+            .min(Movable::MAXACCELERATION);
         let theta = dy.atan2(dx);
 
         Acceleration {
@@ -505,18 +872,35 @@ impl Movable {
     /// given a slice of all other Movables in the universe, calculates the x and y components of
     /// acceleration on self due to the gravity of all the other objects. The accelerations are
     /// vector summed and then the supplied time interval is used to calculate the new velocity
-    /// for the next frame: v = v + a * t
-    pub fn update_velocity(&self, others: &[&Movable], time: f32) -> Velocity {
+    /// for the next frame: v = v + a * t. Objects whose type does not attract self's type
+    /// (see GravityMatrix) are skipped. Each pull is capped at MAXACCELERATION and so is
+    /// their sum, keeping its direction, so that a body in a dense cluster is not flung
+    /// across the universe in a single frame
+    pub fn update_velocity(
+        &self,
+        others: &[&Movable],
+        time: f32,
+        gravity: &GravityMatrix,
+        config: &PhysicsConfig,
+        universe: &UniverseConfig,
+    ) -> Velocity {
         let mut acc = Acceleration { ax: 0.0, ay: 0.0 };
 
         for other in others {
-            if self != *other {
-                let cur = self.calculate_acceleration(other);
+            if self != *other && gravity.attracts(other.otype, self.otype) {
+                let cur = self.calculate_acceleration(other, config, universe);
                 acc.ax += cur.ax;
                 acc.ay += cur.ay;
             }
         }
 
+        let magnitude = (acc.ax.squared() + acc.ay.squared()).sqrt();
+        if magnitude > Movable::MAXACCELERATION {
+            let scale = Movable::MAXACCELERATION / magnitude;
+            acc.ax *= scale;
+            acc.ay *= scale;
+        }
+
         Velocity {
             vx: self.velocity.vx + acc.ax * time,
             vy: self.velocity.vy + acc.ay * time,
@@ -525,15 +909,18 @@ impl Movable {
 
     /// fn generate_blackhole:  static, private!
     ///
-    /// given 2 movables, returns a single Movable using the black hole mechanics
-    fn generate_blackhole(one: &Self, two: &Self) -> Self {
+    /// given 2 movables, returns a single Movable using the black hole mechanics.
+    /// The product sits exactly at the pair's center of mass, so that the spawned
+    /// black hole appears precisely between the two originals, wrapped onto universe
+    fn generate_blackhole(one: &Self, two: &Self, universe: &UniverseConfig) -> Self {
         let new_mass = one.size.mass + two.size.mass;
 
-        //use 2 body center of mass equation
-        let center_of_mass_x = (one.size.mass * one.position.x + two.size.mass * two.position.x)
-            / (one.size.mass + two.size.mass);
-        let center_of_mass_y = (one.size.mass * one.position.y + two.size.mass * two.position.y)
-            / (one.size.mass + two.size.mass);
+        //use 2 body center of mass equation, measured along the shortest path between the
+        //two so that a merge across an edge of the universe happens at the edge rather than
+        //between the two far apart visual positions
+        let (dx, dy) = one.shortest_offset(two, universe);
+        let center_of_mass_x = one.position.x + two.size.mass * dx / new_mass;
+        let center_of_mass_y = one.position.y + two.size.mass * dy / new_mass;
 
         //add momentum because then divide by new mass
         let new_velocity_x =
@@ -541,94 +928,220 @@ impl Movable {
         let new_velocity_y =
             ((one.size.mass * one.velocity.vy) + (two.size.mass * two.velocity.vy)) / new_mass;
 
-        Movable::new(&ObjectType::BlackHole)
+        let mut merged = Movable::new(&ObjectType::BlackHole)
             .set_position(center_of_mass_x, center_of_mass_y)
             .set_velocity(new_velocity_x, new_velocity_y)
             .set_mass(new_mass)
-            .build()
+            .clamp_radius(universe)
+            .build();
+        merged.wrap(universe);
+
+        merged
     }
 
-    /// fn split_planet:  todo()!, private!
+    /// fn split_planet: private!
     ///
-    /// currently unused method intended to split a planet into two planets,
-    /// redistributing mass and radius to the split pair. Reserved for future use
+    /// splits a planet into two planets (ObjectType::World), each carrying half of the
+    /// parent's mass and radius, so that a split never creates mass. The fragments
+    /// are returned at the parent's position: split_planets places them
     fn split_planet(&self) -> (Self, Self) {
-        let new_velocity = (self.velocity.vx.squared() + self.velocity.vy.squared()).sqrt() / 2.0;
-        let theta = self.velocity.vy.atan2(self.velocity.vx);
+        let new_mass = self.size.mass / 2.0;
         let new_radius = self.size.radius / 2.0;
-        let angle_offset = FRAC_PI_4; //45 deg in rad 
-
-        //need to move these new planets so that they are outside eachother's collision zone
-        let new_theta = theta + angle_offset;
-        let p1 = Movable::new(&ObjectType::BlackHole)
-            .set_position(
-                self.position.x + new_radius * new_theta.cos(),
-                self.position.y + new_radius * new_theta.sin(),
-            )
-            .set_velocity(
-                new_velocity * new_theta.cos(),
-                new_velocity * new_theta.sin(),
-            )
-            .set_radius(new_radius)
-            .build();
 
-        let new_theta = theta - angle_offset;
-        let p2 = Movable::new(&ObjectType::BlackHole)
-            .set_position(
-                self.position.x - new_radius * new_theta.cos(),
-                self.position.y - new_radius * new_theta.sin(),
-            )
-            .set_velocity(
-                new_velocity * new_theta.cos(),
-                new_velocity * new_theta.sin(),
-            )
-            .set_radius(new_radius)
-            .build();
+        let fragment = || {
+            Movable::new(&ObjectType::World)
+                .set_position(self.position.x, self.position.y)
+                .set_velocity(self.velocity.vx, self.velocity.vy)
+                .set_size(new_mass, new_radius)
+                .set_atmosphere(self.atmosphere)
+                .build()
+        };
 
-        (p1, p2)
+        (fragment(), fragment())
+    }
+
+    /// fn split_planets: static, private!
+    ///
+    /// given a slice of planet references involved in a collision together, splits
+    /// every planet in two and spreads all of the fragments evenly around a ring
+    /// centered on the group's center of mass. Placing the whole set (rather than each
+    /// pair around its own parent) keeps fragments of different parents from
+    /// overlapping and cascading into further splits. Each fragment moves at the
+    /// group's center of mass velocity plus, radially outward, half of its parent's
+    /// speed relative to it, so that the fragments fly apart
+    fn split_planets(items: &[&&Movable], universe: &UniverseConfig) -> Vec<Movable> {
+        //measured along the shortest path from the first planet, so that a split across
+        //an edge of the universe happens at the edge. Massless planets (e.g. the player's)
+        //are weighted equally when the group carries no mass at all
+        let total_mass: f32 = items.iter().map(|item| item.size.mass).sum();
+        let weight = |item: &Movable| {
+            if total_mass > 0.0 {
+                item.size.mass / total_mass
+            } else {
+                1.0 / items.len() as f32
+            }
+        };
+
+        let (mut cm_dx, mut cm_dy, mut cm_vx, mut cm_vy) = (0.0, 0.0, 0.0, 0.0);
+        for item in items {
+            let (dx, dy) = items[0].shortest_offset(item, universe);
+            cm_dx += weight(item) * dx;
+            cm_dy += weight(item) * dy;
+            cm_vx += weight(item) * item.velocity.vx;
+            cm_vy += weight(item) * item.velocity.vy;
+        }
+        let cm_x = items[0].position.x + cm_dx;
+        let cm_y = items[0].position.y + cm_dy;
+
+        let mut fragments = Vec::<(Movable, f32)>::new();
+        for item in items {
+            let speed = ((item.velocity.vx - cm_vx).squared()
+                + (item.velocity.vy - cm_vy).squared())
+            .sqrt()
+                / 2.0;
+            let (p1, p2) = item.split_planet();
+            fragments.push((p1, speed));
+            fragments.push((p2, speed));
+        }
+
+        //neighbours on a ring of n slots are 2 * ring * sin(PI / n) apart, which must
+        //clear the widest pair of hitboxes
+        let count = fragments.len() as f32;
+        let widest = fragments
+            .iter()
+            .map(|(fragment, _)| fragment.contact_distance(fragment) / 2.0)
+            .fold(0.0, f32::max);
+        let ring = Movable::SPLIT_CLEARANCE * widest / (PI / count).sin();
+
+        //the ring starts at the first parent's heading so that a lone glancing planet
+        //pair splits roughly along its direction of travel
+        let base = (items[0].velocity.vy - cm_vy).atan2(items[0].velocity.vx - cm_vx);
+
+        fragments
+            .into_iter()
+            .enumerate()
+            .map(|(index, (mut fragment, speed))| {
+                let theta = base + 2.0 * PI * index as f32 / count;
+                fragment
+                    .set_position(cm_x + ring * theta.cos(), cm_y + ring * theta.sin())
+                    .set_velocity(cm_vx + speed * theta.cos(), cm_vy + speed * theta.sin())
+                    .wrap(universe);
+                fragment.position.x_prev = fragment.position.x;
+                fragment.position.y_prev = fragment.position.y;
+                fragment
+            })
+            .collect()
     }
 
     /// fn process_collisions: static
     ///
     /// given a slice of Movable references all involved in a collision together,
     /// process the mathematics of the collision and returns the resultant object(s)
-    /// as a CollisionResults enum variant
-    pub fn process_collisions(items: &[&&Movable]) -> CollisionResult {
+    /// as a CollisionResults enum variant. What the collision produces is decided by
+    /// the CollisionBehavior of the colliding object types (see outcomes.rs).
+    ///
+    /// A merge whose summed mass is below bh_threshold does not form a black hole:
+    /// the product is a planet (ObjectType::World) instead. The products are kept on
+    /// the field of universe
+    pub fn process_collisions(
+        items: &[&&Movable],
+        bh_threshold: f32,
+        universe: &UniverseConfig,
+    ) -> CollisionResult {
         let count = items.len();
         if count == 0 {
             return CollisionResult::None;
         }
 
-        let bh_count: i32 = items
-            .iter()
-            .map(|x| {
-                if x.otype == ObjectType::BlackHole {
-                    1
-                } else {
-                    0
+        //the highest precedence outcome of any pair decides for the whole group
+        let kinds: Vec<ObjectType> = items.iter().map(|item| item.otype).collect();
+
+        match group_outcome(&kinds) {
+            CollisionOutcome::Merge => {
+                let mut cur = Movable::generate_blackhole(items[0], items[1], universe);
+
+                for item in items.iter().take(count).skip(2) {
+                    cur = Movable::generate_blackhole(&cur, item, universe); //like a cumsum
                 }
-            })
-            .sum();
 
-        if bh_count > 0 {
-            //then the result must be a bh
-            let mut cur = Movable::generate_blackhole(items[0], items[1]);
+                if cur.size.mass < bh_threshold {
+                    cur = Movable::new(&ObjectType::World)
+                        .set_position(cur.position.x, cur.position.y)
+                        .set_velocity(cur.velocity.vx, cur.velocity.vy)
+                        .set_mass(cur.size.mass)
+                        .clamp_radius(universe)
+                        .build();
+                }
 
-            for item in items.iter().take(count).skip(2) {
-                cur = Movable::generate_blackhole(&cur, item); //like a cumsum
+                CollisionResult::Single(cur)
+            }
+            CollisionOutcome::Bounce => CollisionResult::NSize(Movable::bounce(items, universe)),
+            CollisionOutcome::Split => {
+                CollisionResult::NSize(Movable::split_planets(items, universe))
             }
+        }
+    }
 
-            CollisionResult::Single(cur)
+    /// fn bounce: static, private!
+    ///
+    /// given a slice of Movable references involved in a collision together, returns
+    /// new Movables which rebound off one another. Each velocity is reflected about the
+    /// group's center of mass velocity (v' = 2 * v_cm - v), which is the exact elastic
+    /// result for a head-on collision and conserves momentum. The objects are then
+    /// separated so that they do not collide again on the next frame
+    fn bounce(items: &[&&Movable], universe: &UniverseConfig) -> Vec<Movable> {
+        let total_mass: f32 = items.iter().map(|item| item.size.mass).sum();
+        let (cm_vx, cm_vy) = if total_mass > 0.0 {
+            (
+                items
+                    .iter()
+                    .map(|item| item.size.mass * item.velocity.vx)
+                    .sum::<f32>()
+                    / total_mass,
+                items
+                    .iter()
+                    .map(|item| item.size.mass * item.velocity.vy)
+                    .sum::<f32>()
+                    / total_mass,
+            )
         } else {
-            //only planets in this collision
-            let mut vec = Vec::<Movable>::new();
-            for item in items.iter().take(count) {
-                let (p1, p2) = item.split_planet();
-                vec.push(p1);
-                vec.push(p2);
+            (0.0, 0.0)
+        };
+
+        let mut vec: Vec<Movable> = items
+            .iter()
+            .map(|item| {
+                Movable::new(&item.otype)
+                    .set_position(item.position.x, item.position.y)
+                    .set_velocity(
+                        2.0 * cm_vx - item.velocity.vx,
+                        2.0 * cm_vy - item.velocity.vy,
+                    )
+                    .set_size(item.size.mass, item.size.radius)
+                    .set_atmosphere(item.atmosphere)
+                    .build()
+            })
+            .collect();
+
+        for index in 1..vec.len() {
+            let (placed, rest) = vec.split_at_mut(index);
+            for other in placed.iter() {
+                rest[0].clear_bubble(other, 1.0, universe);
             }
+        }
 
-            CollisionResult::NSize(vec)
+        vec
+    }
+}
+
+/// CollisionBehavior is a trait defined within this crate (see outcomes.rs).
+/// Every object type declares here what colliding with every other type produces
+impl CollisionBehavior for ObjectType {
+    fn collision_outcome(&self, other: &Self) -> CollisionOutcome {
+        match (self, other) {
+            //anything swallowed by a black hole becomes part of the black hole
+            (ObjectType::BlackHole, _) | (_, ObjectType::BlackHole) => CollisionOutcome::Merge,
+            (ObjectType::World, ObjectType::World) => CollisionOutcome::Split,
         }
     }
 }
@@ -654,7 +1167,11 @@ impl PartialOrd for Movable {
 }
 
 /// the Ord trait is implemented for Movable so that
-/// Movable references can be used in BTreeSets
+/// Movable references can be used in BTreeSets. Objects are ordered by id
+/// alone, with no secondary key: ids are unique among the live objects (see
+/// OBJECTCOUNT), so Equal means the very same object. Copies made by build()
+/// keep their id and must replace, never join, the original; collision_pass and
+/// CollisionSet::append check this invariant in debug builds
 impl Ord for Movable {
     fn cmp(&self, other: &Self) -> Ordering {
         let my_index = self.get_id();
@@ -688,6 +1205,7 @@ impl Default for Movable {
                 radius: 0.0,
                 mass: 0.0,
             },
+            atmosphere: Movable::PLANET_ATMOSPHERE,
         }
     }
 }
@@ -701,8 +1219,827 @@ impl CollisionDetection for Movable {
         self.position
     }
 
-    /// describes how to define the objects hit box as a geometric shape
+    /// describes how to define the objects hit box as a geometric shape. Each ObjectType
+    /// declares its own: the hitbox is independent of the mesh drawn for the object
     fn get_hitbox(&self) -> Shapes {
-        Shapes::Circle(self.size.radius)
+        match self.otype {
+            ObjectType::BlackHole => Shapes::Circle(self.size.radius), //the event horizon
+            ObjectType::World => Shapes::Circle(self.size.radius * self.atmosphere),
+        }
+    }
+}
+
+/// fn test_planet_bubble ensures that black holes placed on, near, and far from
+/// the planet never collide with it on the first simulated frame once
+/// Movable::clear_bubble has been applied.
+#[test]
+fn test_planet_bubble() {
+    use crate::objects::gamestate::PLANET_CLEARANCE;
+
+    let planet = Movable::new(&ObjectType::World)
+        .set_position(100.0, -200.0)
+        .set_size(0.0, 50.0)
+        .build();
+
+    let mut black_holes: Vec<Movable> = [(100.0, -200.0), (150.0, -180.0), (5_000.0, 5_000.0)]
+        .iter()
+        .map(|(x, y)| {
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(*x, *y)
+                .set_velocity(-1_000.0, 0.0)
+                .set_mass(20.0)
+                .build()
+        })
+        .collect();
+
+    let moved: Vec<bool> = black_holes
+        .iter_mut()
+        .map(|bh| bh.clear_bubble(&planet, PLANET_CLEARANCE, &UniverseConfig::default()))
+        .collect();
+    assert_eq!(moved, vec![true, true, false]);
+
+    //frame zero: step every object once and sweep for collisions
+    let mut planet = planet;
+    planet.set_velocity(1_000.0, 0.0);
+    planet.update_location(1.0 / 60.0);
+    for bh in &mut black_holes {
+        assert!(bh.position.distance_to(&planet.position) >= PLANET_CLEARANCE);
+        bh.update_location(1.0 / 60.0);
+        assert!(!bh.collided(&planet));
+    }
+}
+
+/// fn test_split_planets ensures that a collision of two planets produces four
+/// planet fragments, that no two fragments (siblings or not) overlap, that the
+/// split conserves mass and that the fragments are moving apart
+#[test]
+fn test_split_planets() {
+    let one = Movable::new(&ObjectType::World)
+        .set_position(0.0, 0.0)
+        .set_velocity(500.0, 0.0)
+        .set_radius(50.0)
+        .build();
+    let two = Movable::new(&ObjectType::World)
+        .set_position(60.0, 0.0)
+        .set_velocity(-500.0, 0.0)
+        .set_radius(50.0)
+        .build();
+
+    let fragments =
+        match Movable::process_collisions(&[&&one, &&two], 0.0, &UniverseConfig::default()) {
+            CollisionResult::NSize(n) => n,
+            _ => panic!("planet collisions must split into several fragments"),
+        };
+
+    assert_eq!(fragments.len(), 4);
+    for fragment in &fragments {
+        assert_eq!(fragment.otype, ObjectType::World);
+    }
+
+    let mass: f32 = fragments.iter().map(|fragment| fragment.size.mass).sum();
+    let total = one.size.mass + two.size.mass;
+    assert!((mass - total).abs() <= 1e-5 * total);
+
+    for (index, p1) in fragments.iter().enumerate() {
+        for p2 in fragments.iter().skip(index + 1) {
+            let gap = p1.position.distance_to(&p2.position);
+            assert!(gap > p1.contact_distance(p2));
+            assert!(!p1.collided(p2));
+
+            //one step later the fragments must be further apart
+            let mut p1 = p1.build();
+            let mut p2 = p2.build();
+            p1.update_location(1.0 / 60.0);
+            p2.update_location(1.0 / 60.0);
+            assert!(p1.position.distance_to(&p2.position) > gap);
+        }
+    }
+
+    //a massless planet (the player's) splits into massless fragments
+    let player = Movable::new(&ObjectType::World)
+        .set_position(0.0, 0.0)
+        .set_velocity(500.0, 0.0)
+        .set_size(0.0, 50.0)
+        .build();
+    match Movable::process_collisions(&[&&player, &&two], 0.0, &UniverseConfig::default()) {
+        CollisionResult::NSize(n) => {
+            let mass: f32 = n.iter().map(|fragment| fragment.size.mass).sum();
+            assert!((mass - two.size.mass).abs() <= 1e-5 * two.size.mass);
+        }
+        _ => panic!("planet collisions must split into several fragments"),
+    }
+}
+
+/// fn test_ids_past_u32 ensures that object IDs keep counting past u32::MAX
+/// without wrapping around and that black holes and planets never share an ID.
+///
+/// Rather than spawning 4 billion objects, the shared counter is fast-forwarded
+/// (fetch_max only ever moves it forward, keeping other tests' IDs unique)
+#[test]
+fn test_ids_past_u32() {
+    OBJECTCOUNT.fetch_max(u32::MAX as u64 - 2, SeqCst);
+
+    let live: Vec<Movable> = (0..8)
+        .map(|i| {
+            if i % 2 == 0 {
+                Movable::new(&ObjectType::BlackHole).build()
+            } else {
+                Movable::new(&ObjectType::World).build()
+            }
+        })
+        .collect();
+
+    let ids: BTreeSet<u64> = live.iter().map(|m| m.get_id()).collect();
+    assert_eq!(ids.len(), live.len());
+    assert!(ids.iter().any(|id| *id > u32::MAX as u64));
+    for (i, one) in live.iter().enumerate() {
+        for two in live.iter().skip(i + 1) {
+            assert!(one != two);
+        }
+    }
+}
+
+/// fn test_collision_outcomes ensures the default CollisionBehavior preserves the
+/// original rules: anything colliding with a black hole merges into a single black
+/// hole, while planets colliding only with planets split apart
+#[test]
+fn test_collision_outcomes() {
+    use ObjectType::{BlackHole, World};
+
+    assert_eq!(
+        BlackHole.collision_outcome(&BlackHole),
+        CollisionOutcome::Merge
+    );
+    assert_eq!(BlackHole.collision_outcome(&World), CollisionOutcome::Merge);
+    assert_eq!(World.collision_outcome(&BlackHole), CollisionOutcome::Merge);
+    assert_eq!(World.collision_outcome(&World), CollisionOutcome::Split);
+    assert_eq!(
+        group_outcome(&[World, World, BlackHole]),
+        CollisionOutcome::Merge
+    );
+    assert_eq!(group_outcome(&[World, World]), CollisionOutcome::Split);
+    assert_eq!(group_outcome(&[BlackHole]), CollisionOutcome::Split);
+
+    let bh = Movable::new(&BlackHole)
+        .set_position(0.0, 0.0)
+        .set_mass(10.0)
+        .build();
+    let planet = Movable::new(&World)
+        .set_position(10.0, 0.0)
+        .set_radius(50.0)
+        .build();
+    let other_planet = Movable::new(&World)
+        .set_position(-10.0, 0.0)
+        .set_radius(50.0)
+        .build();
+
+    match Movable::process_collisions(
+        &[&&planet, &&bh, &&other_planet],
+        0.0,
+        &UniverseConfig::default(),
+    ) {
+        CollisionResult::Single(n) => assert_eq!(n.otype, BlackHole),
+        _ => panic!("a collision with a black hole must merge into one black hole"),
     }
 }
+
+/// fn test_wrap_offsets ensures ghosts are only requested for objects whose
+/// circle crosses an edge of the universe, and on the opposite side of that edge
+#[test]
+fn test_wrap_offsets() {
+    use crate::objects::gamestate::UNIVERSE_SIZE;
+
+    let edge = 0.5 * UNIVERSE_SIZE;
+    let mut bh = Movable::new(&ObjectType::BlackHole)
+        .set_position(0.0, 0.0)
+        .set_mass(20.0)
+        .build();
+    assert!(bh.wrap_offsets(&UniverseConfig::default()).is_empty());
+
+    bh.set_position(edge - 10.0, 0.0);
+    assert_eq!(
+        bh.wrap_offsets(&UniverseConfig::default()),
+        vec![Vec2::new(-UNIVERSE_SIZE, 0.0)]
+    );
+
+    bh.set_position(-edge + 10.0, -edge + 10.0);
+    assert_eq!(
+        bh.wrap_offsets(&UniverseConfig::default()),
+        vec![
+            Vec2::new(UNIVERSE_SIZE, 0.0),
+            Vec2::new(0.0, UNIVERSE_SIZE),
+            Vec2::new(UNIVERSE_SIZE, UNIVERSE_SIZE),
+        ]
+    );
+}
+
+/// fn test_merge_below_threshold ensures two light black holes whose summed mass
+/// is below the black hole threshold merge into a planet
+#[test]
+fn test_merge_below_threshold() {
+    let one = Movable::new(&ObjectType::BlackHole)
+        .set_position(0.0, 0.0)
+        .set_mass(2.0)
+        .build();
+    let two = Movable::new(&ObjectType::BlackHole)
+        .set_position(5.0, 0.0)
+        .set_mass(2.0)
+        .build();
+
+    match Movable::process_collisions(&[&&one, &&two], 5.0, &UniverseConfig::default()) {
+        CollisionResult::Single(n) => {
+            assert_eq!(n.otype, ObjectType::World);
+            assert_eq!(n.size.mass, 4.0);
+        }
+        _ => panic!("a merge must produce a single object"),
+    }
+}
+
+/// fn test_merge_above_threshold ensures two black holes whose summed mass
+/// reaches the black hole threshold still merge into a black hole
+#[test]
+fn test_merge_above_threshold() {
+    let one = Movable::new(&ObjectType::BlackHole)
+        .set_position(0.0, 0.0)
+        .set_mass(3.0)
+        .build();
+    let two = Movable::new(&ObjectType::BlackHole)
+        .set_position(5.0, 0.0)
+        .set_mass(2.0)
+        .build();
+
+    match Movable::process_collisions(&[&&one, &&two], 5.0, &UniverseConfig::default()) {
+        CollisionResult::Single(n) => {
+            assert_eq!(n.otype, ObjectType::BlackHole);
+            assert_eq!(n.size.mass, 5.0);
+        }
+        _ => panic!("a merge must produce a single object"),
+    }
+}
+
+/// fn test_radius_clamp ensures that no object can be clamped to a radius exceeding
+/// MAX_RADIUS_FRACTION of the shorter side of the universe, however absurd the request,
+/// and that merge products are clamped to the universe they merge in
+#[test]
+fn test_radius_clamp() {
+    use crate::objects::gamestate::{MAX_RADIUS_FRACTION, UNIVERSE_SIZE};
+
+    let universe = UniverseConfig {
+        width: 2.0 * UNIVERSE_SIZE,
+        height: UNIVERSE_SIZE,
+    };
+    let max_radius = MAX_RADIUS_FRACTION * UNIVERSE_SIZE;
+    assert_eq!(universe.max_radius(), max_radius);
+
+    let bh = Movable::new(&ObjectType::BlackHole)
+        .set_mass(1.0e9)
+        .clamp_radius(&universe)
+        .build();
+    assert_eq!(bh.size.radius, max_radius);
+    assert_eq!(bh.size.mass, 1.0e9);
+
+    let planet = Movable::new(&ObjectType::World)
+        .set_mass(1.0e12)
+        .clamp_radius(&universe)
+        .build();
+    assert_eq!(planet.size.radius, max_radius);
+
+    let one = Movable::new(&ObjectType::BlackHole).set_mass(1.0e8).build();
+    let two = Movable::new(&ObjectType::BlackHole).set_mass(1.0e8).build();
+    match Movable::process_collisions(&[&&one, &&two], 0.0, &universe) {
+        CollisionResult::Single(n) => assert_eq!(n.size.radius, max_radius),
+        _ => panic!("a merge must produce a single object"),
+    }
+
+    //sensible sizes are untouched
+    let bh = Movable::new(&ObjectType::BlackHole)
+        .set_mass(20.0)
+        .clamp_radius(&universe)
+        .build();
+    assert_eq!(bh.size.radius, 60.0);
+}
+
+/// fn test_radius_round_trip ensures set_radius inverts set_mass: exactly for black holes
+/// and within float tolerance for planets, on both sides of the planet branch change
+#[test]
+fn test_radius_round_trip() {
+    for mass in [0.5, 1.0, 2.5, 10.0, 100.0, 1_000.0] {
+        let radius = Movable::new(&ObjectType::BlackHole)
+            .set_mass(mass)
+            .build()
+            .size
+            .radius;
+        let bh = Movable::new(&ObjectType::BlackHole)
+            .set_radius(radius)
+            .build();
+        assert_eq!(bh.size.mass, mass);
+    }
+
+    let mut previous = 0.0;
+    for mass in [
+        1.0, 2.0, 4.0, 4.4, 4.5, 5.0, 10.0, 127.0, 128.0, 815.0, 1.0e5,
+    ] {
+        let radius = Movable::new(&ObjectType::World)
+            .set_mass(mass)
+            .build()
+            .size
+            .radius;
+        assert!(radius > previous, "a heavier planet is never smaller");
+        previous = radius;
+
+        let planet = Movable::new(&ObjectType::World).set_radius(radius).build();
+        assert!(
+            (planet.size.mass - mass).abs() <= 1e-4 * mass,
+            "mass {mass} came back as {}",
+            planet.size.mass
+        );
+    }
+}
+
+/// fn test_atmosphere ensures a planet's hitbox follows its atmosphere factor while its
+/// visual radius, and a black hole's hitbox, do not
+#[test]
+fn test_atmosphere() {
+    let planet = Movable::new(&ObjectType::World)
+        .set_size(1.0, 10.0)
+        .set_atmosphere(1.5)
+        .build();
+    let black_hole = Movable::new(&ObjectType::BlackHole)
+        .set_size(1.0, 10.0)
+        .set_atmosphere(1.5)
+        .build();
+
+    assert_eq!(planet.size.radius, 10.0);
+    assert!(matches!(planet.get_hitbox(), Shapes::Circle(r) if r == 15.0));
+    assert!(matches!(black_hole.get_hitbox(), Shapes::Circle(r) if r == 10.0));
+    assert_eq!(planet.contact_distance(&black_hole), 25.0);
+
+    //a planet without an atmosphere collides at its visual radius
+    let bare = Movable::new(&ObjectType::World).set_size(1.0, 10.0).build();
+    assert!(matches!(bare.get_hitbox(), Shapes::Circle(r) if r == 10.0));
+}
+
+/// fn test_merge_center_of_mass ensures a merge product sits at the exact center of
+/// mass of the pair, including when the pair straddles an edge of the universe
+#[test]
+fn test_merge_center_of_mass() {
+    use crate::objects::gamestate::UNIVERSE_SIZE;
+
+    let merged = |one: &Movable, two: &Movable| match Movable::process_collisions(
+        &[&one, &two],
+        0.0,
+        &UniverseConfig::default(),
+    ) {
+        CollisionResult::Single(merged) => (merged.position.x, merged.position.y),
+        _ => panic!("two black holes must merge into one"),
+    };
+
+    let light = Movable::new(&ObjectType::BlackHole)
+        .set_position(-100.0, 50.0)
+        .set_mass(1.0)
+        .build();
+    let heavy = Movable::new(&ObjectType::BlackHole)
+        .set_position(200.0, 50.0)
+        .set_mass(2.0)
+        .build();
+    assert_eq!(merged(&light, &heavy), (100.0, 50.0));
+
+    //either side of the right/left edge: the product belongs on the edge, not the center
+    let edge = 0.5 * UNIVERSE_SIZE;
+    let left = Movable::new(&ObjectType::BlackHole)
+        .set_position(-edge + 100.0, 0.0)
+        .set_mass(1.0)
+        .build();
+    let right = Movable::new(&ObjectType::BlackHole)
+        .set_position(edge - 100.0, 0.0)
+        .set_mass(1.0)
+        .build();
+    let (x, y) = merged(&left, &right);
+    assert!((x.abs() - edge).abs() < 1e-2 && y == 0.0);
+}
+
+/// fn test_merge_speed_cap ensures the speed, not each axis, is capped at
+/// Movable::MAXVELOCITY and that a high-speed merge never yields a faster resultant
+#[test]
+fn test_merge_speed_cap() {
+    //each axis alone is within the cap, the speed is not
+    let fast = 0.9 * Movable::MAXVELOCITY;
+    let capped = Movable::new(&ObjectType::BlackHole)
+        .set_velocity(-fast, fast)
+        .build();
+    assert!((capped.speed() - Movable::MAXVELOCITY).abs() < 1e-2);
+    assert_eq!(capped.velocity.vx, -capped.velocity.vy);
+
+    //gravity may accelerate objects past the cap between merges
+    let mut one = Movable::new(&ObjectType::BlackHole)
+        .set_position(0.0, 0.0)
+        .set_mass(1.0)
+        .build();
+    let mut two = Movable::new(&ObjectType::BlackHole)
+        .set_position(10.0, 0.0)
+        .set_mass(1.0)
+        .build();
+    one.velocity = Velocity {
+        vx: 2.0 * Movable::MAXVELOCITY,
+        vy: 0.0,
+    };
+    two.velocity = Velocity {
+        vx: Movable::MAXVELOCITY,
+        vy: Movable::MAXVELOCITY,
+    };
+
+    match Movable::process_collisions(&[&&one, &&two], 0.0, &UniverseConfig::default()) {
+        CollisionResult::Single(merged) => {
+            assert!(merged.speed() <= Movable::MAXVELOCITY * (1.0 + 1e-6));
+            assert!(merged.velocity.vx > 2.0 * merged.velocity.vy);
+        }
+        _ => panic!("two black holes must merge into one"),
+    }
+}
+
+/// fn test_collision_set_algebra ensures CollisionSet's intersect and union work on
+/// object ids and that merge_intersection only combines sets sharing a member
+#[test]
+fn test_collision_set_algebra() {
+    let objects: Vec<Movable> = (0..5)
+        .map(|i| {
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(100.0 * i as f32, 0.0)
+                .set_mass(1.0)
+                .build()
+        })
+        .collect();
+    let ids = |set: &CollisionSet| set.data.iter().map(|m| m.get_id()).collect::<Vec<u64>>();
+    let set = |members: &[usize]| {
+        let mut set = CollisionSet::new();
+        for &member in members {
+            set.append(&objects[member]);
+        }
+        set
+    };
+
+    let ab = set(&[0, 1]);
+    let bc = set(&[1, 2]);
+    let de = set(&[3, 4]);
+
+    //appending a member twice keeps one copy
+    let mut again = set(&[0, 1]);
+    assert!(!again.append(&objects[1]));
+    assert_eq!(again.len(), 2);
+
+    assert_eq!(ids(&ab.intersect(&bc)), vec![objects[1].get_id()]);
+    assert!(ab.intersect(&de).is_empty());
+
+    let union = ab.union(&bc);
+    assert_eq!(
+        ids(&union),
+        vec![
+            objects[0].get_id(),
+            objects[1].get_id(),
+            objects[2].get_id()
+        ]
+    );
+
+    let merged = CollisionSet::merge_intersection(&ab, &bc).expect("ab and bc share b");
+    assert_eq!(ids(&merged), ids(&union));
+    assert!(CollisionSet::merge_intersection(&ab, &de).is_none());
+
+    //a CollisionFrame merges chained collisions and keeps disjoint ones apart
+    let mut frame = CollisionFrame::new();
+    assert!(!frame.push(ab));
+    assert!(!frame.push(de));
+    assert!(frame.push(bc));
+    assert_eq!(frame.array.len(), 2);
+    assert_eq!(frame.array[0].len(), 3);
+}
+
+/// fn test_bridging_sets ensures a CollisionSet bridging two earlier, disjoint ones
+/// merges all three: chained pairs 0-5, 1-6 and 5-6 collide together, once each, so the
+/// merged mass is counted once
+#[test]
+fn test_bridging_sets() {
+    let objects: Vec<Movable> = (0..7)
+        .map(|i| {
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(100.0 * i as f32, 0.0)
+                .set_mass(1.0)
+                .build()
+        })
+        .collect();
+    let pair = |one: usize, two: usize| {
+        let mut set = CollisionSet::new();
+        set.append(&objects[one]);
+        set.append(&objects[two]);
+        set
+    };
+
+    let mut frame = CollisionFrame::new();
+    assert!(!frame.push(pair(0, 5)));
+    assert!(!frame.push(pair(1, 6)));
+    assert!(frame.push(pair(5, 6)));
+    assert_eq!(frame.array.len(), 1);
+    assert_eq!(
+        frame.array[0].ids(),
+        [0, 1, 5, 6].map(|i| objects[i].get_id()).to_vec()
+    );
+
+    //a pair already inside the merged set changes nothing
+    assert!(frame.push(pair(0, 1)));
+    assert_eq!(frame.array.len(), 1);
+
+    let universe = UniverseConfig::default();
+    let lineage = frame.collect_lineage(0.0, &universe);
+    assert_eq!(lineage.len(), 1);
+    let (_, _, products) = &lineage[0];
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0].size.mass, 4.0);
+}
+
+/// fn test_consumed_planet ensures only a collision between the given planet and black
+/// holes which produces a black hole reports the black holes' combined mass
+#[test]
+fn test_consumed_planet() {
+    let heavy = Movable::new(&ObjectType::BlackHole).set_mass(30.0).build();
+    let light = Movable::new(&ObjectType::BlackHole).set_mass(8.0).build();
+    let planet = Movable::new(&ObjectType::World).set_size(1.0, 50.0).build();
+    let other_planet = Movable::new(&ObjectType::World).set_size(1.0, 50.0).build();
+    let universe = UniverseConfig::default();
+
+    let consumed = |parents: &[&Movable], bh_threshold: f32| {
+        let mut set = CollisionSet::new();
+        for parent in parents {
+            set.append(*parent);
+        }
+        let mut frame = CollisionFrame::new();
+        frame.push(set);
+
+        let lineage = frame.collect_lineage(bh_threshold, &universe);
+        let [(_, _, products)] = &lineage[..] else {
+            panic!("one set collides into one lineage entry");
+        };
+        Movable::consumed_planet(planet.get_id(), parents, products)
+    };
+
+    assert_eq!(consumed(&[&heavy, &light], 0.0), None);
+    assert_eq!(consumed(&[&planet, &other_planet], 0.0), None);
+    assert_eq!(consumed(&[&heavy, &planet], 0.0), Some(30.0));
+    assert_eq!(consumed(&[&heavy, &light, &planet], 0.0), Some(38.0));
+
+    //some other planet (a fragment, a sandbox planet) is not the one being followed
+    assert_eq!(consumed(&[&heavy, &other_planet], 0.0), None);
+
+    //a merge too light to stay a black hole respawns as a planet: nothing was consumed
+    assert_eq!(consumed(&[&light, &planet], 20.0), None);
+    assert_eq!(consumed(&[&heavy, &planet], 20.0), Some(30.0));
+}
+
+/// fn test_duplicate_id ensures a CollisionSet refuses, in debug builds, a distinct
+/// object sharing the id of one of its members rather than silently dropping it
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "share the id")]
+fn test_duplicate_id() {
+    let original = Movable::new(&ObjectType::BlackHole).set_mass(5.0).build();
+    let copy = original.build();
+
+    let mut set = CollisionSet::new();
+    assert!(set.append(&original));
+    assert!(!set.append(&original));
+    set.append(&copy);
+}
+
+/// fn test_parallel_collect ensures a frame of many independent collisions, collided in
+/// parallel, produces the same objects in the same (ID) order as colliding its sets one
+/// at a time
+#[test]
+fn test_parallel_collect() {
+    let objects: Vec<Movable> = (0..64)
+        .map(|i| {
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(1_000.0 * (i / 2) as f32, (i % 2) as f32)
+                .set_velocity(10.0 * i as f32, -5.0 * i as f32)
+                .set_mass(1.0 + i as f32)
+                .build()
+        })
+        .collect();
+
+    let mut frame = CollisionFrame::new();
+    for pair in objects.chunks(2) {
+        let mut set = CollisionSet::new();
+        set.append(&pair[0]);
+        set.append(&pair[1]);
+        frame.push(set);
+    }
+
+    let serial: Vec<Movable> = frame
+        .array
+        .iter()
+        .filter_map(|set| match set.collide(0.0, &UniverseConfig::default()) {
+            CollisionResult::Single(n) => Some(n),
+            _ => None,
+        })
+        .collect();
+
+    match frame.collect(0.0, &UniverseConfig::default()) {
+        CollisionResult::NSize(parallel) => {
+            assert_eq!(parallel.len(), serial.len());
+            assert!(parallel.windows(2).all(|w| w[0].get_id() < w[1].get_id()));
+            for (p, s) in parallel.iter().zip(serial.iter()) {
+                assert_eq!((p.position.x, p.position.y), (s.position.x, s.position.y));
+                assert_eq!(
+                    (p.velocity.vx, p.velocity.vy),
+                    (s.velocity.vx, s.velocity.vy)
+                );
+                assert_eq!(p.size.mass, s.size.mass);
+            }
+        }
+        _ => panic!("a frame of collisions must produce objects"),
+    }
+}
+
+/// fn test_softening ensures the acceleration at a fixed separation follows the
+/// configured softening: G * m / (r^2 + softening)
+#[test]
+fn test_softening() {
+    let one = Movable::new(&ObjectType::BlackHole)
+        .set_position(0.0, 0.0)
+        .set_mass(1.0)
+        .build();
+    let two = Movable::new(&ObjectType::BlackHole)
+        .set_position(100.0, 0.0)
+        .set_mass(1.0)
+        .build();
+
+    let acceleration = |softening: f32| {
+        let acc = one.calculate_acceleration(
+            &two,
+            &PhysicsConfig {
+                softening,
+                ..Default::default()
+            },
+            &UniverseConfig::default(),
+        );
+        assert_eq!(acc.ay, 0.0);
+        acc.ax
+    };
+
+    let default = one
+        .calculate_acceleration(&two, &PhysicsConfig::default(), &UniverseConfig::default())
+        .ax;
+    assert_eq!(default, acceleration(1000.0));
+    assert!((default - PhysicsConfig::default().gravity_constant / 11_000.0).abs() < 1e-2);
+
+    //r^2 = 10_000: raising the softening from 1_000 to 21_000 smooths the pull to 11 / 31
+    let ratio = acceleration(21_000.0) / default;
+    assert!((ratio - 11.0 / 31.0).abs() < 1e-5);
+    assert!(acceleration(0.0) > default);
+}
+
+/// fn test_gravity_constant ensures the acceleration scales with the configured
+/// PhysicsConfig gravity_constant
+#[test]
+fn test_gravity_constant() {
+    let one = Movable::new(&ObjectType::BlackHole).set_mass(1.0).build();
+    let two = Movable::new(&ObjectType::BlackHole)
+        .set_position(0.0, 100.0)
+        .set_mass(1.0)
+        .build();
+
+    let acceleration = |gravity_constant: f32| {
+        let config = PhysicsConfig {
+            gravity_constant,
+            ..Default::default()
+        };
+        one.calculate_acceleration(&two, &config, &UniverseConfig::default())
+            .ay
+    };
+
+    let default = acceleration(PhysicsConfig::default().gravity_constant);
+    assert!((acceleration(2.0E8) / default - 2.0).abs() < 1e-5);
+    assert!((acceleration(2.5E7) / default - 0.25).abs() < 1e-5);
+    assert_eq!(acceleration(0.0), 0.0);
+}
+
+/// fn test_acceleration_cap ensures the summed pull of 50 nearby masses, each already
+/// at MAXACCELERATION, is capped at MAXACCELERATION in total while keeping its direction
+#[test]
+fn test_acceleration_cap() {
+    let body = Movable::new(&ObjectType::BlackHole).set_mass(1.0).build();
+    let cluster: Vec<Movable> = (0..50)
+        .map(|i| {
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(100.0 + 2.0 * i as f32, 3.0 * (i % 5) as f32 - 6.0)
+                .set_mass(1_000.0)
+                .build()
+        })
+        .collect();
+    let others: Vec<&Movable> = cluster.iter().collect();
+    let config = PhysicsConfig::default();
+
+    //every single pull is already at the cap
+    for other in &others {
+        let single = body.calculate_acceleration(other, &config, &UniverseConfig::default());
+        let magnitude = (single.ax.squared() + single.ay.squared()).sqrt();
+        assert!((magnitude - Movable::MAXACCELERATION).abs() < 1e-1);
+    }
+
+    let time = 0.01;
+    let velocity = body.update_velocity(
+        &others,
+        time,
+        &GravityMatrix::default(),
+        &config,
+        &UniverseConfig::default(),
+    );
+    let speed = (velocity.vx.squared() + velocity.vy.squared()).sqrt();
+    assert!((speed - Movable::MAXACCELERATION * time).abs() < 1e-3);
+    assert!(velocity.vx > 0.99 * speed);
+}
+
+/// fn test_wrap ensures a step several universe widths long, in any direction, still
+/// lands the object on the field at the same place as a step of the remainder, and that
+/// the previous position follows it so the step keeps its length
+#[test]
+fn test_wrap() {
+    use crate::objects::gamestate::UNIVERSE_SIZE;
+
+    const BOUNDARY: f32 = 0.5 * UNIVERSE_SIZE;
+
+    let mut object = Movable::new(&ObjectType::BlackHole)
+        .set_position(100.0, -200.0)
+        .build();
+    object.velocity.vx = 2.0 * UNIVERSE_SIZE + 50.0;
+    object.velocity.vy = -3.0 * UNIVERSE_SIZE - 50.0;
+    object.update_location(1.0);
+    object.wrap(&UniverseConfig::default());
+
+    assert!((object.position.x - 150.0).abs() < 1e-2);
+    assert!((object.position.y + 250.0).abs() < 1e-2);
+    let step = (
+        object.position.x - object.position.x_prev,
+        object.position.y - object.position.y_prev,
+    );
+    assert!((step.0 - object.velocity.vx).abs() < 1e-1);
+    assert!((step.1 - object.velocity.vy).abs() < 1e-1);
+
+    let mut edge = Movable::new(&ObjectType::BlackHole)
+        .set_position(BOUNDARY, -BOUNDARY)
+        .build();
+    edge.wrap(&UniverseConfig::default());
+    assert_eq!((edge.position.x, edge.position.y), (BOUNDARY, -BOUNDARY));
+
+    edge.set_position(BOUNDARY + 10.0, -BOUNDARY - 10.0);
+    edge.wrap(&UniverseConfig::default());
+    assert!((edge.position.x + BOUNDARY - 10.0).abs() < 1e-2);
+    assert!((edge.position.y - BOUNDARY + 10.0).abs() < 1e-2);
+}
+
+/// fn test_rectangular_universe ensures a 2:1 universe wraps each axis at its own size
+/// and that gravity picks the shorter direction on each axis independently
+#[test]
+fn test_rectangular_universe() {
+    use crate::objects::gamestate::UNIVERSE_SIZE;
+
+    let wide = UniverseConfig {
+        width: 2.0 * UNIVERSE_SIZE,
+        height: UNIVERSE_SIZE,
+    };
+    let half = wide.half();
+
+    //past the square's edge, but still on the wide field
+    let mut object = Movable::new(&ObjectType::BlackHole)
+        .set_position(0.8 * UNIVERSE_SIZE, half.y + 100.0)
+        .build();
+    object.wrap(&wide);
+    assert_eq!(object.position.x, 0.8 * UNIVERSE_SIZE);
+    assert!((object.position.y + half.y - 100.0).abs() < 1e-2);
+
+    object.set_position(half.x + 100.0, 0.0);
+    object.wrap(&wide);
+    assert!((object.position.x + half.x - 100.0).abs() < 1e-2);
+
+    //the same pair pulls around the wrap of the square universe, but straight across the wide one
+    let one = Movable::new(&ObjectType::BlackHole)
+        .set_position(0.4 * UNIVERSE_SIZE, 0.4 * UNIVERSE_SIZE)
+        .build();
+    let two = Movable::new(&ObjectType::BlackHole)
+        .set_position(-0.4 * UNIVERSE_SIZE, -0.4 * UNIVERSE_SIZE)
+        .set_mass(10.0)
+        .build();
+    let config = PhysicsConfig::default();
+
+    let square = one.calculate_acceleration(&two, &config, &UniverseConfig::default());
+    assert!(square.ax > 0.0 && square.ay > 0.0);
+
+    let across = one.calculate_acceleration(&two, &config, &wide);
+    assert!(across.ax < 0.0 && across.ay > 0.0);
+
+    let (dx, dy) = one.shortest_offset(&two, &wide);
+    assert!((dx + 0.8 * UNIVERSE_SIZE).abs() < 1e-2);
+    assert!((dy - 0.2 * UNIVERSE_SIZE).abs() < 1e-2);
+    assert_eq!(
+        two.wrap_offsets(&wide),
+        Vec::<Vec2>::new(),
+        "a small object away from the edges needs no ghosts"
+    );
+}
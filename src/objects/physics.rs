@@ -0,0 +1,648 @@
+//! Physics.rs
+//!
+//! The physics module contains the per-frame physics passes as pure functions over
+//! slices of Movables, independent of Bevy and of rendering. The Bevy systems in
+//! plugins.rs call into these functions, and so can benchmarks and headless tests.
+//!
+//! In short:
+//! 1. gravity_pass - the O(N^2) acceleration / velocity update
+//! 2. collision_pass - the O(N^2) pairwise collision detection
+//! 3. step_world - one full headless frame: gravity, motion, wrap and collisions
+//! 4. interpolated_position - where to draw an object between two physics steps
+//!
+//! The ReferenceFrame selects whether the universe is simulated as is, or from its
+//! center of mass so that a drifting cluster stays centered (see remove_drift)
+//!
+//! The Bevy systems run these passes on a fixed timestep of PHYSICS_DT seconds
+
+use crate::objects::gamestate::UniverseConfig;
+use crate::objects::movables::{
+    CollisionFrame, CollisionResult, CollisionSet, GravityMatrix, Movable, PhysicsConfig, Velocity,
+};
+use crate::objects::traits::collisions::{CollisionDetection, Position};
+use bevy::prelude::{Resource, Vec2};
+use rayon::prelude::*;
+use std::collections::BTreeSet;
+
+/// The rate, in steps per second, of the fixed physics timestep (Bevy's FixedUpdate).
+/// Every physics step advances the universe by PHYSICS_DT seconds regardless of the
+/// display's frame rate, so a universe always evolves identically
+pub const PHYSICS_HZ: f64 = 120.0;
+
+/// The duration, in seconds, of one fixed physics step
+pub const PHYSICS_DT: f32 = (1.0 / PHYSICS_HZ) as f32;
+
+/// ReferenceFrame enum: Resource
+///
+/// The frame of reference the universe is simulated in. Toggled with the `F` key
+/// prior to launch (it is part of a Recording)
+/// 1. Universe - the objects move exactly as gravity dictates: a universe with net
+///    momentum slowly slides across the field (and wraps around it)
+/// 2. CenterOfMass - every step the center of mass velocity is removed from all objects
+///    so the cluster stays put. Only the view changes: relative motion is identical
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceFrame {
+    #[default]
+    Universe,
+    CenterOfMass,
+}
+
+impl ReferenceFrame {
+    /// fn toggle returns the other frame of reference
+    pub fn toggle(&self) -> Self {
+        match self {
+            ReferenceFrame::Universe => ReferenceFrame::CenterOfMass,
+            ReferenceFrame::CenterOfMass => ReferenceFrame::Universe,
+        }
+    }
+
+    /// fn name is the name of the frame of reference, as saved in a Recording
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReferenceFrame::Universe => "Universe",
+            ReferenceFrame::CenterOfMass => "CenterOfMass",
+        }
+    }
+
+    /// fn from_name is the inverse of fn name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [ReferenceFrame::Universe, ReferenceFrame::CenterOfMass]
+            .into_iter()
+            .find(|frame| frame.name() == name)
+    }
+}
+
+/// fn gravity_pass
+///
+/// returns the velocity of every object in objects after time seconds of gravitational
+/// attraction to all the others (see Movable::update_velocity), honoring which pairs
+/// attract (see GravityMatrix), the PhysicsConfig and the wrap of universe. The returned
+/// velocities are in the same order as objects
+pub fn gravity_pass(
+    objects: &[&Movable],
+    time: f32,
+    gravity: &GravityMatrix,
+    config: &PhysicsConfig,
+    universe: &UniverseConfig,
+) -> Vec<Velocity> {
+    objects
+        .iter()
+        .map(|movable| movable.update_velocity(objects, time, gravity, config, universe))
+        .collect()
+}
+
+/// fn interpolated_position
+///
+/// returns where to draw an object alpha (0.0 to 1.0) of the way from its previous physics
+/// position to its current one, so that motion stays smooth when the display and physics
+/// rates differ. alpha is the fraction of a physics step the display has overshot (see
+/// Time<Fixed>::overstep_fraction). A step which wrapped the object around the spherical
+/// universe starts past the edge it came in over (see Movable::wrap): the part of it
+/// drawn there is wrapped back onto the field, just inside the edge it left over, so the
+/// object never streaks across the screen. A step longer than half the universe (which
+/// no object can take at MAXVELOCITY) is drawn at its current position
+pub fn interpolated_position(position: &Position, alpha: f32, universe: &UniverseConfig) -> Vec2 {
+    let previous = Vec2::new(position.x_prev, position.y_prev);
+    let current = Vec2::new(position.x, position.y);
+    let step = current - previous;
+    let half = universe.half();
+
+    if step.x.abs() > half.x || step.y.abs() > half.y {
+        return current;
+    }
+
+    universe.wrap(previous.lerp(current, alpha.clamp(0.0, 1.0)))
+}
+
+/// fn remove_drift
+///
+/// subtracts the center of mass velocity of objects, whose new velocities are given
+/// in the same order, from every velocity: a Galilean boost into the center of mass
+/// frame which leaves the net momentum zero. A massless universe is left unchanged
+pub fn remove_drift(objects: &[&Movable], velocities: &mut [Velocity]) {
+    let total_mass: f32 = objects.iter().map(|movable| movable.size.mass).sum();
+    if total_mass <= 0.0 {
+        return;
+    }
+
+    let (mut px, mut py) = (0.0, 0.0);
+    for (movable, velocity) in objects.iter().zip(velocities.iter()) {
+        px += movable.size.mass * velocity.vx;
+        py += movable.size.mass * velocity.vy;
+    }
+
+    for velocity in velocities.iter_mut() {
+        velocity.vx -= px / total_mass;
+        velocity.vy -= py / total_mass;
+    }
+}
+
+/// fn collision_pass
+///
+/// determines which objects collided during the last frame. Because this calculation is
+/// O(N^2) but is still embaressingly parallel, the rayon iterator parallelization logic is
+/// used to calculate the collisions of each object in parallel. The per-object results are
+/// gathered back in index order so the outcome never depends on thread scheduling, which
+/// keeps replays deterministic.
+///
+/// 2 collection types are returned:
+/// 1. BTreeSet<usize>: the indices (into objects) of every object involved in a collision.
+///    The BTreeSet automatically guarantees that duplicates will be removed.
+/// 2. CollisionFrame<'_>: see the movable.rs file for definition. In short, this is
+///    a smart-struct used to prevent duplicate collisions and properly coallesce collision results
+///
+/// A universe of zero or one object cannot collide: both collections are returned
+/// empty without spinning up the parallel machinery (e.g. at the end of every game)
+pub fn collision_pass<'a>(objects: &[&'a Movable]) -> (BTreeSet<usize>, CollisionFrame<'a>) {
+    if objects.len() <= 1 {
+        return (BTreeSet::new(), CollisionFrame::new());
+    }
+
+    //objects are told apart by id: two sharing one would never collide with each other
+    debug_assert!(
+        {
+            let ids: BTreeSet<u64> = objects.iter().map(|movable| movable.get_id()).collect();
+            ids.len() == objects.len()
+        },
+        "two distinct objects share an id"
+    );
+
+    //a lot of this complexity is to remove double counting and to handle group collisions
+    //a group collision would be one where more than 2 items collided together within the last frame -
+    //happens more often than one might think!
+    let sets: Vec<Option<CollisionSet<'a>>> = objects
+        .par_iter()
+        .map(|movable| {
+            let mut set = CollisionSet::new();
+            let mut collide = false;
+
+            for item in objects.iter() {
+                if item != movable && item.collided(*movable) {
+                    collide = true;
+                    set.append(*item);
+                }
+            }
+
+            if collide {
+                set.append(*movable);
+                Some(set)
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut colliding = BTreeSet::<usize>::new();
+    let mut frame = CollisionFrame::new();
+
+    for (index, set) in sets.into_iter().enumerate() {
+        if let Some(set) = set {
+            colliding.insert(index);
+            frame.push(set);
+        }
+    }
+
+    (colliding, frame)
+}
+
+/// fn step_world
+///
+/// advances the universe by time seconds without Bevy, exactly as a frame of the
+/// simulation would: gravity, then motion with the spherical wrap, then collisions.
+/// With the Verlet IntegratorMode of config the objects drift half the step before the
+/// gravity and the other half after it (see IntegratorMode::first_drift).
+/// Returns the objects making up the universe after the step; colliding objects are
+/// replaced by the results of their collisions. bh_threshold is the minimum mass of
+/// a merge product to remain a black hole, gravity selects which pairs attract,
+/// frame is the ReferenceFrame the step is taken in, config tunes the gravity calculation
+/// and universe is the field the objects wrap around
+pub fn step_world(
+    objects: &[Movable],
+    time: f32,
+    bh_threshold: f32,
+    gravity: &GravityMatrix,
+    frame: ReferenceFrame,
+    config: &PhysicsConfig,
+    universe: &UniverseConfig,
+) -> Vec<Movable> {
+    let first_drift = config.integrator.first_drift(time);
+
+    //build() sets the previous position to the current one, which is where it was moved from
+    let mut moved: Vec<Movable> = objects
+        .iter()
+        .map(|movable| {
+            let mut next = movable.build();
+            next.update_location(first_drift);
+            next.wrap(universe);
+            next
+        })
+        .collect();
+
+    let refs: Vec<&Movable> = moved.iter().collect();
+    let mut velocities = gravity_pass(&refs, time, gravity, config, universe);
+    if frame == ReferenceFrame::CenterOfMass {
+        remove_drift(&refs, &mut velocities);
+    }
+
+    for (next, velocity) in moved.iter_mut().zip(velocities) {
+        next.set_velocity(velocity.vx, velocity.vy);
+        next.update_location(time - first_drift);
+        next.wrap(universe);
+    }
+
+    let refs: Vec<&Movable> = moved.iter().collect();
+    let (colliding, frame) = collision_pass(&refs);
+
+    let mut next: Vec<Movable> = moved
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !colliding.contains(index))
+        .map(|(_, movable)| movable.build())
+        .collect();
+
+    match frame.collect(bh_threshold, universe) {
+        CollisionResult::Single(n) => next.push(n),
+        CollisionResult::NSize(mut n) => next.append(&mut n),
+        CollisionResult::None => {}
+    }
+
+    next
+}
+
+/// fn test_single_body ensures that collision_pass short-circuits for a universe of zero
+/// or one object: nothing collides and the frame is empty
+#[test]
+fn test_single_body() {
+    use crate::objects::movables::ObjectType;
+
+    let lone = Movable::new(&ObjectType::BlackHole).set_mass(10.0).build();
+
+    for objects in [vec![], vec![&lone]] {
+        let (colliding, frame) = collision_pass(&objects);
+        assert!(colliding.is_empty());
+        assert!(matches!(
+            frame.collect(0.0, &UniverseConfig::default()),
+            CollisionResult::None
+        ));
+    }
+
+    assert_eq!(
+        step_world(
+            std::slice::from_ref(&lone),
+            PHYSICS_DT,
+            0.0,
+            &GravityMatrix::default(),
+            ReferenceFrame::Universe,
+            &PhysicsConfig::default(),
+            &UniverseConfig::default(),
+        )
+        .len(),
+        1
+    );
+}
+
+/// fn test_wrap_sweep ensures an object wrapping off the right edge of the universe
+/// sweeps only the short step it took across that edge, not the whole field: a black
+/// hole sitting mid-field on the same row is left alone
+#[test]
+fn test_wrap_sweep() {
+    use crate::objects::gamestate::UNIVERSE_SIZE;
+    use crate::objects::movables::ObjectType;
+
+    let objects = vec![
+        Movable::new(&ObjectType::BlackHole)
+            .set_position(0.0, 0.0)
+            .set_mass(20.0)
+            .build(),
+        Movable::new(&ObjectType::World)
+            .set_position(0.5 * UNIVERSE_SIZE - 20.0, 0.0)
+            .set_velocity(5_000.0, 0.0)
+            .set_size(10.0, 50.0)
+            .build(),
+    ];
+
+    let next = step_world(
+        &objects,
+        PHYSICS_DT,
+        0.0,
+        &GravityMatrix::default(),
+        ReferenceFrame::Universe,
+        &PhysicsConfig::default(),
+        &UniverseConfig::default(),
+    );
+    assert_eq!(next.len(), 2);
+    assert_eq!(next[0].size.mass, 20.0);
+
+    let planet = &next[1];
+    assert_eq!(planet.otype, ObjectType::World);
+    assert!(planet.position.x < -0.5 * UNIVERSE_SIZE + 50.0);
+    assert!((planet.position.x - (-0.5 * UNIVERSE_SIZE - 20.0 + 5_000.0 * PHYSICS_DT)).abs() < 1.0);
+}
+
+/// fn test_gravity_matrix ensures that disabling black hole on planet gravity leaves a
+/// planet drifting in a straight line past a black hole, while the black hole is still
+/// pulled by the (massive) planet unless planet on black hole gravity is disabled too
+#[test]
+fn test_gravity_matrix() {
+    use crate::objects::movables::ObjectType;
+
+    let objects = vec![
+        Movable::new(&ObjectType::BlackHole)
+            .set_position(0.0, 2_000.0)
+            .set_mass(20.0)
+            .build(),
+        Movable::new(&ObjectType::World)
+            .set_position(-5_000.0, 0.0)
+            .set_velocity(1_000.0, 0.0)
+            .set_size(10.0, 50.0)
+            .build(),
+    ];
+
+    let gravity = GravityMatrix {
+        black_hole_on_planet: false,
+        ..GravityMatrix::default()
+    };
+
+    let mut stepped: Vec<Movable> = objects.iter().map(|movable| movable.build()).collect();
+    for _ in 0..PHYSICS_HZ as usize {
+        stepped = step_world(
+            &stepped,
+            PHYSICS_DT,
+            0.0,
+            &gravity,
+            ReferenceFrame::Universe,
+            &PhysicsConfig::default(),
+            &UniverseConfig::default(),
+        );
+    }
+
+    let black_hole = &stepped[0];
+    let planet = &stepped[1];
+    assert_eq!((planet.velocity.vx, planet.velocity.vy), (1_000.0, 0.0));
+    assert_eq!(planet.position.y, 0.0);
+    assert!((planet.position.x - -4_000.0).abs() < 1.0);
+    assert!(black_hole.velocity.vx != 0.0 || black_hole.velocity.vy != 0.0);
+
+    let gravity = GravityMatrix {
+        planet_on_black_hole: false,
+        ..gravity
+    };
+    let stepped = step_world(
+        &objects,
+        PHYSICS_DT,
+        0.0,
+        &gravity,
+        ReferenceFrame::Universe,
+        &PhysicsConfig::default(),
+        &UniverseConfig::default(),
+    );
+    assert_eq!((stepped[0].velocity.vx, stepped[0].velocity.vy), (0.0, 0.0));
+}
+
+/// fn test_center_of_mass_frame ensures the CenterOfMass frame halts the drift of a
+/// universe with net momentum while every relative position matches the same universe
+/// simulated in the Universe frame
+#[test]
+fn test_center_of_mass_frame() {
+    use crate::objects::movables::ObjectType;
+
+    let initial = || {
+        vec![
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(-3_000.0, 0.0)
+                .set_velocity(300.0, 200.0)
+                .set_mass(20.0)
+                .build(),
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(3_000.0, 0.0)
+                .set_velocity(100.0, 400.0)
+                .set_mass(10.0)
+                .build(),
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(0.0, 4_000.0)
+                .set_velocity(200.0, 300.0)
+                .set_mass(5.0)
+                .build(),
+        ]
+    };
+    let run = |frame: ReferenceFrame| {
+        let mut objects = initial();
+        for _ in 0..PHYSICS_HZ as usize {
+            objects = step_world(
+                &objects,
+                PHYSICS_DT,
+                0.0,
+                &GravityMatrix::default(),
+                frame,
+                &PhysicsConfig::default(),
+                &UniverseConfig::default(),
+            );
+        }
+        objects
+    };
+    let center = |objects: &[Movable]| {
+        let mass: f32 = objects.iter().map(|m| m.size.mass).sum();
+        (
+            objects
+                .iter()
+                .map(|m| m.size.mass * m.position.x)
+                .sum::<f32>()
+                / mass,
+            objects
+                .iter()
+                .map(|m| m.size.mass * m.position.y)
+                .sum::<f32>()
+                / mass,
+        )
+    };
+
+    let drifting = run(ReferenceFrame::Universe);
+    let centered = run(ReferenceFrame::CenterOfMass);
+    assert_eq!(drifting.len(), 3);
+    assert_eq!(centered.len(), 3);
+
+    //the drifting universe has moved, the centered one has (practically) not
+    let start = center(&initial());
+    let (dx, dy) = center(&drifting);
+    let (cx, cy) = center(&centered);
+    assert!((dx - start.0).hypot(dy - start.1) > 100.0);
+    assert!((cx - start.0).hypot(cy - start.1) < 5.0);
+
+    for index in 1..3 {
+        let relative = |objects: &[Movable]| {
+            (
+                objects[index].position.x - objects[0].position.x,
+                objects[index].position.y - objects[0].position.y,
+            )
+        };
+        let (ax, ay) = relative(&drifting);
+        let (bx, by) = relative(&centered);
+        assert!((ax - bx).hypot(ay - by) < 1.0);
+    }
+}
+
+/// fn test_integrator_mode ensures a light black hole on a circular orbit around a heavy
+/// one stays far closer to its orbit over a whole revolution when stepped with the
+/// Verlet IntegratorMode than with the default Euler one, at a coarse 10 Hz step
+#[test]
+fn test_integrator_mode() {
+    use crate::objects::movables::{IntegratorMode, ObjectType};
+
+    let config = |integrator: IntegratorMode| PhysicsConfig {
+        integrator,
+        ..Default::default()
+    };
+    let radius = 5_000.0f32;
+    let (mass, time) = (100.0f32, 0.1f32);
+    let defaults = PhysicsConfig::default();
+    let pull = defaults.gravity_constant * mass / (radius * radius + defaults.softening);
+    let speed = (pull * radius).sqrt();
+    let steps = (std::f32::consts::TAU * radius / speed / time) as usize;
+
+    let deviation = |integrator: IntegratorMode| {
+        let mut objects = vec![
+            Movable::new(&ObjectType::BlackHole)
+                .set_size(mass, 50.0)
+                .build(),
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(radius, 0.0)
+                .set_velocity(0.0, speed)
+                .set_size(0.001, 10.0)
+                .build(),
+        ];
+        let mut worst = 0.0f32;
+        for _ in 0..steps {
+            objects = step_world(
+                &objects,
+                time,
+                0.0,
+                &GravityMatrix::default(),
+                ReferenceFrame::Universe,
+                &config(integrator),
+                &UniverseConfig::default(),
+            );
+            assert_eq!(objects.len(), 2);
+            let separation = (objects[1].position.x - objects[0].position.x)
+                .hypot(objects[1].position.y - objects[0].position.y);
+            worst = worst.max((separation - radius).abs() / radius);
+        }
+        worst
+    };
+
+    let euler = deviation(IntegratorMode::Euler);
+    let verlet = deviation(IntegratorMode::Verlet);
+    assert!(euler > 0.005);
+    assert!(verlet < 0.002);
+    assert!(verlet < euler / 10.0);
+}
+
+/// fn test_coincident_objects ensures two stationary black holes spawned at the same
+/// point pull on each other in no direction and merge on the very first step
+#[test]
+fn test_coincident_objects() {
+    use crate::objects::movables::ObjectType;
+
+    let objects: Vec<Movable> = (0..2)
+        .map(|_| {
+            Movable::new(&ObjectType::BlackHole)
+                .set_position(250.0, -250.0)
+                .set_mass(5.0)
+                .build()
+        })
+        .collect();
+
+    let acceleration = objects[0].calculate_acceleration(
+        &objects[1],
+        &PhysicsConfig::default(),
+        &UniverseConfig::default(),
+    );
+    assert_eq!((acceleration.ax, acceleration.ay), (0.0, 0.0));
+    assert!(objects[0].collided(&objects[1]));
+
+    let next = step_world(
+        &objects,
+        PHYSICS_DT,
+        0.0,
+        &GravityMatrix::default(),
+        ReferenceFrame::Universe,
+        &PhysicsConfig::default(),
+        &UniverseConfig::default(),
+    );
+    assert_eq!(next.len(), 1);
+    assert_eq!(next[0].size.mass, 10.0);
+    assert_eq!((next[0].position.x, next[0].position.y), (250.0, -250.0));
+}
+
+/// fn test_interpolated_position ensures objects are drawn between their previous and
+/// current positions, and that a step which wrapped them around the universe is drawn
+/// on either side of the edge it crossed rather than across the field
+#[test]
+fn test_interpolated_position() {
+    use crate::objects::gamestate::UNIVERSE_SIZE;
+
+    let universe = UniverseConfig::default();
+    let moving = Position {
+        x_prev: 100.0,
+        y_prev: -50.0,
+        x: 200.0,
+        y: 50.0,
+    };
+    assert_eq!(
+        interpolated_position(&moving, 0.0, &universe),
+        Vec2::new(100.0, -50.0)
+    );
+    assert_eq!(
+        interpolated_position(&moving, 0.25, &universe),
+        Vec2::new(125.0, -25.0)
+    );
+    assert_eq!(
+        interpolated_position(&moving, 1.0, &universe),
+        Vec2::new(200.0, 50.0)
+    );
+
+    //off the right edge and back in on the left: Movable::wrap moved the previous
+    //position along, past the left edge
+    let wrapped = Position {
+        x_prev: -0.5 * UNIVERSE_SIZE - 10.0,
+        y_prev: 0.0,
+        x: -0.5 * UNIVERSE_SIZE + 10.0,
+        y: 0.0,
+    };
+    let drawn = interpolated_position(&wrapped, 0.25, &universe);
+    assert!((drawn.x - (0.5 * UNIVERSE_SIZE - 5.0)).abs() < 1e-2);
+    assert_eq!(
+        interpolated_position(&wrapped, 0.5, &universe),
+        Vec2::new(-0.5 * UNIVERSE_SIZE, 0.0)
+    );
+    assert_eq!(
+        interpolated_position(&wrapped, 1.0, &universe),
+        Vec2::new(wrapped.x, wrapped.y)
+    );
+
+    //a jump longer than half the universe is drawn where it landed
+    let jump = Position {
+        x_prev: -0.3 * UNIVERSE_SIZE,
+        y_prev: 0.0,
+        x: 0.3 * UNIVERSE_SIZE,
+        y: 0.0,
+    };
+    assert_eq!(
+        interpolated_position(&jump, 0.5, &universe),
+        Vec2::new(jump.x, jump.y)
+    );
+}
+
+/// fn test_duplicate_ids ensures collision_pass refuses, in debug builds, a universe
+/// holding two distinct objects with the same id
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "share an id")]
+fn test_duplicate_ids() {
+    use crate::objects::movables::ObjectType;
+
+    let original = Movable::new(&ObjectType::BlackHole).set_mass(5.0).build();
+    let mut copy = original.build();
+    copy.set_position(5_000.0, 0.0);
+
+    collision_pass(&[&original, &copy]);
+}
@@ -17,94 +17,543 @@
 //! 1. Any object rendering
 //! 2. Any object actions
 //! 3. Any user-interaction handling
+//!
+//! The simulation itself is the PhysicsPlugin, which BlackHoleUniverse adds beneath the
+//! interface. On its own it runs without a window or a GPU (see headless.rs)
 
 use crate::objects::button::{BtnState, GameOverBtn, gen_button, update_btn};
 use crate::objects::clocks::{BHCounter, TotalTime, WorldCounter, WorldTime};
-use crate::objects::gamestate::{GameState, ThePlanet, UNIVERSE_SIZE};
-use crate::objects::gauss::{Gauss, GaussBoundary};
+use crate::objects::controls::{
+    GAMEPAD_CURSOR_RADIUS, GAMEPAD_CURSOR_SPEED, GAMEPAD_DRAG_SPEED, GamepadCursor, LaunchConfig,
+    MouseLaunch, TouchLaunch,
+};
+use crate::objects::edges::{EDGE_MARKER_LENGTH, EdgeIndicators, edge_markers, wrapped_pull};
+use crate::objects::endgame::{BulletTime, FinalMergeNotice, final_merge_imminent};
+use crate::objects::entropy::{EntropyBar, EntropyMeter};
+use crate::objects::gamestate::{
+    BlackHoleThreshold, GameOver, GameStarted, GameState, Ghost, InfluenceRings, LaunchHint,
+    LaunchPrompt, MINIMUM_OBJECTS, Merge, PLANET_CLEARANCE, PlacementFeedback, PlacementNotice,
+    PlanetConsumed, PlanetFate, PlanetFateLabel, PlanetPlaced, RestartChoice, SandboxLabel,
+    SandboxMode, ThePlanet, UniverseConfig,
+};
+use crate::objects::history::MergeHistory;
+use crate::objects::hud::hud_scale;
+use crate::objects::inspector::{Hovered, INSPECTOR_OFFSET, Inspector, describe};
+use crate::objects::lensing::{LENS_LAYER_ALPHA, Lens, Lensing, lens_layers};
 use crate::objects::movables::{
-    CollisionFrame, CollisionResult, CollisionSet, Movable, ObjectType, Velocity,
+    GravityMatrix, IntegratorMode, Movable, ObjectType, PhysicsConfig, Velocity,
+};
+use crate::objects::physics::{
+    PHYSICS_HZ, ReferenceFrame, collision_pass, gravity_pass, interpolated_position, remove_drift,
 };
+use crate::objects::recording::{Recording, Replay};
+use crate::objects::settings::{
+    Setting, SettingsOpen, SettingsPanel, TimeScale, settings_closed, slider_from_softening,
+    slider_from_time_scale, softening_from_slider, time_scale_from_slider,
+};
+use crate::objects::shield::{Shield, ShieldHits, shielded_graze};
 use crate::objects::sliders::{
-    BLACKHOLE_COUNT_RNG, BLACKHOLE_MASS_RNG, BLACKHOLE_VEL_RNG, POSSTDEVMIN, SLIDERWIDTH,
-    SliderBkg, SliderType, SliderValue, VELSTDEVMIN, generate_slider,
+    BLACKHOLE_MASS_RNG, SLIDERWIDTH, SliderBkg, SliderType, SliderValue, generate_slider,
+};
+use crate::objects::slingshot::{
+    SLINGSHOT_DISPLAY_SECS, SLINGSHOT_RANGE_FACTOR, SlingshotNotice, SlingshotTracker,
 };
+use crate::objects::snapshot::{Universe, UniverseSnapshot};
+use crate::objects::stats::{LastMergeClock, STALE_TIMEOUT_SECS, SimStats, StableNotice};
+use crate::objects::theme::Theme;
 use crate::objects::traits::collisions::CollisionDetection;
+use crate::objects::traits::outcomes::CollisionOutcome;
+use crate::objects::universe::{
+    ClusterCount, ClusterLabel, NewGameConfig, PatternLabel, Scatter, StartingUniverse,
+    UniverseLayout, UniverseSettings, VelocityPattern,
+};
 use bevy::camera::ScalingMode;
 use bevy::ui::RelativeCursorPosition;
-use bevy::window::PrimaryWindow;
+use bevy::window::{PrimaryWindow, WindowResized};
 use bevy::{input_focus::InputFocus, prelude::*};
-use std::collections::BTreeSet;
-use std::sync::Mutex;
 
 /// Bevy plugin definition
-pub struct BlackHoleUniverse;
+///
+/// `BlackHoleUniverse::default()` runs the simulation as shipped. Embedders can
+/// configure it without editing the source by chaining the with_ methods below, e.g.
+/// `BlackHoleUniverse::default().with_seed(42).with_softening(500.0)`: each inserts
+/// the corresponding resource when the plugin is built. The simulation itself is
+/// configured on the PhysicsPlugin it adds (see fn physics_plugin)
+pub struct BlackHoleUniverse {
+    simulation: PhysicsPlugin,
+    objects: Option<Vec<Movable>>,
+    bullet_time: bool,
+}
+
+impl Default for BlackHoleUniverse {
+    fn default() -> Self {
+        BlackHoleUniverse {
+            simulation: PhysicsPlugin::default(),
+            objects: None,
+            bullet_time: false,
+        }
+    }
+}
+
+/// Bevy plugin definition
+///
+/// The simulation without any way to see or interact with it: the resources and messages
+/// of a universe and the physics systems stepping it on the fixed timestep. Added by
+/// BlackHoleUniverse beneath the interface, it also runs on its own on `MinimalPlugins`,
+/// e.g. in the integration tests (see headless.rs). Configured by the with_ methods of
+/// BlackHoleUniverse, from which it is taken with fn physics_plugin
+#[derive(Clone, Copy)]
+pub struct PhysicsPlugin {
+    seed: Option<u64>,
+    physics: PhysicsConfig,
+    gravity: GravityMatrix,
+    frame: ReferenceFrame,
+    black_hole_threshold: f32,
+    sandbox: bool,
+    stale_timeout: Option<f64>,
+    theme: Theme,
+    universe: UniverseConfig,
+    shield_hits: u32,
+}
+
+impl Default for PhysicsPlugin {
+    fn default() -> Self {
+        PhysicsPlugin {
+            seed: None,
+            physics: PhysicsConfig::default(),
+            gravity: GravityMatrix::default(),
+            frame: ReferenceFrame::default(),
+            black_hole_threshold: 0.0,
+            sandbox: false,
+            stale_timeout: Some(STALE_TIMEOUT_SECS),
+            theme: Theme::default(),
+            universe: UniverseConfig::default(),
+            shield_hits: 0,
+        }
+    }
+}
+
+impl BlackHoleUniverse {
+    /// fn with_seed: chain
+    ///
+    /// generates the first universe from seed instead of a random one. The `N` key and
+    /// restarts still draw new seeds, and a replayed recording uses its own seed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.simulation.seed = Some(seed);
+        self
+    }
+
+    /// fn with_softening: chain
+    ///
+    /// sets the initial gravitational softening (see PhysicsConfig)
+    pub fn with_softening(mut self, softening: f32) -> Self {
+        self.simulation.physics.softening = softening;
+        self
+    }
+
+    /// fn with_gravity_constant: chain
+    ///
+    /// sets G, the strength of gravity (see PhysicsConfig)
+    pub fn with_gravity_constant(mut self, gravity_constant: f32) -> Self {
+        self.simulation.physics.gravity_constant = gravity_constant;
+        self
+    }
+
+    /// fn with_integrator: chain
+    ///
+    /// sets how each physics step advances the objects (see IntegratorMode)
+    pub fn with_integrator(mut self, integrator: IntegratorMode) -> Self {
+        self.simulation.physics.integrator = integrator;
+        self
+    }
+
+    /// fn with_gravity: chain
+    ///
+    /// sets which object types attract each other (see GravityMatrix)
+    pub fn with_gravity(mut self, gravity: GravityMatrix) -> Self {
+        self.simulation.gravity = gravity;
+        self
+    }
+
+    /// fn with_reference_frame: chain
+    ///
+    /// sets the initial frame of reference, which the `F` key still toggles before launch
+    pub fn with_reference_frame(mut self, frame: ReferenceFrame) -> Self {
+        self.simulation.frame = frame;
+        self
+    }
+
+    /// fn with_black_hole_threshold: chain
+    ///
+    /// sets the minimum mass of a merge product to remain a black hole (see BlackHoleThreshold)
+    pub fn with_black_hole_threshold(mut self, threshold: f32) -> Self {
+        self.simulation.black_hole_threshold = threshold;
+        self
+    }
+
+    /// fn with_sandbox: chain
+    ///
+    /// starts in (or out of) sandbox mode, which the `S` key still toggles
+    pub fn with_sandbox(mut self, sandbox: bool) -> Self {
+        self.simulation.sandbox = sandbox;
+        self
+    }
+
+    /// fn with_stale_timeout: chain
+    ///
+    /// sets the seconds without a merge after which a run is flagged as settled
+    /// (see SimStats). None never flags a run
+    pub fn with_stale_timeout(mut self, timeout: Option<f64>) -> Self {
+        self.simulation.stale_timeout = timeout;
+        self
+    }
+
+    /// fn with_theme: chain
+    ///
+    /// sets the palette of the field, objects and HUD (see Theme), e.g. Theme::high_contrast()
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.simulation.theme = theme;
+        self
+    }
+
+    /// fn with_objects: chain
+    ///
+    /// starts the first game with exactly these objects (a preset) instead of a generated
+    /// universe, see NewGameConfig::Objects. Restarting the same universe places them
+    /// again, while a slider edit or a new universe generates one as usual
+    pub fn with_objects(mut self, objects: Vec<Movable>) -> Self {
+        self.objects = Some(objects);
+        self
+    }
+
+    /// fn with_universe_size: chain
+    ///
+    /// sets the width and height of the universe (see UniverseConfig), e.g. a 2:1 field
+    /// for a wide window. Each axis wraps at its own size and the camera fits the whole field
+    pub fn with_universe_size(mut self, width: f32, height: f32) -> Self {
+        self.simulation.universe = UniverseConfig { width, height };
+        self
+    }
+
+    /// fn with_bullet_time: chain
+    ///
+    /// starts with (or without) the slow motion final merge, which the `B` key still
+    /// toggles (see BulletTime)
+    pub fn with_bullet_time(mut self, enabled: bool) -> Self {
+        self.bullet_time = enabled;
+        self
+    }
+
+    /// fn with_shield: chain
+    ///
+    /// gives the player's planet a Shield which survives `hits` grazes of a black hole before
+    /// it can be consumed (see shield.rs). 0, the default, gives it no Shield
+    pub fn with_shield(mut self, hits: u32) -> Self {
+        self.simulation.shield_hits = hits;
+        self
+    }
+
+    /// fn physics_plugin
+    ///
+    /// returns the PhysicsPlugin as configured by the with_ methods above, to run the
+    /// simulation without the interface, e.g.
+    /// `BlackHoleUniverse::default().with_softening(500.0).physics_plugin()`
+    pub fn physics_plugin(&self) -> PhysicsPlugin {
+        self.simulation
+    }
+}
 
 /// Implementation for the Bevy plugin: addes necessary
 /// resources (similiar to globals),
 /// required plugins (similiar to modules)
 /// and registers systems (functions) to run
-/// at every frame update. This is the heart of the program
+/// at every frame update. This is the heart of the program: the simulation
+/// (see PhysicsPlugin) and everything drawing it or taking input for it
 impl Plugin for BlackHoleUniverse {
     fn build(&self, app: &mut App) {
-        app.add_plugins(MeshPickingPlugin);
-        app.insert_resource(GameState::new());
+        app.add_plugins(self.simulation);
+        app.init_resource::<GamepadCursor>();
+        app.init_resource::<TouchLaunch>();
+        app.init_resource::<MouseLaunch>();
+        app.init_resource::<LaunchConfig>();
+        app.init_resource::<SlingshotTracker>();
+        app.init_resource::<EntropyMeter>();
+        app.init_resource::<PlanetFate>();
+        app.add_message::<PlanetPlaced>();
+        app.add_message::<GameStarted>();
+        app.add_message::<NewGameConfig>();
+        app.insert_resource(StartingUniverse(
+            self.objects
+                .as_ref()
+                .map(|objects| objects.iter().map(Movable::build).collect()),
+        ));
+        app.init_resource::<InfluenceRings>();
+        app.init_resource::<Lensing>();
+        app.init_resource::<EdgeIndicators>();
+        app.insert_resource(BulletTime {
+            enabled: self.bullet_time,
+            ..default()
+        });
+        app.init_resource::<SettingsOpen>();
+        app.init_resource::<TimeScale>();
+        app.init_resource::<PlacementFeedback>();
+        app.init_resource::<Hovered>();
+        app.init_resource::<ClusterCount>();
+        app.init_resource::<Replay>();
         app.init_resource::<InputFocus>();
-        app.add_systems(Startup, (setup_field, setup_hub, setup_objects).chain());
-        app.add_systems(
-            Update,
-            (drag_slider, update_slider, update_slider_results).chain(),
-        );
-        app.add_systems(Update, (button_system, check_for_restart).chain());
+
+        add_interface(app);
+    }
+}
+
+/// Implementation for the Bevy plugin: adds the resources the physics systems read and
+/// write and registers the systems themselves on the fixed timestep
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameState::new());
+        app.insert_resource(BlackHoleThreshold(self.black_hole_threshold));
+        app.insert_resource(self.gravity);
+        app.insert_resource(self.physics);
+        app.insert_resource(self.frame);
+        app.insert_resource(self.theme);
+        app.init_resource::<MergeHistory>();
+        app.add_message::<PlanetConsumed>();
+        app.add_message::<Merge>();
+        app.insert_resource(SimStats {
+            stale_timeout: self.stale_timeout,
+            ..default()
+        });
+        app.insert_resource(ShieldHits(self.shield_hits));
+        app.insert_resource(SandboxMode(self.sandbox));
+        let mut layout = match self.seed {
+            Some(seed) => UniverseLayout::seeded(seed),
+            None => UniverseLayout::default(),
+        };
+        layout.universe = self.universe;
+        app.insert_resource(layout);
+        app.init_resource::<VelocityPattern>();
+
+        //the physics run on a fixed timestep so a universe evolves identically at any
+        //frame rate (see recording.rs); FixedUpdate runs ahead of Update every frame
+        app.insert_resource(Time::<Fixed>::from_hz(PHYSICS_HZ));
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
-                update_clock,
+                attach_shield,
                 update_velocity,
                 update_motion,
                 update_collisions,
-                check_for_gameover,
             )
                 .chain(),
         );
+        app.add_message::<GameOver>();
+        app.add_systems(Update, check_for_gameover);
     }
 }
 
+/// not called directly from a system/event loop but is instead a helper function
+/// called by the plugin's build. Registers every system which draws the universe or
+/// handles input, on top of the PhysicsPlugin: the field and the HUD, the controls, the
+/// settings panel and the game over sequence. The systems acting on the universe from
+/// the keyboard, gamepad or touch only run while the settings panel is closed (see fn
+/// settings_closed), while the pointer observers of the field and of the planet ignore
+/// the pointer until it is closed again
+fn add_interface(app: &mut App) {
+    app.add_plugins(MeshPickingPlugin);
+    app.add_systems(
+        Startup,
+        (setup_field, setup_hub, setup_settings, setup_objects).chain(),
+    );
+    app.add_systems(
+        Update,
+        (
+            apply_new_game,
+            drag_slider,
+            update_softening_setting,
+            update_time_scale_setting,
+            update_slider,
+            cycle_velocity_pattern.run_if(settings_closed),
+            cycle_cluster_count.run_if(settings_closed),
+            reroll_layout.run_if(settings_closed),
+            update_slider_results,
+        )
+            .chain(),
+    );
+    app.add_systems(
+        Update,
+        (button_system, check_for_restart)
+            .chain()
+            .before(apply_new_game),
+    );
+    app.add_systems(
+        Update,
+        (gamepad_cursor, gamepad_controls)
+            .chain()
+            .run_if(settings_closed),
+    );
+    app.add_systems(Update, touch_controls.run_if(settings_closed));
+    app.add_systems(
+        Update,
+        (
+            update_launch_hint,
+            update_launch_prompt,
+            update_placement_notice,
+        ),
+    );
+    app.add_systems(
+        Update,
+        (
+            replay_launch.after(apply_new_game),
+            save_recording.run_if(settings_closed),
+        ),
+    );
+    app.add_systems(Update, (render_interpolation, update_ghosts).chain());
+    app.add_systems(Update, update_inspector);
+    app.add_systems(Update, scale_hud);
+    app.add_systems(Update, update_launch_config.after(drag_slider));
+    app.add_systems(
+        Update,
+        (toggle_influence_rings, draw_influence_rings).chain(),
+    );
+    app.add_systems(Update, toggle_lensing);
+    app.add_systems(Update, draw_shield);
+    app.add_systems(
+        Update,
+        (toggle_edge_indicators, draw_edge_indicators).chain(),
+    );
+    app.add_systems(Update, toggle_sandbox);
+    app.add_systems(Update, toggle_reference_frame);
+    app.add_systems(Update, adjust_softening);
+    app.add_systems(
+        Update,
+        (toggle_settings, settings_buttons, apply_theme).chain(),
+    );
+    app.add_systems(
+        Update,
+        (update_clock, detect_slingshot)
+            .chain()
+            .before(check_for_gameover),
+    );
+    app.add_systems(
+        Update,
+        (
+            update_counters,
+            update_entropy_meter,
+            update_final_merge_notice,
+            update_bullet_time,
+            update_merge_stats,
+            update_planet_fate,
+            log_merge_history,
+        )
+            .chain()
+            .after(check_for_gameover),
+    );
+}
+
 /// not called directly from a system/event loop but is instead a helper function
 /// called by either setup_objects or slider motion, etc to physically produce
 /// a visual object on the playing field. All objects are Mesh2d circles where
-/// only the color changes to distinguish between the objects
+/// only the color changes to distinguish between the objects. Heavy black holes
+/// also get their lensing halo (see lensing.rs) as children. Planets spawned here
+/// (split fragments, sandbox planets, light merge products) are not the player's:
+/// see spawn_the_planet. Returns the spawned Entity
 fn spawn_object(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
+    theme: &Theme,
     object: Movable,
-) {
-    let mut color = Color::linear_rgb(0.9, 0.9, 0.9);
-
+) -> Entity {
     if object.otype == ObjectType::World {
-        color = Color::linear_rgb(0.0, 0.9, 0.0);
-
-        commands.spawn((
-            Mesh2d(meshes.add(Circle::new(object.size.radius))),
-            MeshMaterial2d(materials.add(color)),
-            Transform::from_xyz(object.position.x, object.position.y, 0.0),
-            ThePlanet,
-            object,
-        ));
+        commands
+            .spawn((
+                circle_mesh(meshes, object.size.radius),
+                MeshMaterial2d(materials.add(theme.planet)),
+                Transform::from_xyz(object.position.x, object.position.y, 0.0),
+                object,
+            ))
+            .observe(hover_object)
+            .observe(unhover_object)
+            .id()
     } else {
-        commands.spawn((
-            Mesh2d(meshes.add(Circle::new(object.size.radius))),
-            MeshMaterial2d(materials.add(color)),
-            Transform::from_xyz(object.position.x, object.position.y, 0.0),
-            object,
-        ));
+        //light black holes have no halo, and so no material for it: the field is
+        //respawned on every slider edit
+        let halo = lens_layers(object.size.mass, object.size.radius);
+        let halo_material =
+            (!halo.is_empty()).then(|| materials.add(theme.lens.with_alpha(LENS_LAYER_ALPHA)));
+
+        commands
+            .spawn((
+                circle_mesh(meshes, object.size.radius),
+                MeshMaterial2d(materials.add(theme.black_hole)),
+                Transform::from_xyz(object.position.x, object.position.y, 0.0),
+                object,
+            ))
+            .with_children(|parent| {
+                //the halo sits behind the black hole and must not catch the clicks meant
+                //for the universe (placing the planet) or the hover of the black hole
+                let Some(halo_material) = halo_material else {
+                    return;
+                };
+                for radius in halo {
+                    parent.spawn((
+                        circle_mesh(meshes, radius),
+                        MeshMaterial2d(halo_material.clone()),
+                        Transform::from_xyz(0.0, 0.0, -0.5),
+                        Pickable::IGNORE,
+                        Lens,
+                    ));
+                }
+            })
+            .observe(hover_object)
+            .observe(unhover_object)
+            .id()
+    }
+}
+
+/// not called directly from a system/event loop but is instead a helper function
+/// like above which spawns the player's planet: the only object carrying ThePlanet and
+/// the observers which aim, drag and launch it
+fn spawn_the_planet(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    theme: &Theme,
+    planet: Movable,
+) {
+    let entity = spawn_object(commands, meshes, materials, theme, planet);
+    commands
+        .entity(entity)
+        .insert(ThePlanet)
+        .observe(aim_planet)
+        .observe(planet_dragged)
+        .observe(check_for_start);
+}
+
+/// Observer: the mouse moved over an object, make it the inspector's subject
+fn hover_object(over: On<Pointer<Over>>, mut hovered: ResMut<Hovered>) {
+    hovered.0 = Some(over.entity);
+}
+
+/// Observer: the mouse left an object, clear the inspector if it was the subject
+fn unhover_object(out: On<Pointer<Out>>, mut hovered: ResMut<Hovered>) {
+    if hovered.0 == Some(out.entity) {
+        hovered.0 = None;
     }
 }
 
+/// A helper function which builds the circle mesh drawn for an object of the
+/// given radius. Every path that draws or resizes an object (spawning, merging,
+/// slider edits) builds its mesh here so that the drawn size always matches the
+/// radius used by the collision math, rather than scaling a stale mesh
+fn circle_mesh(meshes: &mut ResMut<Assets<Mesh>>, radius: f32) -> Mesh2d {
+    Mesh2d(meshes.add(Circle::new(radius)))
+}
+
 /// A helper function like above, except removes an Entity (this is a
 /// Bevy object / collection of components) from the game. Used to
-/// destroy a visible object
+/// destroy a visible object. despawn is recursive: the object's children and,
+/// through the Ghost relationship, its ghosts are destroyed along with it
 fn destroy_object(commands: &mut Commands, entity: Entity) {
     commands.entity(entity).despawn();
 }
@@ -114,17 +563,23 @@ fn destroy_object(commands: &mut Commands, entity: Entity) {
 /// spawns the camera (2d) with its Orthographic Projection,
 /// the space-time playing field
 /// and a small red border to highlight the universe boundary against
-/// the window background
+/// the window background. The camera fits the whole universe (see UniverseConfig)
+/// into the window, whatever their aspect ratios
 fn setup_field(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+    layout: Res<UniverseLayout>,
 ) {
+    let universe = layout.universe;
+
     commands.spawn((
         Camera2d,
         Projection::from(OrthographicProjection {
-            scaling_mode: ScalingMode::FixedVertical {
-                viewport_height: UNIVERSE_SIZE,
+            scaling_mode: ScalingMode::AutoMin {
+                min_width: universe.width,
+                min_height: universe.height,
             },
             ..OrthographicProjection::default_2d()
         }),
@@ -133,91 +588,171 @@ fn setup_field(
     //spawn the space-time
     commands
         .spawn((
-            Mesh2d(meshes.add(Rectangle::new(UNIVERSE_SIZE - 10.0, UNIVERSE_SIZE - 10.0))),
-            MeshMaterial2d(materials.add(Color::linear_rgb(0.0, 0.0, 0.0))),
+            Mesh2d(meshes.add(Rectangle::new(
+                universe.width - 10.0,
+                universe.height - 10.0,
+            ))),
+            MeshMaterial2d(materials.add(theme.background)),
             Transform::from_translation(Vec3::new(0.0, 0.0, -1.0)),
         ))
         .observe(place_planet)
         .observe(planet_dragged)
-        .observe(check_for_start);
+        .observe(check_for_start)
+        .observe(sandbox_spawn)
+        .observe(scatter_cluster);
 
     //border
     commands.spawn((
-        Mesh2d(meshes.add(Rectangle::new(UNIVERSE_SIZE, UNIVERSE_SIZE))),
-        MeshMaterial2d(materials.add(Color::linear_rgb(0.9, 0.3, 0.3))),
+        Mesh2d(meshes.add(Rectangle::new(universe.width, universe.height))),
+        MeshMaterial2d(materials.add(theme.border)),
         Transform::from_translation(Vec3::new(0.0, 0.0, -2.0)),
     ));
 }
 
 /// Schedule: Startup Bevy System
 ///
-/// sets the initial state of the Universe (playing field)
-/// all slider-bars default to 50% full and so the initial
-/// configuration will represent this 50% option.
+/// starts the first game (see fn apply_new_game). When launched with a Recording (see
+/// main.rs) that is the recorded universe, scatters included, with the recorded black
+/// hole threshold, frame of reference, softening, gravity constant, integrator,
+/// GravityMatrix and shield, so that the replay starts from exactly the recorded
+/// universe; the launch itself is replayed by fn replay_launch. Otherwise it is the
+/// objects of `BlackHoleUniverse::with_objects` or, by default, the universe generated
+/// from the UniverseLayout seed and the sliders, which all default to 50% full
+#[allow(clippy::too_many_arguments)]
 fn setup_objects(
+    mut new_game: MessageWriter<NewGameConfig>,
+    mut starting: ResMut<StartingUniverse>,
+    replay: Res<Replay>,
+    mut bh_threshold: ResMut<BlackHoleThreshold>,
+    mut frame: ResMut<ReferenceFrame>,
+    mut physics: ResMut<PhysicsConfig>,
+    mut gravity: ResMut<GravityMatrix>,
+    mut shield_hits: ResMut<ShieldHits>,
+    sliders: Query<(&SliderValue, &SliderType)>,
+    pattern: Res<VelocityPattern>,
+    clusters: Res<ClusterCount>,
+    layout: Res<UniverseLayout>,
+) {
+    if let Some(recording) = &replay.0 {
+        bh_threshold.0 = recording.bh_threshold;
+        *frame = recording.frame;
+        physics.softening = recording.softening;
+        physics.gravity_constant = recording.gravity_constant;
+        physics.integrator = recording.integrator;
+        *gravity = recording.gravity_matrix;
+        shield_hits.0 = recording.shield_hits;
+        new_game.write(NewGameConfig::Generated {
+            seed: recording.seed,
+            settings: recording.settings,
+            scatters: recording.scatters.clone(),
+        });
+        return;
+    }
+
+    if let Some(objects) = starting.0.take() {
+        new_game.write(NewGameConfig::Objects(objects));
+        return;
+    }
+
+    //the sliders are spawned by setup_hub: a missing one keeps its default position
+    let missing = UniverseSettings::missing_sliders(&sliders);
+    if !missing.is_empty() {
+        warn!(
+            "sliders not found ({}): generating the universe from their default positions",
+            missing.join(", ")
+        );
+    }
+
+    new_game.write(NewGameConfig::Generated {
+        seed: layout.seed,
+        settings: UniverseSettings::from_sliders(&sliders, *pattern, *clusters),
+        scatters: layout.scatters().to_vec(),
+    });
+}
+
+/// Schedule: Update Bevy System
+///
+/// The one code path which starts a new game, for every NewGameConfig written at
+/// startup, on restart or to replay a recording (only the last, if several arrive at once)
+/// 1. Destroys all Entities that remain which are Movable
+/// 2. For a Generated universe, moves the sliders, VelocityPattern and ClusterCount to
+///    the settings it is generated from
+/// 3. Spawns the objects of the config (see NewGameConfig::objects)
+/// 4. Resets the GameState and clears the MergeHistory. A config which brings its own
+///    planet counts as placed, ready to be aimed and launched
+/// 5. Hides the buttons until next GameOver state has been reached
+#[allow(clippy::too_many_arguments)]
+fn apply_new_game(
+    mut new_game: MessageReader<NewGameConfig>,
+    mut state: ResMut<GameState>,
+    mut history: ResMut<MergeHistory>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    sliders: Query<(&SliderValue, &SliderType)>,
+    theme: Res<Theme>,
+    mut sliders: Query<(&mut SliderValue, &SliderType)>,
+    objects: Query<Entity, With<Movable>>,
+    mut interaction_query: Query<&mut Visibility, With<GameOverBtn>>,
+    mut pattern: ResMut<VelocityPattern>,
+    mut clusters: ResMut<ClusterCount>,
+    mut layout: ResMut<UniverseLayout>,
 ) {
-    let mut bh_count = 0;
-    let mut bh_mass = 0.0;
-    let mut bh_vel = 0.0;
-    let mut bh_pos_std = 0.0; //std for the position gauss
-
-    let bh_mass_mean = (BLACKHOLE_MASS_RNG.upper + BLACKHOLE_MASS_RNG.lower) / 2.0;
+    let Some(config) = new_game.read().last() else {
+        return;
+    };
 
-    for (slider_value, slider_type) in sliders {
-        match slider_type {
-            SliderType::Count => {
-                bh_count = (slider_value.value * BLACKHOLE_COUNT_RNG.upper as f32)
-                    .max(BLACKHOLE_COUNT_RNG.lower as f32)
-                    .round() as u32;
-            }
-            SliderType::Mass => {
-                bh_mass = slider_value.value * bh_mass_mean;
-            }
-            SliderType::Velocity => {
-                bh_vel = (slider_value.value + VELSTDEVMIN)
-                    * (BLACKHOLE_VEL_RNG.upper.abs() + BLACKHOLE_VEL_RNG.lower.abs())
-                    / 2.0;
-            }
-            SliderType::Density => {
-                //use 1-slider value so that max on the bar squeezes the universe the most
-                bh_pos_std = (1.0 - slider_value.value + POSSTDEVMIN) * UNIVERSE_SIZE / 2.0; //universesize/2 is max - basically fills the universe
-            }
-        }
+    for entity in &objects {
+        destroy_object(&mut commands, entity);
     }
 
-    let mut position_rand = Gauss::new(
-        0.0,
-        bh_pos_std,
-        GaussBoundary::WrapBoth((-UNIVERSE_SIZE / 2.0, UNIVERSE_SIZE / 2.0)),
-    );
+    if let NewGameConfig::Generated { settings, .. } = config {
+        *pattern = settings.pattern;
+        *clusters = settings.clusters;
+        restore_sliders(&mut sliders, settings);
+    }
 
-    let mut bh_mass_rand = Gauss::new(
-        bh_mass,
-        BLACKHOLE_MASS_RNG.upper / 4.0,
-        GaussBoundary::ClampBoth((BLACKHOLE_MASS_RNG.lower, BLACKHOLE_MASS_RNG.upper)),
-    );
+    let current = UniverseSettings::from_sliders(&sliders, *pattern, *clusters);
+    let objects = config.objects(&mut layout, &current);
+
+    state.reset();
+    state.planet_placed = objects
+        .iter()
+        .any(|object| object.otype == ObjectType::World);
+    history.clear();
+
+    //the first planet of the config is the player's, any others are plain planets
+    let mut player = true;
+    for object in objects {
+        if player && object.otype == ObjectType::World {
+            player = false;
+            spawn_the_planet(&mut commands, &mut meshes, &mut materials, &theme, object);
+        } else {
+            spawn_object(&mut commands, &mut meshes, &mut materials, &theme, object);
+        }
+    }
 
-    let mut bh_vel_rand = Gauss::new(
-        0.0,
-        bh_vel,
-        GaussBoundary::ClampBoth((BLACKHOLE_VEL_RNG.lower, BLACKHOLE_VEL_RNG.upper)),
-    );
+    for mut visibility in &mut interaction_query {
+        *visibility = Visibility::Hidden;
+    }
+}
 
-    for _ in 0..bh_count {
-        spawn_object(
-            &mut commands,
-            &mut meshes,
-            &mut materials,
-            Movable::new(&ObjectType::BlackHole)
-                .set_position(position_rand.sample(), position_rand.sample())
-                .set_velocity(bh_vel_rand.sample(), bh_vel_rand.sample())
-                .set_mass(bh_mass_rand.sample())
-                .build(),
-        );
+/// not called directly from a system/event loop but is instead a helper function
+/// called by apply_new_game: moves every universe slider to its
+/// position in settings. The Launch slider is not part of the universe and is left alone
+fn restore_sliders(
+    sliders: &mut Query<(&mut SliderValue, &SliderType)>,
+    settings: &UniverseSettings,
+) {
+    for (mut slider_value, slider_type) in sliders {
+        let value = match slider_type {
+            SliderType::Count => settings.count,
+            SliderType::Mass => settings.mass,
+            SliderType::Velocity => settings.velocity,
+            SliderType::Density => settings.density,
+            SliderType::Launch | SliderType::Softening | SliderType::TimeScale => continue,
+        };
+        slider_value.value = value;
+        slider_value.prev_value = value;
     }
 }
 
@@ -226,7 +761,20 @@ fn setup_objects(
 /// Bevy system which spawns the HUB: the
 /// slider bar option controls, any visible text,
 /// the progress timer counters, etc.
-fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWindow>>) {
+fn setup_hub(
+    mut commands: Commands,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+    sandbox: Res<SandboxMode>,
+    theme: Res<Theme>,
+) {
+    //every size below is for a REFERENCE_WINDOW_HEIGHT tall window: the UiScale fits it
+    //to the actual window here and on every resize, see fn scale_hud
+    match window_query.single() {
+        Ok(window) => ui_scale.0 = hud_scale(window.resolution.height()),
+        Err(_) => warn!("no primary window found: laying out the HUD at its reference size"),
+    }
+
     //spawn top left text: Total time and black hole counter
     commands
         .spawn(Node {
@@ -247,7 +795,7 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
                     font_size: 20.0,
                     ..default()
                 },
-                TextColor(Color::linear_rgba(0.5, 0.5, 0.0, 0.5)),
+                TextColor(theme.hud_label),
             ));
             parent.spawn((
                 Text::new("0.00"),
@@ -255,7 +803,7 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
                     font_size: 18.0,
                     ..default()
                 },
-                TextColor(Color::linear_rgba(1.0, 0.5, 0.0, 0.25)),
+                TextColor(theme.hud_value),
                 TotalTime,
             ));
             parent.spawn((
@@ -264,7 +812,7 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
                     font_size: 16.0,
                     ..default()
                 },
-                TextColor(Color::linear_rgba(0.5, 0.5, 0.0, 0.5)),
+                TextColor(theme.hud_label),
             ));
             parent.spawn((
                 Text::new("0"),
@@ -272,7 +820,7 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
                     font_size: 16.0,
                     ..default()
                 },
-                TextColor(Color::linear_rgba(1.0, 0.5, 0.0, 0.25)),
+                TextColor(theme.hud_value),
                 BHCounter,
             ));
         });
@@ -282,11 +830,12 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
         .spawn(Node {
             position_type: PositionType::Absolute,
             top: px(5),
-            right: px(-85), //Val::Percent(-5.0),
+            right: px(5),
 
             display: Display::Grid, // Use Grid display
 
-            grid_template_columns: vec![GridTrack::flex(1.0), GridTrack::flex(1.0)], // Two equal columns
+            grid_template_columns: vec![GridTrack::auto(), GridTrack::auto()], // Two columns sized to fit, anchored to the right edge
+            column_gap: px(5),
             grid_template_rows: vec![GridTrack::flex(1.0), GridTrack::flex(1.0)], // Two equal rows
             ..Default::default()
         })
@@ -297,7 +846,7 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
                     font_size: 20.0,
                     ..default()
                 },
-                TextColor(Color::linear_rgba(0.5, 0.5, 0.0, 0.5)),
+                TextColor(theme.hud_label),
             ));
             parent.spawn((
                 Text::new("0.00"),
@@ -305,7 +854,7 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
                     font_size: 18.0,
                     ..default()
                 },
-                TextColor(Color::linear_rgba(1.0, 0.5, 0.0, 0.25)),
+                TextColor(theme.hud_value),
                 WorldTime,
             ));
             parent.spawn((
@@ -314,7 +863,7 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
                     font_size: 18.0,
                     ..default()
                 },
-                TextColor(Color::linear_rgba(0.5, 0.5, 0.0, 0.5)),
+                TextColor(theme.hud_label),
             ));
             parent.spawn((
                 Text::new("0"),
@@ -322,16 +871,11 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
                     font_size: 16.0,
                     ..default()
                 },
-                TextColor(Color::linear_rgba(1.0, 0.5, 0.0, 0.25)),
+                TextColor(theme.hud_value),
                 WorldCounter,
             ));
         });
 
-    let mut height_in_pixels = 1000;
-    if let Ok(window) = window_query.single() {
-        height_in_pixels = window.resolution.physical_height();
-    }
-
     // spawn the Black Hole Settings group container and the title bar
     let left_container = commands
         .spawn((Node {
@@ -339,8 +883,8 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
             flex_direction: FlexDirection::Column, // Stack children vertically
             row_gap: Val::Px(15.0),
             top: px(50),
+            bottom: px(150),
             left: px(20),
-            height: px(height_in_pixels - 200),
             width: px(SLIDERWIDTH * 2.0),
             align_items: AlignItems::Center,
             justify_items: JustifyItems::Center,
@@ -357,14 +901,14 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
                 font_size: 16.0,
                 ..default()
             },
-            TextColor(Color::linear_rgba(0.9, 0.9, 0.9, 0.5)),
+            TextColor(theme.hud_text.with_alpha(0.5)),
         ))
         .id();
 
     commands.entity(left_container).add_child(left_header);
 
     //spawn blackhole count slider
-    let count_slider = generate_slider(SliderType::Count, "Count");
+    let count_slider = generate_slider(&theme, SliderType::Count, "Count");
     let count_base = commands
         .spawn((
             count_slider.base,
@@ -380,7 +924,7 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
     commands.entity(left_container).add_child(count_base);
 
     //spawn blackhole mass slider
-    let mass_slider = generate_slider(SliderType::Mass, "Masses");
+    let mass_slider = generate_slider(&theme, SliderType::Mass, "Masses");
     let mass_base = commands
         .spawn((
             mass_slider.base,
@@ -396,7 +940,7 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
     commands.entity(left_container).add_child(mass_base);
 
     //spawn blackhole velocity slider
-    let mass_slider = generate_slider(SliderType::Velocity, "Velocity");
+    let mass_slider = generate_slider(&theme, SliderType::Velocity, "Velocity");
     let mass_base = commands
         .spawn((
             mass_slider.base,
@@ -412,7 +956,7 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
     commands.entity(left_container).add_child(mass_base);
 
     //spawn blackhole density slider
-    let mass_slider = generate_slider(SliderType::Density, "Density");
+    let mass_slider = generate_slider(&theme, SliderType::Density, "Density");
     let mass_base = commands
         .spawn((
             mass_slider.base,
@@ -427,546 +971,2578 @@ fn setup_hub(mut commands: Commands, window_query: Query<&Window, With<PrimaryWi
     commands.entity(mass_base).add_child(mass_text);
     commands.entity(left_container).add_child(mass_base);
 
-    //spawn the reset button in bottom right corner
+    //the velocity pattern is cycled with the V key, see fn cycle_velocity_pattern
+    let pattern_label = commands
+        .spawn((
+            Text::new(pattern_text(VelocityPattern::default())),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(theme.hud_text),
+            PatternLabel,
+        ))
+        .id();
+    commands.entity(left_container).add_child(pattern_label);
+
+    //the cluster count is cycled with the C key, see fn cycle_cluster_count
+    let cluster_label = commands
+        .spawn((
+            Text::new(cluster_text(ClusterCount::default())),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(theme.hud_text),
+            ClusterLabel,
+        ))
+        .id();
+    commands.entity(left_container).add_child(cluster_label);
+
+    //sandbox mode is toggled with the S key, see fn toggle_sandbox
+    let sandbox_label = commands
+        .spawn((
+            Text::new(sandbox_text(sandbox.0)),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(theme.hud_text),
+            SandboxLabel,
+        ))
+        .id();
+    commands.entity(left_container).add_child(sandbox_label);
+
+    let planet_header = commands
+        .spawn((
+            Text::new("Planet Settings"),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(theme.hud_text.with_alpha(0.5)),
+        ))
+        .id();
+
+    commands.entity(left_container).add_child(planet_header);
+
+    //spawn planet launch sensitivity slider
+    let launch_slider = generate_slider(&theme, SliderType::Launch, "Launch");
+    let launch_base = commands
+        .spawn((
+            launch_slider.base,
+            Interaction::None,
+            RelativeCursorPosition::default(),
+            SliderValue::default(),
+        ))
+        .id();
+    let launch_bkg = commands.spawn((launch_slider.bkg, SliderBkg)).id();
+    let launch_text = commands.spawn(launch_slider.text).id();
+    commands.entity(launch_base).add_child(launch_bkg);
+    commands.entity(launch_base).add_child(launch_text);
+    commands.entity(left_container).add_child(launch_base);
+
+    //spawn the slingshot notification at the top center: only visible after a slingshot
     commands
         .spawn(Node {
-            //width: percent(100),
-            //height: percent(100),
             position_type: PositionType::Absolute,
-            bottom: px(10),
-            right: px(10), //Val::Percent(-5.0),
-            align_items: AlignItems::Center,
+            top: px(5),
+            width: percent(100),
             justify_content: JustifyContent::Center,
             ..default()
         })
         .with_children(|parent| {
             parent.spawn((
-                GameOverBtn,
-                gen_button("Restart?", 150, 50, Visibility::Hidden),
+                Text::new("Slingshot!"),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(theme.hud_success),
+                Visibility::Hidden,
+                SlingshotNotice,
             ));
         });
-}
-
-/// Schedule: Update Bevy System
-///
-/// this system applies the changes made by the user
-/// on any of the slider-bars to the real-time display.
-/// Note: changes are only accepted prior to the start of the
-/// game. Changes after the start immediately return from this system.
-fn update_slider_results(
-    state: Res<GameState>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut objects: Query<(Entity, &mut Movable, &mut Transform), With<Movable>>,
-    sliders: Query<(&SliderValue, &SliderType)>,
-) {
-    //only accept slider-changes prior to game start
-    if state.game_started {
-        return;
-    }
 
-    let mut count_difference: i32 = 0;
-    let mut bh_mass = 0.0;
-    let mut update_bh_masses = false;
-    let mut bh_vel = 0.0;
-    let mut update_bh_vel = false;
-    let mut bh_pos_std = 0.0;
-    let mut update_bh_pos = false;
+    //spawn the entropy meter below the slingshot notification: fills as the universe merges
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: px(35),
+            width: percent(100),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            column_gap: px(8),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Entropy"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(theme.hud_label),
+            ));
+            parent
+                .spawn((
+                    Node {
+                        width: px(200),
+                        height: px(10),
+                        ..default()
+                    },
+                    BackgroundColor(theme.meter_track),
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        Node {
+                            width: percent(0),
+                            height: percent(100),
+                            ..default()
+                        },
+                        BackgroundColor(theme.hud_value.with_alpha(0.5)),
+                        EntropyBar,
+                    ));
+                });
+        });
 
-    let bh_mass_mean = (BLACKHOLE_MASS_RNG.upper + BLACKHOLE_MASS_RNG.lower) / 2.0;
+    //spawn the final merge notice below the entropy meter: only visible just before
+    //the last two objects merge (see endgame.rs)
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: px(55),
+            width: percent(100),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Final merge imminent"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(theme.hud_value.with_alpha(0.6)),
+                Visibility::Hidden,
+                FinalMergeNotice,
+            ));
+        });
 
-    //check all slider bars for changes and gather those changes, if neccessary
-    for (slider_value, slider_type) in sliders {
-        match slider_type {
-            SliderType::Count => {
-                let bh_count = slider_value;
-                count_difference = (bh_count.value * BLACKHOLE_COUNT_RNG.upper as f32)
-                    .max(BLACKHOLE_COUNT_RNG.lower as f32)
-                    .round() as i32
-                    - (bh_count.prev_value * BLACKHOLE_COUNT_RNG.upper as f32)
-                        .max(BLACKHOLE_COUNT_RNG.lower as f32)
-                        .round() as i32;
-            }
-            SliderType::Mass => {
-                bh_mass = slider_value.value * bh_mass_mean;
-                if slider_value.value != slider_value.prev_value {
-                    update_bh_masses = true;
-                }
+    //spawn the time since the last merge below the final merge notice, followed by the
+    //stable system notice once a run has settled (see stats.rs)
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: px(75),
+            width: percent(100),
+            justify_content: JustifyContent::Center,
+            column_gap: px(8),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Since Last Merge: "),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.hud_label),
+            ));
+            parent.spawn((
+                Text::new("0.00"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.hud_value),
+                LastMergeClock,
+            ));
+            parent.spawn((
+                Text::new("Stable system reached"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(theme.hud_success),
+                Visibility::Hidden,
+                StableNotice,
+            ));
+        });
+
+    //spawn the planet's fate below the merge stats: only visible once the game is over
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: px(95),
+            width: percent(100),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(theme.hud_text),
+                Visibility::Hidden,
+                PlanetFateLabel,
+            ));
+        });
+
+    //spawn the hover inspector: positioned next to the mouse by fn update_inspector
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(theme.hud_text.with_alpha(0.9)),
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        Visibility::Hidden,
+        Inspector,
+    ));
+
+    //spawn the launch prompt above the launch hint: hidden once the simulation starts
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            bottom: px(32),
+            width: percent(100),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(GameState::new().prompt().unwrap_or_default()),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(theme.hud_text),
+                LaunchPrompt,
+            ));
+        });
+
+    //spawn the placement notice above the launch prompt: only visible after a failed placement
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            bottom: px(54),
+            width: percent(100),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Couldn't place the planet: try again"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(theme.hud_alert),
+                Visibility::Hidden,
+                PlacementNotice,
+            ));
+        });
+
+    //spawn the launch hint at the bottom center: only visible when a launch is blocked
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            bottom: px(10),
+            width: percent(100),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!(
+                    "At least {MINIMUM_OBJECTS} objects are needed to launch: raise the Count slider"
+                )),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(theme.hud_alert),
+                Visibility::Hidden,
+                LaunchHint,
+            ));
+        });
+
+    //spawn the reset button in bottom right corner
+    commands
+        .spawn(Node {
+            //width: percent(100),
+            //height: percent(100),
+            position_type: PositionType::Absolute,
+            bottom: px(10),
+            right: px(10), //Val::Percent(-5.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            column_gap: px(10),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                GameOverBtn,
+                RestartChoice::Same,
+                gen_button(&theme, "Retry", 150, 50, Visibility::Hidden),
+            ));
+            parent.spawn((
+                GameOverBtn,
+                RestartChoice::New,
+                gen_button(&theme, "New Universe", 170, 50, Visibility::Hidden),
+            ));
+        });
+}
+
+/// Schedule: Update Bevy System
+///
+/// this system applies the changes made by the user
+/// on any of the slider-bars, or to the VelocityPattern or ClusterCount, to the real-time display.
+/// The black holes are regenerated from the UniverseLayout seed whenever the settings
+/// differ from the ones the field was generated from. Each attribute has its own seeded
+/// stream (see universe.rs), so e.g. moving the Mass slider only changes the masses.
+/// Note: changes are only accepted prior to the start of the
+/// game. Changes after the start immediately return from this system.
+#[allow(clippy::too_many_arguments)]
+fn update_slider_results(
+    state: Res<GameState>,
+    mut layout: ResMut<UniverseLayout>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+    mut objects: Query<(Entity, &mut Movable)>,
+    sliders: Query<(&SliderValue, &SliderType)>,
+    pattern: Res<VelocityPattern>,
+    clusters: Res<ClusterCount>,
+) {
+    //only accept slider-changes prior to game start
+    if state.game_started {
+        return;
+    }
+
+    let settings = UniverseSettings::from_sliders(&sliders, *pattern, *clusters);
+    if !layout.is_stale(&settings) {
+        return;
+    }
+
+    let mut black_holes = layout.generate(&settings);
+
+    for (entity, mut movable) in &mut objects {
+        match movable.otype {
+            ObjectType::BlackHole => destroy_object(&mut commands, entity),
+            ObjectType::World => {
+                //an already placed planet is ordered after the new black holes, exactly
+                //as if it had been placed afterwards, and keeps its safe bubble
+                movable.renew_id();
+                for black_hole in &mut black_holes {
+                    black_hole.clear_bubble(&movable, PLANET_CLEARANCE, &layout.universe);
+                }
+            }
+        }
+    }
+
+    for black_hole in black_holes {
+        spawn_object(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &theme,
+            black_hole,
+        );
+    }
+}
+
+/// A helper function which formats the VelocityPattern label of the HUD
+fn pattern_text(pattern: VelocityPattern) -> String {
+    format!("Pattern: {} (V)", pattern.name())
+}
+
+/// Schedule: Update Bevy System
+///
+/// Pressing `V` prior to the start of the game cycles the VelocityPattern (Random,
+/// Tangential, Radial, BigBang, see VelocityPattern::next); update_slider_results then
+/// regenerates the black holes. The HUD label follows the pattern
+fn cycle_velocity_pattern(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<GameState>,
+    mut pattern: ResMut<VelocityPattern>,
+    mut label_query: Query<&mut Text, With<PatternLabel>>,
+) {
+    if !state.game_started && keys.just_pressed(KeyCode::KeyV) {
+        *pattern = pattern.next();
+    }
+
+    if pattern.is_changed() {
+        for mut label in &mut label_query {
+            **label = pattern_text(*pattern);
+        }
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Pressing `N` prior to the start of the game draws a new UniverseLayout seed;
+/// update_slider_results then regenerates the black holes: a new arrangement
+/// with the same count, mass, velocity and density profile
+fn reroll_layout(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<GameState>,
+    mut layout: ResMut<UniverseLayout>,
+) {
+    if !state.game_started && keys.just_pressed(KeyCode::KeyN) {
+        layout.reseed();
+    }
+}
+
+/// A helper function which formats the ClusterCount label of the HUD
+fn cluster_text(clusters: ClusterCount) -> String {
+    format!("Clusters: {} (C)", clusters.0)
+}
+
+/// Schedule: Update Bevy System
+///
+/// Pressing `C` prior to the start of the game cycles the ClusterCount (1 to
+/// ClusterCount::MAX_CLUSTERS); update_slider_results then regenerates the
+/// black hole positions. The HUD label follows the count
+fn cycle_cluster_count(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<GameState>,
+    mut clusters: ResMut<ClusterCount>,
+    mut label_query: Query<&mut Text, With<ClusterLabel>>,
+) {
+    if !state.game_started && keys.just_pressed(KeyCode::KeyC) {
+        *clusters = clusters.next();
+    }
+
+    if clusters.is_changed() {
+        for mut label in &mut label_query {
+            **label = cluster_text(*clusters);
+        }
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// checks if
+///
+/// 1. the mouse btn is pressed
+/// 2. the mouse position is inside one of the slider-bars
+/// 3. if 1 && 2, store the value inside the SliderValue struct
+///    as a percentage of the bar [0-1]
+///
+/// this stored value will then be used in:
+/// 1. fn update_slider to graphically show the slider bar change
+/// 2. fn update_slider_results to apply these changes to the playing field
+///
+/// While the settings panel is open only its own sliders move, the HUD sliders behind
+/// it stay where they are (and the other way around while it is closed)
+fn drag_slider(
+    mut interaction_query: Query<(
+        &Interaction,
+        &RelativeCursorPosition,
+        &mut SliderValue,
+        &SliderType,
+    )>,
+    open: Res<SettingsOpen>,
+) {
+    for (interaction, relative_cursor, mut slider_value, slider_type) in &mut interaction_query {
+        //check that mouse button is down
+        if !matches!(*interaction, Interaction::Pressed) {
+            continue;
+        }
+
+        if slider_type.in_settings_panel() != open.0 {
+            continue;
+        }
+
+        //check that it was pressed inside the slider:
+        let Some(pos) = relative_cursor.normalized else {
+            continue;
+        };
+
+        //slider takes [0:1] but pos.x.clamp is [-0.5:0.5] so this works as expected:
+        slider_value.prev_value = slider_value.value;
+        slider_value.value = 0.5 + pos.x.clamp(-0.5, 0.5); //percentage
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// physically updates the background of the slider to give the movement response
+/// by changing the width of the node containing the green-colored background
+fn update_slider(
+    parent_query: Query<(&Children, &SliderValue)>,
+    mut child_query: Query<&mut Node, With<SliderBkg>>,
+) {
+    for (children, slider_value) in &parent_query {
+        let mut bkg_iter = child_query.iter_many_mut(children);
+        if let Some(mut node) = bkg_iter.fetch_next() {
+            node.width = px(SLIDERWIDTH * slider_value.value);
+        }
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// updates the text in the top-left (black hole) and top-right (planet)
+/// timer boxes used to indicate the lifetime of the universe and of our
+/// dear planet. Both clocks count from GameState::start_time (set at launch by
+/// fn launch_planet) and read 0.00 until then, so time spent adjusting the
+/// universe before launch is never counted. Both clocks freeze once the game is over;
+/// the world clock freezes earlier if the planet is destroyed.
+///
+/// Note the odd format here:
+/// - the query returns a Query<&mut Text> iterator
+/// - the first deref returns the first &mut Text in that query return
+/// - the second deref returns the internal &String struct underlying the Text
+///   which we then alter via the format!(...) macro
+fn update_clock(
+    time: Res<Time<Fixed>>,
+    mut total_time: Query<&mut Text, (With<TotalTime>, Without<WorldTime>)>,
+    mut world_time: Query<&mut Text, (With<WorldTime>, Without<TotalTime>)>,
+    state: Res<GameState>,
+) {
+    if state.game_started {
+        if state.game_alive {
+            for mut clock in &mut total_time {
+                //First deref gets the Text object, 2nd gets the internal String
+                **clock = format!("{:.2}", time.elapsed_secs_f64() - state.start_time);
+            }
+        }
+
+        //a planet outliving every black hole stops with the universe, not after it
+        if state.world_alive && state.game_alive {
+            for mut clock in &mut world_time {
+                //First deref gets the Text object, 2nd gets the internal String
+                **clock = format!("{:.2}", time.elapsed_secs_f64() - state.start_time);
+            }
+        }
+    } else {
+        //pre-launch (including after a restart) both clocks read zero
+        for mut clock in &mut total_time {
+            **clock = String::from("0.00");
+        }
+        for mut clock in &mut world_time {
+            **clock = String::from("0.00");
+        }
+    }
+}
+
+/// Schedule: FixedUpdate Bevy System
+///
+/// Updates the velocity of all objects on the playing field.
+/// A vec of Velocity structs is built by calculting the new frame's
+/// velocity using the fixed physics timestep and then each
+/// object's velocity is updated. Objects are processed in ID order
+/// (see fn in_id_order) so the floating point sums are reproducible.
+/// In the CenterOfMass ReferenceFrame the universe's drift is removed as well, and
+/// a BigBang's timestep is eased in after launch (see fn launch_time_scale). With the
+/// Verlet IntegratorMode the objects first drift half the step, so that their velocity
+/// is kicked by the gravity at the midpoint, and fn update_motion drifts the other half
+#[allow(clippy::too_many_arguments)]
+fn update_velocity(
+    time: Res<Time>,
+    mut objects: Query<(Entity, &mut Movable)>,
+    state: Res<GameState>,
+    gravity: Res<GravityMatrix>,
+    frame: Res<ReferenceFrame>,
+    config: Res<PhysicsConfig>,
+    pattern: Res<VelocityPattern>,
+    layout: Res<UniverseLayout>,
+) {
+    if state.game_started && state.game_alive {
+        let elapsed = time.delta_secs() * launch_time_scale(&time, &state, &pattern);
+        let first_drift = config.integrator.first_drift(elapsed);
+        if first_drift > 0.0 {
+            for (_, mut movable) in &mut objects {
+                movable.position.x_prev = movable.position.x;
+                movable.position.y_prev = movable.position.y;
+                movable.update_location(first_drift);
+                movable.wrap(&layout.universe);
+            }
+        }
+
+        let entities = in_id_order(&objects);
+        let movables: Vec<&Movable> = entities.iter().map(|(_, movable)| *movable).collect();
+        let mut velocities: Vec<Velocity> =
+            gravity_pass(&movables, elapsed, &gravity, &config, &layout.universe);
+        if *frame == ReferenceFrame::CenterOfMass {
+            remove_drift(&movables, &mut velocities);
+        }
+        let updates: Vec<(Entity, Velocity)> = entities
+            .iter()
+            .map(|(entity, _)| *entity)
+            .zip(velocities)
+            .collect();
+
+        for (entity, velocity) in updates {
+            if let Ok((_, mut movable)) = objects.get_mut(entity) {
+                movable.set_velocity(velocity.vx, velocity.vy);
+            }
+        }
+    }
+}
+
+/// A helper function which returns the rate at which the universe's clock currently runs
+/// (see VelocityPattern::time_scale): below 1.0 while a BigBang is easing in after launch.
+/// Both update_velocity and update_motion scale the physics timestep by it
+fn launch_time_scale(time: &Time, state: &GameState, pattern: &VelocityPattern) -> f32 {
+    pattern.time_scale(time.elapsed_secs_f64() - state.start_time)
+}
+
+/// A helper function which lists the objects in ascending ID order. Bevy's query
+/// order depends on the history of spawns and despawns, whereas IDs are handed out in
+/// creation order, so physics passes over this list are identical between a run and
+/// its replay
+fn in_id_order<'a>(objects: &'a Query<(Entity, &mut Movable)>) -> Vec<(Entity, &'a Movable)> {
+    let mut entities: Vec<(Entity, &Movable)> = objects.iter().collect();
+    entities.sort_by_key(|(_, movable)| movable.get_id());
+    entities
+}
+
+/// Schedule: FixedUpdate Bevy System
+///
+/// Physically moves the objects on the playing field.
+/// uses the updated velocities as set by the above System and then
+/// moves the objects by the fixed physics timestep, less the part of it the Verlet
+/// IntegratorMode already drifted in fn update_velocity. Note the wrap around
+/// logic to enfource the Spherical Universe concept. The objects are drawn
+/// by fn render_interpolation rather than here
+fn update_motion(
+    time: Res<Time>,
+    mut objects: Query<&mut Movable>,
+    state: Res<GameState>,
+    config: Res<PhysicsConfig>,
+    pattern: Res<VelocityPattern>,
+    layout: Res<UniverseLayout>,
+) {
+    if state.game_started && state.game_alive {
+        let elapsed = time.delta_secs() * launch_time_scale(&time, &state, &pattern);
+        let first_drift = config.integrator.first_drift(elapsed);
+
+        for mut movable in &mut objects {
+            //println!("{},{}", movable.velocity.vx, movable.velocity.vy);
+
+            //the previous position was already kept before the first drift
+            if first_drift == 0.0 {
+                movable.position.x_prev = movable.position.x;
+                movable.position.y_prev = movable.position.y;
+            }
+            movable.update_location(elapsed - first_drift);
+
+            //spherical universe wrap around
+            movable.wrap(&layout.universe);
+        }
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Draws every object between its previous and current physics positions, by the
+/// fraction of a physics step the display has overshot, so fast objects glide smoothly
+/// at any display rate (see fn interpolated_position). Once the game is over the
+/// objects are drawn exactly where they came to rest
+fn render_interpolation(
+    time: Res<Time<Fixed>>,
+    state: Res<GameState>,
+    mut objects: Query<(&Movable, &mut Transform)>,
+    layout: Res<UniverseLayout>,
+) {
+    if !state.game_started {
+        return;
+    }
+
+    let alpha = if state.game_alive {
+        time.overstep_fraction()
+    } else {
+        1.0
+    };
+
+    for (movable, mut transform) in &mut objects {
+        let drawn = interpolated_position(&movable.position, alpha, &layout.universe);
+        transform.translation.x = drawn.x;
+        transform.translation.y = drawn.y;
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Keeps the ghost copies of every object in sync with the spherical wrap. Objects whose
+/// circle extends past an edge of the universe get a Ghost drawn at the wrapped offset
+/// (see Movable::wrap_offsets) so they appear continuous across the seam.
+/// 1. existing ghosts follow their parent's drawn position (its Transform) and mesh
+/// 2. ghosts that are no longer needed (the parent is fully across) or whose parent has
+///    been destroyed are despawned
+/// 3. missing ghosts are spawned
+fn update_ghosts(
+    mut commands: Commands,
+    objects: Query<
+        (
+            Entity,
+            &Movable,
+            &Transform,
+            &Mesh2d,
+            &MeshMaterial2d<ColorMaterial>,
+        ),
+        Without<Ghost>,
+    >,
+    mut ghosts: Query<(Entity, &Ghost, &mut Transform, &mut Mesh2d)>,
+    layout: Res<UniverseLayout>,
+) {
+    let mut present = Vec::<(Entity, Vec2)>::new();
+
+    for (ghost_entity, ghost, mut transform, mut ghost_mesh) in &mut ghosts {
+        match objects.get(ghost.parent) {
+            Ok((_, movable, parent, mesh, _))
+                if movable
+                    .wrap_offsets(&layout.universe)
+                    .contains(&ghost.offset) =>
+            {
+                transform.translation.x = parent.translation.x + ghost.offset.x;
+                transform.translation.y = parent.translation.y + ghost.offset.y;
+                if ghost_mesh.0 != mesh.0 {
+                    *ghost_mesh = mesh.clone();
+                }
+                present.push((ghost.parent, ghost.offset));
+            }
+            _ => destroy_object(&mut commands, ghost_entity),
+        }
+    }
+
+    for (entity, movable, parent, mesh, material) in &objects {
+        for offset in movable.wrap_offsets(&layout.universe) {
+            if !present.contains(&(entity, offset)) {
+                commands.spawn((
+                    mesh.clone(),
+                    material.clone(),
+                    Transform::from_xyz(
+                        parent.translation.x + offset.x,
+                        parent.translation.y + offset.y,
+                        0.0,
+                    ),
+                    Ghost {
+                        parent: entity,
+                        offset,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+/// Schedule: FixedUpdate Bevy System
+///
+/// The workhorse of each physics step:
+/// iterates through each object and determines if the current object has
+/// collided with another object (see fn collision_pass in physics.rs).
+///
+/// Every object involved in a collision is despawned from the graphical display
+/// and the results of the collisions are spawned in their place, each writing a
+/// MergeRecord of the objects which formed it (see history.rs) and, for the product of
+/// a merge, a Merge message. The collision swallowing the player's planet (see
+/// ThePlanet) also writes a PlanetConsumed message. A shielded planet which only
+/// grazed a black hole is spared first (see fn spare_shielded_planets)
+#[allow(clippy::too_many_arguments)]
+fn update_collisions(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+    mut objects: Query<(Entity, &mut Movable)>,
+    state: Res<GameState>,
+    bh_threshold: Res<BlackHoleThreshold>,
+    time: Res<Time<Fixed>>,
+    mut stats: ResMut<SimStats>,
+    mut consumed: MessageWriter<PlanetConsumed>,
+    mut merges: MessageWriter<Merge>,
+    mut history: ResMut<MergeHistory>,
+    layout: Res<UniverseLayout>,
+    mut shields: Query<&mut Shield>,
+    the_planet: Query<Entity, With<ThePlanet>>,
+) {
+    //a lone object has nothing to collide with: the end state of every game
+    if objects.iter().len() <= 1 {
+        return;
+    }
+
+    // next check for collisions
+    if state.game_started && state.game_alive {
+        let spared = spare_shielded_planets(&mut objects, &mut shields, &layout.universe);
+        let entities: Vec<(Entity, &Movable)> = in_id_order(&objects)
+            .into_iter()
+            .filter(|(entity, _)| !spared.contains(entity))
+            .collect();
+        let movables: Vec<&Movable> = entities.iter().map(|(_, movable)| *movable).collect();
+
+        let (colliding, frame) = collision_pass(&movables);
+
+        for index in colliding {
+            destroy_object(&mut commands, entities[index].0);
+        }
+
+        let survived = time.elapsed_secs_f64() - state.start_time;
+        let planet = entities
+            .iter()
+            .find(|(entity, _)| the_planet.contains(*entity))
+            .map(|(_, movable)| movable.get_id());
+
+        let lineage = frame.collect_lineage(bh_threshold.0, &layout.universe);
+        if !lineage.is_empty() {
+            stats.record_merge(time.elapsed_secs_f64());
+        }
+
+        //then add. Only a merge product is a Merge: fragments of a split and the
+        //objects of a bounce are not
+        for (parents, outcome, products) in lineage {
+            if let Some(planet) = planet {
+                let members: Vec<&Movable> = movables
+                    .iter()
+                    .filter(|movable| parents.contains(&movable.get_id()))
+                    .copied()
+                    .collect();
+                if let Some(by_mass) = Movable::consumed_planet(planet, &members, &products) {
+                    consumed.write(PlanetConsumed {
+                        time: survived,
+                        by_mass,
+                    });
+                }
+            }
+
+            for new in products {
+                history.record(&parents, new.get_id(), survived);
+                if outcome == CollisionOutcome::Merge {
+                    merges.write(Merge {
+                        result_mass: new.size.mass,
+                        location: Vec2::new(new.position.x, new.position.y),
+                    });
+                }
+                spawn_object(&mut commands, &mut meshes, &mut materials, &theme, new);
+            }
+        }
+    }
+}
+
+/// not called directly from a system/event loop but is instead a helper function
+/// called by update_collisions. Every planet whose Shield has hits left and which only
+/// grazed a black hole during the step (see fn shielded_graze) spends a hit and
+/// rebounds. Returns the planets spared, which sit out the rest of the step's collisions
+fn spare_shielded_planets(
+    objects: &mut Query<(Entity, &mut Movable)>,
+    shields: &mut Query<&mut Shield>,
+    universe: &UniverseConfig,
+) -> Vec<Entity> {
+    let entities = in_id_order(objects);
+    let mut rebounds = Vec::new();
+
+    for (entity, planet) in &entities {
+        if !shields.get(*entity).is_ok_and(|shield| shield.hits > 0) {
+            continue;
+        }
+
+        let colliding: Vec<&Movable> = entities
+            .iter()
+            .filter(|(other, movable)| other != entity && movable.collided(*planet))
+            .map(|(_, movable)| *movable)
+            .collect();
+        if let Some(rebound) = shielded_graze(planet, &colliding, universe) {
+            rebounds.push((*entity, rebound));
+        }
+    }
+
+    let mut spared = Vec::new();
+    for (entity, rebound) in rebounds {
+        if let Ok((_, mut movable)) = objects.get_mut(entity) {
+            *movable = rebound;
+        }
+        if let Ok(mut shield) = shields.get_mut(entity) {
+            shield.hits -= 1;
+        }
+        spared.push(entity);
+    }
+
+    spared
+}
+
+/// Schedule: FixedUpdate Bevy System
+///
+/// Gives the newly spawned player's planet (see ThePlanet) a Shield of ShieldHits hits,
+/// unless shields are off. Other planets, e.g. split fragments, are never shielded
+fn attach_shield(
+    mut commands: Commands,
+    hits: Res<ShieldHits>,
+    spawned: Query<Entity, Added<ThePlanet>>,
+) {
+    if hits.0 == 0 {
+        return;
+    }
+
+    for entity in &spawned {
+        commands.entity(entity).insert(Shield { hits: hits.0 });
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Draws the remaining hits of every shielded planet as a ring around it, which
+/// shrinks onto the planet as the hits run out (see Shield::ring_radius)
+fn draw_shield(planets: Query<(&Movable, &Shield)>, theme: Res<Theme>, mut gizmos: Gizmos) {
+    for (movable, shield) in &planets {
+        if shield.hits > 0 {
+            gizmos.circle_2d(
+                Vec2::new(movable.position.x, movable.position.y),
+                shield.ring_radius(movable),
+                theme.planet.with_alpha(0.6),
+            );
+        }
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// The world requires a trigger to start the simulation. Here, it is a click onto the
+/// Universe canvas, an (optional) drag, and a release. This system represents the logic
+/// for the click whic will place the (heroic) planet at the location underneath the mouse
+/// cursor. planet_placed is only set once the planet has actually been spawned: a click
+/// that can not be mapped into the universe shows the PlacementNotice instead.
+///
+/// Most of this confusing logic are just coordinate mappings: the trigger (On<Pointer<Press>>)
+/// stores it's mouse coordinates in viewport coordinates = pixels of the rendering window but we
+/// need World coordinates which represents the universe as seen by the camera.
+/// A click with Shift held scatters black holes instead, see fn scatter_cluster
+#[allow(clippy::too_many_arguments)]
+fn place_planet(
+    trigger: On<Pointer<Press>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    open: Res<SettingsOpen>,
+    mut state: ResMut<GameState>,
+    mut mouse_launch: ResMut<MouseLaunch>,
+    camera_query: Single<(&Camera, &GlobalTransform)>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+    planet_query: Query<Entity, With<ThePlanet>>,
+    mut black_holes: Query<(&mut Movable, &mut Transform), Without<ThePlanet>>,
+    layout: Res<UniverseLayout>,
+    mut feedback: ResMut<PlacementFeedback>,
+) {
+    //touches are handled by fn touch_controls
+    if open.0 || state.game_started || trigger.pointer_id.is_touch() || scatter_held(&keys) {
+        return;
+    }
+
+    let position = trigger.pointer_location.position; //Vec2 in screen coordianates (int, top left is 0,0)
+
+    let (camera, camera_transform) = *camera_query;
+    if let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, position) {
+        spawn_planet(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &theme,
+            planet_query.iter(),
+            &mut black_holes,
+            &layout,
+            world_pos,
+        );
+        state.planet_placed = true;
+        mouse_launch.press(false);
+    } else {
+        feedback.fail();
+
+        //an aborted placement must never leave an older planet armed for launch
+        for entity in planet_query.iter() {
+            destroy_object(&mut commands, entity);
+        }
+        state.planet_placed = false;
+        mouse_launch.disarm();
+    }
+}
+
+/// not called directly from a system/event loop but is instead a helper function
+/// shared by every input method (mouse, gamepad, touch, replay) that can place the planet.
+/// Removes any previously placed planet, returns the black holes to their generated
+/// positions (undoing the safe bubble of any earlier placement, so the universe only
+/// depends on the final placement), clears the safe bubble around the new
+/// location, spawns the planet at world_pos (World coordinates) and writes PlanetPlaced
+#[allow(clippy::too_many_arguments)]
+fn spawn_planet(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    theme: &Theme,
+    planets: impl IntoIterator<Item = Entity>,
+    black_holes: &mut Query<(&mut Movable, &mut Transform), Without<ThePlanet>>,
+    layout: &UniverseLayout,
+    world_pos: Vec2,
+) {
+    for entity in planets {
+        //prevent any bugs with the click capture
+        destroy_object(commands, entity);
+    }
+
+    restore_layout(layout, black_holes);
+
+    let planet = Movable::new(&ObjectType::World)
+        .set_position(world_pos.x, world_pos.y)
+        .set_velocity(0.0, 0.0)
+        .set_size(0.0, 50.0)
+        .build();
+
+    clear_planet_bubble(&planet, black_holes, &layout.universe);
+
+    spawn_the_planet(commands, meshes, materials, theme, planet);
+    commands.write_message(PlanetPlaced {
+        position: world_pos,
+    });
+}
+
+/// not called directly from a system/event loop but is instead a helper function
+/// called by spawn_planet. Moves every black hole back to the position it was generated
+/// at (see UniverseLayout::black_holes). The black holes are matched up in ID order;
+/// nothing is moved if the field no longer matches the layout (e.g. a slider changed
+/// this frame, in which case update_slider_results regenerates the field anyway)
+fn restore_layout(
+    layout: &UniverseLayout,
+    black_holes: &mut Query<(&mut Movable, &mut Transform), Without<ThePlanet>>,
+) {
+    let Some(generated) = layout.black_holes() else {
+        return;
+    };
+
+    let mut present: Vec<(Mut<Movable>, Mut<Transform>)> = black_holes
+        .iter_mut()
+        .filter(|(movable, _)| movable.otype == ObjectType::BlackHole)
+        .collect();
+    if present.len() != generated.len() {
+        return;
+    }
+
+    present.sort_by_key(|(movable, _)| movable.get_id());
+    for ((mut movable, mut transform), original) in present.into_iter().zip(generated) {
+        movable.position = original.position;
+        transform.translation.x = original.position.x;
+        transform.translation.y = original.position.y;
+    }
+}
+
+/// not called directly from a system/event loop but is instead a helper function
+/// called by spawn_planet and launch_planet. Nudges every black hole within
+/// PLANET_CLEARANCE of the planet's hitbox outward so the simulation can never
+/// begin with the planet already inside a black hole
+fn clear_planet_bubble(
+    planet: &Movable,
+    black_holes: &mut Query<(&mut Movable, &mut Transform), Without<ThePlanet>>,
+    universe: &UniverseConfig,
+) {
+    for (mut movable, mut transform) in black_holes.iter_mut() {
+        if movable.clear_bubble(planet, PLANET_CLEARANCE, universe) {
+            transform.translation.x = movable.position.x;
+            transform.translation.y = movable.position.y;
+        }
+    }
+}
+
+/// Observer: a press on the placed planet (rather than on the universe, which places
+/// a new one, see fn place_planet) arms the mouse to re-aim or launch it (see MouseLaunch)
+fn aim_planet(
+    trigger: On<Pointer<Press>>,
+    open: Res<SettingsOpen>,
+    state: Res<GameState>,
+    mut mouse_launch: ResMut<MouseLaunch>,
+) {
+    //touches are handled by fn touch_controls
+    if open.0 || state.game_started || !state.planet_placed || trigger.pointer_id.is_touch() {
+        return;
+    }
+
+    mouse_launch.press(true);
+}
+
+/// Observer: captures the dragging motion after a press which placed the planet or
+/// landed on it. The drag sets the velocity such that the planet's heading follows the
+/// arrow drawn from where the drag began to the mouse's current location, with the
+/// magnitude of that distance translated into velocity (kinda like strecthing a rubberband).
+/// Every drag aims afresh rather than adding to the previous aim, so a misjudged flick
+/// is corrected by simply dragging again
+fn planet_dragged(
+    drag: On<Pointer<Drag>>,
+    open: Res<SettingsOpen>,
+    state: Res<GameState>,
+    mut mouse_launch: ResMut<MouseLaunch>,
+    launch: Res<LaunchConfig>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut planet_query: Query<&mut Movable, With<ThePlanet>>,
+) {
+    //touches are handled by fn touch_controls
+    if open.0 || state.game_started || drag.pointer_id.is_touch() || !mouse_launch.armed {
+        return;
+    }
+
+    let Ok(mut planet) = planet_query.single_mut() else {
+        return;
+    };
+
+    mouse_launch.dragged = true;
+    let scale = launch.scale(window_query.single().ok());
+    planet.velocity.vx = drag.distance.x * scale;
+    planet.velocity.vy = -drag.distance.y * scale;
+}
+
+/// Schedule: Update Bevy System
+///
+/// copies the Launch slider's value into the LaunchConfig resource so that
+/// every input method picks up the player's chosen launch sensitivity
+fn update_launch_config(
+    sliders: Query<(&SliderValue, &SliderType)>,
+    mut launch: ResMut<LaunchConfig>,
+) {
+    for (slider_value, slider_type) in sliders {
+        if let SliderType::Launch = slider_type {
+            let sensitivity = LaunchConfig::from_slider(slider_value.value).sensitivity;
+            if launch.sensitivity != sensitivity {
+                launch.sensitivity = sensitivity;
+            }
+        }
+    }
+}
+
+/// Observer: initiates the start of the game / simulation. Only a click on the placed
+/// planet (a press and release without dragging) launches it: the release ending the
+/// placement or an aiming drag leaves the planet waiting (see MouseLaunch). Updates the
+/// GameState resource to indicate the simluation has begun and sets the reference
+/// start time for the clocks (see fn launch_planet)
+fn check_for_start(
+    trigger: On<Pointer<Release>>,
+    open: Res<SettingsOpen>,
+    mut commands: Commands,
+    mut state: ResMut<GameState>,
+    mut mouse_launch: ResMut<MouseLaunch>,
+    time: Res<Time<Fixed>>,
+    planet_query: Query<&Movable, With<ThePlanet>>,
+    mut black_holes: Query<(&mut Movable, &mut Transform), Without<ThePlanet>>,
+    layout: Res<UniverseLayout>,
+) {
+    //touches are handled by fn touch_controls
+    if open.0 || trigger.pointer_id.is_touch() || !mouse_launch.release() {
+        return;
+    }
+
+    launch_planet(
+        &mut commands,
+        &mut state,
+        &time,
+        planet_query.iter().next(),
+        &mut black_holes,
+        &layout.universe,
+    );
+}
+
+/// not called directly from a system/event loop but is instead a helper function
+/// shared by every input method (mouse, gamepad, touch) that can launch the planet.
+///
+/// The planet's safe bubble is re-checked here because the sliders may have
+/// reshuffled the black holes after the planet was placed. The start time is taken
+/// from the fixed physics clock, the launch is kept for saving a Recording and
+/// GameStarted is written
+fn launch_planet(
+    commands: &mut Commands,
+    state: &mut GameState,
+    time: &Time<Fixed>,
+    planet: Option<&Movable>,
+    black_holes: &mut Query<(&mut Movable, &mut Transform), Without<ThePlanet>>,
+    universe: &UniverseConfig,
+) {
+    let Some(planet) = planet else {
+        return;
+    };
+
+    if state.game_started || !state.planet_placed {
+        return;
+    }
+
+    //never start a universe that is already in the game over condition
+    let bh_count = black_holes
+        .iter()
+        .filter(|(movable, _)| movable.otype == ObjectType::BlackHole)
+        .count();
+    if bh_count + 1 < MINIMUM_OBJECTS {
+        warn!("launch ignored: at least {MINIMUM_OBJECTS} objects are required to start");
+        return;
+    }
+
+    clear_planet_bubble(planet, black_holes, universe);
+
+    let position = Vec2::new(planet.position.x, planet.position.y);
+    let velocity = Vec2::new(planet.velocity.vx, planet.velocity.vy);
+
+    state.game_started = true;
+    state.start_time = time.elapsed_secs_f64();
+    state.launch = Some((position, velocity));
+    commands.write_message(GameStarted {
+        time: state.start_time,
+        position,
+        velocity,
+    });
+}
+
+/// Schedule: Update Bevy System
+///
+/// Flips the InfluenceRings overlay on and off with the `I` key
+fn toggle_influence_rings(keys: Res<ButtonInput<KeyCode>>, mut rings: ResMut<InfluenceRings>) {
+    if keys.just_pressed(KeyCode::KeyI) {
+        rings.0 = !rings.0;
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Draws a faint ring around every black hole at the planet's capture distance:
+/// the exact separation at which CollisionDetection::collided reports a hit
+/// against the planet. Runs after update_collisions so that merged black holes
+/// show their grown danger zone on the same frame
+fn draw_influence_rings(
+    rings: Res<InfluenceRings>,
+    planet: Query<&Movable, With<ThePlanet>>,
+    objects: Query<&Movable, Without<ThePlanet>>,
+    theme: Res<Theme>,
+    mut gizmos: Gizmos,
+) {
+    if !rings.0 {
+        return;
+    }
+
+    let Ok(planet) = planet.single() else {
+        return;
+    };
+
+    for movable in objects {
+        if movable.otype != ObjectType::BlackHole {
+            continue;
+        }
+
+        gizmos.circle_2d(
+            Vec2::new(movable.position.x, movable.position.y),
+            movable.contact_distance(planet),
+            theme.ring,
+        );
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Flips the EdgeIndicators on and off with the `E` key
+fn toggle_edge_indicators(keys: Res<ButtonInput<KeyCode>>, mut edges: ResMut<EdgeIndicators>) {
+    if keys.just_pressed(KeyCode::KeyE) {
+        edges.0 = !edges.0;
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Draws the spherical wrap (see edges.rs): a faint arrow on the opposite edge for every
+/// object about to cross an edge, pointing the way it will come back in and growing as
+/// it nears the edge, and a faint line out of both edges joining every pair which pulls
+/// on each other through the seam, so that an object accelerating toward an empty edge
+/// shows what pulls it there
+fn draw_edge_indicators(
+    edges: Res<EdgeIndicators>,
+    objects: Query<&Movable>,
+    theme: Res<Theme>,
+    layout: Res<UniverseLayout>,
+    mut gizmos: Gizmos,
+) {
+    if !edges.0 {
+        return;
+    }
+
+    let movables: Vec<&Movable> = objects.iter().collect();
+
+    for movable in &movables {
+        for marker in edge_markers(
+            Vec2::new(movable.position.x, movable.position.y),
+            &layout.universe,
+        ) {
+            gizmos.arrow_2d(
+                marker.position,
+                marker.position + marker.direction * EDGE_MARKER_LENGTH * marker.strength,
+                theme.highlight.with_alpha(0.15 + 0.35 * marker.strength),
+            );
+        }
+    }
+
+    for (index, one) in movables.iter().enumerate() {
+        for two in &movables[index + 1..] {
+            if let Some(segments) = wrapped_pull(one, two, &layout.universe) {
+                for (start, end) in segments {
+                    gizmos.line_2d(start, end, theme.highlight.with_alpha(0.25));
+                }
+            }
+        }
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Switches the ReferenceFrame between the Universe and CenterOfMass with the `F` key.
+/// The frame is part of a Recording, so it is locked once the planet is launched
+fn toggle_reference_frame(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<GameState>,
+    mut frame: ResMut<ReferenceFrame>,
+) {
+    if keys.just_pressed(KeyCode::KeyF) && !locked_mid_run(&state, "frame of reference") {
+        *frame = frame.toggle();
+        info!("frame of reference: {:?}", *frame);
+    }
+}
+
+/// not called directly from a system/event loop but is instead a helper function
+/// called by the systems changing a setting which is part of a Recording (see
+/// recording.rs). Returns true, and warns, if the planet has been launched: changing
+/// the setting mid-run would make the run impossible to replay
+fn locked_mid_run(state: &GameState, setting: &str) -> bool {
+    if state.game_started {
+        warn!("the {setting} can not change once the planet is launched");
+    }
+    state.game_started
+}
+
+/// Schedule: Update Bevy System
+///
+/// Halves (`[`) or doubles (`]`) the PhysicsConfig softening prior to launch: sharper or
+/// smoother close passes. The new value is logged. The softening is part of a Recording,
+/// so it is locked once the planet is launched
+fn adjust_softening(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<GameState>,
+    mut config: ResMut<PhysicsConfig>,
+) {
+    let factor = if keys.just_pressed(KeyCode::BracketLeft) {
+        0.5
+    } else if keys.just_pressed(KeyCode::BracketRight) {
+        2.0
+    } else {
+        return;
+    };
+
+    if locked_mid_run(&state, "gravitational softening") {
+        return;
+    }
+
+    config.softening *= factor;
+    info!("gravitational softening: {}", config.softening);
+}
+
+/// A helper function which formats the SandboxMode label of the HUD
+fn sandbox_text(sandbox: bool) -> String {
+    format!("Sandbox: {} (S)", if sandbox { "On" } else { "Off" })
+}
+
+/// Schedule: Update Bevy System
+///
+/// Flips SandboxMode on and off with the `S` key, at any time. Switching it off
+/// after launch lets check_for_gameover end a universe which is already down to one object
+fn toggle_sandbox(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut sandbox: ResMut<SandboxMode>,
+    mut label_query: Query<&mut Text, With<SandboxLabel>>,
+) {
+    if keys.just_pressed(KeyCode::KeyS) {
+        sandbox.0 = !sandbox.0;
+    }
+
+    if sandbox.is_changed() {
+        for mut label in &mut label_query {
+            **label = sandbox_text(sandbox.0);
+        }
+    }
+}
+
+/// Observer: in SandboxMode, a click into the universe after launch adds a resting
+/// black hole of the mean mass (left click) or a planet (right click) under the cursor
+#[allow(clippy::too_many_arguments)]
+fn sandbox_spawn(
+    trigger: On<Pointer<Press>>,
+    open: Res<SettingsOpen>,
+    state: Res<GameState>,
+    sandbox: Res<SandboxMode>,
+    camera_query: Single<(&Camera, &GlobalTransform)>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+) {
+    if open.0 || !sandbox.0 || !state.game_started {
+        return;
+    }
+
+    let (camera, camera_transform) = *camera_query;
+    let Ok(world_pos) =
+        camera.viewport_to_world_2d(camera_transform, trigger.pointer_location.position)
+    else {
+        return;
+    };
+
+    let object = match trigger.button {
+        PointerButton::Secondary => Movable::new(&ObjectType::World)
+            .set_position(world_pos.x, world_pos.y)
+            .set_size(0.0, 50.0)
+            .build(),
+        _ => Movable::new(&ObjectType::BlackHole)
+            .set_position(world_pos.x, world_pos.y)
+            .set_mass((BLACKHOLE_MASS_RNG.upper + BLACKHOLE_MASS_RNG.lower) / 2.0)
+            .build(),
+    };
+
+    spawn_object(&mut commands, &mut meshes, &mut materials, &theme, object);
+}
+
+/// not called directly from a system/event loop but is instead a helper function
+/// which returns true while the modifier of the scatter tool (either Shift key) is held
+fn scatter_held(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+/// Observer: before launch, a left click into the universe with Shift held scatters a
+/// cluster of black holes around the cursor (see UniverseSettings::scatter) on top of
+/// the field, so a lopsided universe can be built by hand. A placed planet keeps its
+/// safe bubble and is ordered after the cluster, as in a replay. The click and seed are
+/// kept on the layout (see UniverseLayout::scatter): slider edits, restarts and replays
+/// draw the clusters again, while a new seed generates a fresh field without them
+#[allow(clippy::too_many_arguments)]
+fn scatter_cluster(
+    trigger: On<Pointer<Press>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    open: Res<SettingsOpen>,
+    state: Res<GameState>,
+    camera_query: Single<(&Camera, &GlobalTransform)>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+    sliders: Query<(&SliderValue, &SliderType)>,
+    pattern: Res<VelocityPattern>,
+    clusters: Res<ClusterCount>,
+    mut layout: ResMut<UniverseLayout>,
+    mut planet_query: Query<&mut Movable, With<ThePlanet>>,
+) {
+    if open.0
+        || state.game_started
+        || trigger.pointer_id.is_touch()
+        || trigger.button != PointerButton::Primary
+        || !scatter_held(&keys)
+    {
+        return;
+    }
+
+    let (camera, camera_transform) = *camera_query;
+    let Ok(world_pos) =
+        camera.viewport_to_world_2d(camera_transform, trigger.pointer_location.position)
+    else {
+        return;
+    };
+
+    let settings = UniverseSettings::from_sliders(&sliders, *pattern, *clusters);
+    let scattered = layout.scatter(
+        &settings,
+        Scatter {
+            position: world_pos,
+            seed: rand::random(),
+        },
+    );
+
+    for mut planet in &mut planet_query {
+        planet.renew_id();
+    }
+
+    for mut black_hole in scattered {
+        for planet in &planet_query {
+            black_hole.clear_bubble(planet, PLANET_CLEARANCE, &layout.universe);
+        }
+        spawn_object(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &theme,
+            black_hole,
+        );
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Flips the lensing halos (see lensing.rs) on and off with the `L` key. Halos of
+/// black holes formed by a merge follow the current setting
+fn toggle_lensing(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut lensing: ResMut<Lensing>,
+    mut lens_query: Query<&mut Visibility, With<Lens>>,
+) {
+    if keys.just_pressed(KeyCode::KeyL) {
+        lensing.0 = !lensing.0;
+    }
+
+    let visibility = if lensing.0 {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut lens in &mut lens_query {
+        lens.set_if_neq(visibility);
+    }
+}
+
+/// Schedule: Startup Bevy System
+///
+/// Spawns the settings panel (see settings.rs), hidden until the `Escape` key opens it:
+/// a dimmed overlay over the whole window holding a button per Setting and the
+/// Softening and Time Scale sliders, which start at the current PhysicsConfig softening
+/// and TimeScale
+fn setup_settings(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    config: Res<PhysicsConfig>,
+    time_scale: Res<TimeScale>,
+) {
+    let sliders = [
+        (
+            generate_slider(&theme, SliderType::Softening, "Softening"),
+            slider_from_softening(config.softening),
+        ),
+        (
+            generate_slider(&theme, SliderType::TimeScale, "Time Scale"),
+            slider_from_time_scale(time_scale.0),
+        ),
+    ];
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: percent(100),
+                height: percent(100),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: px(10),
+                ..default()
+            },
+            BackgroundColor(theme.background.with_alpha(0.8)),
+            GlobalZIndex(1),
+            Visibility::Hidden,
+            SettingsPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Settings (Esc to resume)"),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(theme.hud_text),
+            ));
+
+            for setting in Setting::ALL {
+                parent.spawn((
+                    setting,
+                    gen_button(&theme, setting.name(), 300, 40, Visibility::Inherited),
+                ));
             }
-            SliderType::Velocity => {
-                bh_vel = (slider_value.value + VELSTDEVMIN)
-                    * (BLACKHOLE_VEL_RNG.upper.abs() + BLACKHOLE_VEL_RNG.lower.abs())
-                    / 2.0;
-                if slider_value.value != slider_value.prev_value {
-                    update_bh_vel = true;
-                }
+
+            for (graphic, value) in sliders {
+                parent
+                    .spawn((
+                        graphic.base,
+                        Interaction::None,
+                        RelativeCursorPosition::default(),
+                        SliderValue {
+                            value,
+                            prev_value: value,
+                        },
+                    ))
+                    .with_children(|slider| {
+                        slider.spawn((graphic.bkg, SliderBkg));
+                        slider.spawn(graphic.text);
+                    });
             }
-            SliderType::Density => {
-                //use 1-slider value so that max on the bar squeezes the universe the most
-                bh_pos_std = (1.0 - slider_value.value + POSSTDEVMIN) * UNIVERSE_SIZE / 2.0; //universesize/4 is max - basically fills the universe
-                if slider_value.value != slider_value.prev_value {
-                    update_bh_pos = true;
+        });
+}
+
+/// Schedule: Update Bevy System
+///
+/// Opens and closes the settings panel with the `Escape` key. The virtual clock, which
+/// drives the fixed physics clock, is paused while the panel is open so that closing
+/// it resumes the universe exactly where it stopped, with the chosen settings applied
+fn toggle_settings(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut open: ResMut<SettingsOpen>,
+    mut time: ResMut<Time<Virtual>>,
+    mut panel_query: Query<&mut Visibility, With<SettingsPanel>>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    open.0 = !open.0;
+    if open.0 {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+
+    for mut visibility in &mut panel_query {
+        *visibility = if open.0 {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Flips the Setting of a pressed settings panel button, coloring the buttons on hover
+/// and press like the game over buttons (see fn update_btn). Every button's label then
+/// shows the current state of its setting, which may also have been flipped by its key.
+/// The Theme button moves on to the next preset, which fn apply_theme then paints
+#[allow(clippy::too_many_arguments)]
+fn settings_buttons(
+    mut input_focus: ResMut<InputFocus>,
+    mut button_query: Query<
+        (Entity, &Interaction, &mut BackgroundColor, &Setting),
+        Changed<Interaction>,
+    >,
+    label_query: Query<(&Setting, &Children)>,
+    mut text_query: Query<&mut Text>,
+    mut theme: ResMut<Theme>,
+    mut sandbox: ResMut<SandboxMode>,
+    mut lensing: ResMut<Lensing>,
+    mut rings: ResMut<InfluenceRings>,
+    mut frame: ResMut<ReferenceFrame>,
+    mut gravity: ResMut<GravityMatrix>,
+    mut edges: ResMut<EdgeIndicators>,
+    mut bullet: ResMut<BulletTime>,
+    mut physics: ResMut<PhysicsConfig>,
+    state: Res<GameState>,
+) {
+    for (entity, interaction, mut background_color, setting) in &mut button_query {
+        let state = match *interaction {
+            Interaction::None => BtnState::None,
+            Interaction::Hovered => BtnState::Hovered,
+            Interaction::Pressed => {
+                match setting {
+                    Setting::Sandbox => sandbox.0 = !sandbox.0,
+                    Setting::Lensing => lensing.0 = !lensing.0,
+                    Setting::InfluenceRings => rings.0 = !rings.0,
+                    Setting::CenterOfMass => {
+                        if !locked_mid_run(&state, "frame of reference") {
+                            *frame = frame.toggle();
+                        }
+                    }
+                    Setting::PlanetGravity => {
+                        if !locked_mid_run(&state, "planet gravity") {
+                            let on = !(gravity.planet_on_black_hole || gravity.planet_on_planet);
+                            gravity.planet_on_black_hole = on;
+                            gravity.planet_on_planet = on;
+                        }
+                    }
+                    Setting::EdgeIndicators => edges.0 = !edges.0,
+                    Setting::BulletTime => bullet.enabled = !bullet.enabled,
+                    Setting::Verlet => {
+                        if !locked_mid_run(&state, "integrator") {
+                            physics.integrator = physics.integrator.toggle();
+                        }
+                    }
+                    Setting::Theme => *theme = theme.next(),
                 }
+                BtnState::Pressed
+            }
+        };
+        update_btn(
+            entity,
+            &mut input_focus,
+            &mut background_color,
+            &theme,
+            state,
+        );
+    }
+
+    for (setting, children) in &label_query {
+        let label = match setting {
+            Setting::Sandbox => setting.label(sandbox.0),
+            Setting::Lensing => setting.label(lensing.0),
+            Setting::InfluenceRings => setting.label(rings.0),
+            Setting::CenterOfMass => setting.label(*frame == ReferenceFrame::CenterOfMass),
+            Setting::PlanetGravity => {
+                setting.label(gravity.planet_on_black_hole || gravity.planet_on_planet)
+            }
+            Setting::EdgeIndicators => setting.label(edges.0),
+            Setting::BulletTime => setting.label(bullet.enabled),
+            Setting::Verlet => setting.label(physics.integrator == IntegratorMode::Verlet),
+            Setting::Theme => setting.choice_label(theme.name),
+        };
+
+        let mut text_iter = text_query.iter_many_mut(children);
+        while let Some(mut text) = text_iter.fetch_next() {
+            if **text != label {
+                **text = label.clone();
             }
         }
     }
+}
 
-    // build our random-normal number generators using the slider-bar metrics from above:
-    let mut position_rand = Gauss::new(
-        0.0,
-        bh_pos_std,
-        GaussBoundary::WrapBoth((-UNIVERSE_SIZE / 2.0, UNIVERSE_SIZE / 2.0)),
-    );
+/// Schedule: Update Bevy System
+///
+/// Repaints the field, the objects and the HUD once the Theme has changed (see
+/// Setting::Theme): every color spawned from the previous theme is swapped for its
+/// counterpart in the new one (see Theme::recolor_field and Theme::recolor_hud). Objects
+/// spawned afterwards read the new theme themselves, as do the gizmos every frame
+fn apply_theme(
+    theme: Res<Theme>,
+    mut previous: Local<Option<Theme>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut text_query: Query<&mut TextColor>,
+    mut background_query: Query<&mut BackgroundColor>,
+    mut border_query: Query<&mut BorderColor>,
+) {
+    let Some(old) = previous.replace(*theme) else {
+        return;
+    };
+    if old == *theme {
+        return;
+    }
 
-    let mut bh_mass_rand = Gauss::new(
-        bh_mass,
-        BLACKHOLE_MASS_RNG.upper / 4.0,
-        GaussBoundary::ClampBoth((BLACKHOLE_MASS_RNG.lower, BLACKHOLE_MASS_RNG.upper)),
-    );
+    for (_, material) in materials.iter_mut() {
+        material.color = theme.recolor_field(&old, material.color);
+    }
+    for mut color in &mut text_query {
+        color.0 = theme.recolor_hud(&old, color.0);
+    }
+    for mut color in &mut background_query {
+        color.0 = theme.recolor_hud(&old, color.0);
+    }
+    for mut border in &mut border_query {
+        border.top = theme.recolor_hud(&old, border.top);
+        border.right = theme.recolor_hud(&old, border.right);
+        border.bottom = theme.recolor_hud(&old, border.bottom);
+        border.left = theme.recolor_hud(&old, border.left);
+    }
+}
 
-    let mut bh_vel_rand = Gauss::new(
-        0.0,
-        bh_vel,
-        GaussBoundary::ClampBoth((BLACKHOLE_VEL_RNG.lower, BLACKHOLE_VEL_RNG.upper)),
-    );
+/// Schedule: Update Bevy System
+///
+/// Keeps the settings panel's Softening slider and the PhysicsConfig in step: dragging
+/// the slider sets the softening (see SOFTENING_RNG), while a change made with the
+/// `[` and `]` keys (see fn adjust_softening) moves the slider to match. Once the planet
+/// is launched the softening is locked (see fn locked_mid_run): dragging the slider
+/// then only snaps it back
+fn update_softening_setting(
+    mut sliders: Query<(&Interaction, &mut SliderValue, &SliderType)>,
+    state: Res<GameState>,
+    mut config: ResMut<PhysicsConfig>,
+) {
+    for (interaction, mut slider_value, slider_type) in &mut sliders {
+        if !matches!(slider_type, SliderType::Softening) {
+            continue;
+        }
 
-    // if the blackhole masses slider has changed, implement those changes to objects already rendered:
-    if update_bh_masses {
-        for (_entity, mut movable, mut transform) in &mut objects {
-            if movable.otype == ObjectType::BlackHole {
-                let new_mass = bh_mass_rand.sample();
-                let old_mass = movable.size.mass;
-                let ratio = new_mass / old_mass;
+        if matches!(interaction, Interaction::Pressed) {
+            if !state.game_started {
+                let softening = softening_from_slider(slider_value.value);
+                if config.softening != softening {
+                    config.softening = softening;
+                }
+            } else if slider_value.value != slider_value.prev_value {
+                locked_mid_run(&state, "gravitational softening");
+                let value = slider_from_softening(config.softening);
+                slider_value.value = value;
+                slider_value.prev_value = value;
+            }
+        } else if config.is_changed() {
+            let value = slider_from_softening(config.softening);
+            slider_value.value = value;
+            slider_value.prev_value = value;
+        }
+    }
+}
 
-                movable.set_mass(new_mass);
-                transform.scale *= ratio;
+/// Schedule: Update Bevy System
+///
+/// Sets the TimeScale from the settings panel's Time Scale slider (see TIME_SCALE_RNG).
+/// Unlike the softening it may change mid-run: it only changes how fast the universe
+/// plays, not how it evolves (see fn update_bullet_time)
+fn update_time_scale_setting(
+    sliders: Query<(&Interaction, &SliderValue, &SliderType)>,
+    mut time_scale: ResMut<TimeScale>,
+) {
+    for (interaction, slider_value, slider_type) in &sliders {
+        if matches!(slider_type, SliderType::TimeScale)
+            && matches!(interaction, Interaction::Pressed)
+        {
+            let scale = time_scale_from_slider(slider_value.value);
+            if time_scale.0 != scale {
+                time_scale.0 = scale;
             }
         }
     }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Moves the gamepad cursor with the left stick of the first connected gamepad
+/// and draws it as a ring so that the planet can be placed without a mouse.
+/// The cursor is confined to the universe and is only drawn prior to launch
+fn gamepad_cursor(
+    time: Res<Time>,
+    state: Res<GameState>,
+    gamepads: Query<&Gamepad>,
+    mut cursor: ResMut<GamepadCursor>,
+    theme: Res<Theme>,
+    layout: Res<UniverseLayout>,
+    mut gizmos: Gizmos,
+) {
+    if state.game_started {
+        return;
+    }
+
+    let Some(gamepad) = gamepads.iter().next() else {
+        return;
+    };
+
+    let half = layout.universe.half();
+    cursor.position += gamepad.left_stick() * GAMEPAD_CURSOR_SPEED * time.delta_secs();
+    cursor.position = cursor.position.clamp(-half, half);
+
+    gizmos.circle_2d(cursor.position, GAMEPAD_CURSOR_RADIUS, theme.highlight);
+}
+
+/// Schedule: Update Bevy System
+///
+/// Gamepad counterpart to place_planet, planet_dragged and check_for_start:
+/// 1. the South button places the planet underneath the gamepad cursor
+/// 2. the right stick acts as a virtual drag, scaled exactly as a mouse drag (see LaunchConfig)
+/// 3. the right trigger launches the planet
+#[allow(clippy::too_many_arguments)]
+fn gamepad_controls(
+    time: Res<Time>,
+    fixed_time: Res<Time<Fixed>>,
+    mut state: ResMut<GameState>,
+    gamepads: Query<&Gamepad>,
+    cursor: Res<GamepadCursor>,
+    launch: Res<LaunchConfig>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+    mut planet_query: Query<(Entity, &mut Movable), With<ThePlanet>>,
+    mut black_holes: Query<(&mut Movable, &mut Transform), Without<ThePlanet>>,
+    layout: Res<UniverseLayout>,
+) {
+    if state.game_started {
+        return;
+    }
+
+    let Some(gamepad) = gamepads.iter().next() else {
+        return;
+    };
+
+    if gamepad.just_pressed(GamepadButton::South) {
+        spawn_planet(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &theme,
+            planet_query.iter().map(|(entity, _)| entity),
+            &mut black_holes,
+            &layout,
+            cursor.position,
+        );
+        state.planet_placed = true;
+        return;
+    }
+
+    if let Ok((_, mut planet)) = planet_query.single_mut() {
+        //stick y is up-positive already, unlike the screen space drag delta
+        let delta = gamepad.right_stick() * GAMEPAD_DRAG_SPEED * time.delta_secs();
+        let scale = launch.scale(window_query.single().ok());
+        planet.velocity.vx += delta.x * scale;
+        planet.velocity.vy += delta.y * scale;
+    }
+
+    if gamepad.just_pressed(GamepadButton::RightTrigger2) {
+        launch_planet(
+            &mut commands,
+            &mut state,
+            &fixed_time,
+            planet_query.iter().next().map(|(_, planet)| planet),
+            &mut black_holes,
+            &layout.universe,
+        );
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Touch counterpart to place_planet, planet_dragged and check_for_start. The finger
+/// that places the planet is remembered so that only it can aim and launch:
+/// 1. a tap inside the universe places the planet underneath the finger
+/// 2. dragging that finger adds to the launch velocity, scaled exactly as a mouse drag
+/// 3. lifting that finger off the screen starts the simulation
+///
+/// Touches that begin on a slider bar are left to the slider systems, which
+/// already respond to touch through the Bevy UI Interaction component
+#[allow(clippy::too_many_arguments)]
+fn touch_controls(
+    touches: Res<Touches>,
+    time: Res<Time<Fixed>>,
+    mut state: ResMut<GameState>,
+    mut touch_launch: ResMut<TouchLaunch>,
+    launch: Res<LaunchConfig>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Single<(&Camera, &GlobalTransform)>,
+    sliders: Query<&Interaction, With<SliderValue>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+    mut planet_query: Query<(Entity, &mut Movable), With<ThePlanet>>,
+    mut black_holes: Query<(&mut Movable, &mut Transform), Without<ThePlanet>>,
+    layout: Res<UniverseLayout>,
+    mut feedback: ResMut<PlacementFeedback>,
+) {
+    if state.game_started {
+        touch_launch.finger = None;
+        return;
+    }
+
+    let (camera, camera_transform) = *camera_query;
+    let on_slider = sliders
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+
+    for touch in touches.iter_just_pressed() {
+        if on_slider {
+            break;
+        }
+
+        let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, touch.position()) else {
+            feedback.fail();
+            continue;
+        };
+
+        if !layout.universe.contains(world_pos) {
+            continue;
+        }
+
+        spawn_planet(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &theme,
+            planet_query.iter().map(|(entity, _)| entity),
+            &mut black_holes,
+            &layout,
+            world_pos,
+        );
+        state.planet_placed = true;
+        touch_launch.finger = Some(touch.id());
+        //the planet is spawned by a deferred command: it can be aimed next frame
+        return;
+    }
+
+    let Some(finger) = touch_launch.finger else {
+        return;
+    };
+
+    if let Some(touch) = touches.get_pressed(finger)
+        && let Ok((_, mut planet)) = planet_query.single_mut()
+    {
+        let scale = launch.scale(window_query.single().ok());
+        planet.velocity.vx += touch.delta().x * scale;
+        planet.velocity.vy += -touch.delta().y * scale;
+    }
+
+    if touches.just_canceled(finger) {
+        touch_launch.finger = None;
+    } else if touches.just_released(finger) {
+        touch_launch.finger = None;
+        launch_planet(
+            &mut commands,
+            &mut state,
+            &time,
+            planet_query.iter().next().map(|(_, planet)| planet),
+            &mut black_holes,
+            &layout.universe,
+        );
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Replays the launch of a Recording (see setup_objects):
+/// 1. places the planet at the recorded position
+/// 2. once it has spawned, gives it the recorded velocity and launches it
+///
+/// The Replay resource is emptied once launched so the rest of the run,
+/// and any restart, plays as normal
+#[allow(clippy::too_many_arguments)]
+fn replay_launch(
+    mut replay: ResMut<Replay>,
+    time: Res<Time<Fixed>>,
+    mut state: ResMut<GameState>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<Theme>,
+    mut planet_query: Query<(Entity, &mut Movable), With<ThePlanet>>,
+    mut black_holes: Query<(&mut Movable, &mut Transform), Without<ThePlanet>>,
+    layout: Res<UniverseLayout>,
+) {
+    let Some((planet_position, planet_velocity)) = replay
+        .0
+        .as_ref()
+        .map(|recording| (recording.planet_position, recording.planet_velocity))
+    else {
+        return;
+    };
 
-    // if the blackhole velocity slider changed, implement those changes to the objects already rendered:
-    if update_bh_vel {
-        for (_entity, mut movable, mut _transform) in &mut objects {
-            if movable.otype == ObjectType::BlackHole {
-                movable.set_velocity(bh_vel_rand.sample(), bh_vel_rand.sample());
-            }
-        }
+    if state.game_started {
+        replay.0 = None;
+        return;
     }
 
-    // if the blackhole density slider changed, implement those changes to the objects already rendered:
-    if update_bh_pos {
-        for (_entity, mut movable, mut transform) in &mut objects {
-            if movable.otype == ObjectType::BlackHole {
-                let new_x = position_rand.sample();
-                let new_y = position_rand.sample();
-                let old_x = movable.position.x;
-                let old_y = movable.position.y;
-                let diff_x = new_x - old_x;
-                let diff_y = new_y - old_y;
-
-                movable.set_position(new_x, new_y);
-                transform.translation.x += diff_x;
-                transform.translation.y += diff_y;
-            }
-        }
+    //the recorded universe is spawned by deferred commands too (see apply_new_game):
+    //the planet is only placed once it is there to be cleared from
+    if black_holes.is_empty() {
+        return;
     }
 
-    //add any new objects as necessary
-    while count_difference > 0 {
-        spawn_object(
+    if !state.planet_placed {
+        spawn_planet(
             &mut commands,
             &mut meshes,
             &mut materials,
-            Movable::new(&ObjectType::BlackHole)
-                .set_position(position_rand.sample(), position_rand.sample())
-                .set_velocity(bh_vel_rand.sample(), bh_vel_rand.sample())
-                .set_mass(bh_mass_rand.sample())
-                .build(),
+            &theme,
+            planet_query.iter().map(|(entity, _)| entity),
+            &mut black_holes,
+            &layout,
+            planet_position,
         );
-        count_difference -= 1;
+        state.planet_placed = true;
+        //the planet is spawned by a deferred command: it can be launched next frame
+        return;
     }
 
-    //remove objects, if necessary
-    if count_difference < 0 {
-        for (entity, movable, _transform) in &objects {
-            if movable.otype == ObjectType::BlackHole {
-                destroy_object(&mut commands, entity);
-                count_difference += 1;
-                if count_difference >= 0 {
-                    break;
-                }
-            }
-        }
-    }
+    let Ok((_, mut planet)) = planet_query.single_mut() else {
+        return;
+    };
+    planet.velocity.vx = planet_velocity.x;
+    planet.velocity.vy = planet_velocity.y;
+
+    launch_planet(
+        &mut commands,
+        &mut state,
+        &time,
+        Some(&*planet),
+        &mut black_holes,
+        &layout.universe,
+    );
+    replay.0 = None;
 }
 
 /// Schedule: Update Bevy System
 ///
-/// checks if
-///
-/// 1. the mouse btn is pressed
-/// 2. the mouse position is inside one of the slider-bars
-/// 3. if 1 && 2, store the value inside the SliderValue struct
-///    as a percentage of the bar [0-1]
-///
-/// this stored value will then be used in:
-/// 1. fn update_slider to graphically show the slider bar change
-/// 2. fn update_slider_results to apply these changes to the playing field
-fn drag_slider(
-    mut interaction_query: Query<(&Interaction, &RelativeCursorPosition, &mut SliderValue)>,
+/// Pressing `R` once the planet has been launched saves a Recording of the run (seed,
+/// universe size, slider settings, scatters, black hole threshold, frame of reference,
+/// softening, gravity constant, integrator, GravityMatrix, shield and the launch) to the
+/// working directory. Launching the game with that file replays the run exactly (see
+/// main.rs). A placed field (a preset) has no seed to record
+#[allow(clippy::too_many_arguments)]
+fn save_recording(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<GameState>,
+    layout: Res<UniverseLayout>,
+    bh_threshold: Res<BlackHoleThreshold>,
+    frame: Res<ReferenceFrame>,
+    physics: Res<PhysicsConfig>,
+    gravity: Res<GravityMatrix>,
+    shield_hits: Res<ShieldHits>,
 ) {
-    for (interaction, relative_cursor, mut slider_value) in &mut interaction_query {
-        //check that mouse button is down
-        if !matches!(*interaction, Interaction::Pressed) {
-            continue;
-        }
-
-        //check that it was pressed inside the slider:
-        let Some(pos) = relative_cursor.normalized else {
-            continue;
-        };
+    if !keys.just_pressed(KeyCode::KeyR) {
+        return;
+    }
 
-        //slider takes [0:1] but pos.x.clamp is [-0.5:0.5] so this works as expected:
-        slider_value.prev_value = slider_value.value;
-        slider_value.value = 0.5 + pos.x.clamp(-0.5, 0.5); //percentage
+    let Some((planet_position, planet_velocity)) = state.launch else {
+        warn!("nothing to record: launch the planet first");
+        return;
+    };
+    let Some((seed, settings)) = layout.generated() else {
+        warn!("nothing to record: a placed universe can not be reproduced from a seed");
+        return;
+    };
+
+    let recording = Recording {
+        seed,
+        universe: layout.universe,
+        settings,
+        scatters: layout.scatters().to_vec(),
+        bh_threshold: bh_threshold.0,
+        frame: *frame,
+        softening: physics.softening,
+        gravity_constant: physics.gravity_constant,
+        integrator: physics.integrator,
+        gravity_matrix: *gravity,
+        shield_hits: shield_hits.0,
+        planet_position,
+        planet_velocity,
+    };
+
+    let path = recording.file_name();
+    match recording.save(&path) {
+        Ok(()) => info!("recording saved to {path}"),
+        Err(error) => warn!("failed to save recording to {path}: {error}"),
     }
 }
 
 /// Schedule: Update Bevy System
 ///
-/// physically updates the background of the slider to give the movement response
-/// by changing the width of the node containing the green-colored background
-fn update_slider(
-    parent_query: Query<(&Children, &SliderValue)>,
-    mut child_query: Query<&mut Node, With<SliderBkg>>,
+/// Shows the launch hint prior to the start of the game whenever the universe holds
+/// too few objects to be launched (see fn launch_planet) and hides it otherwise
+fn update_launch_hint(
+    state: Res<GameState>,
+    objects: Query<&Movable>,
+    mut hint_query: Query<&mut Visibility, With<LaunchHint>>,
 ) {
-    for (children, slider_value) in &parent_query {
-        let mut bkg_iter = child_query.iter_many_mut(children);
-        if let Some(mut node) = bkg_iter.fetch_next() {
-            node.width = px(SLIDERWIDTH * slider_value.value);
-        }
+    let blocked = !state.game_started && objects.iter().len() < MINIMUM_OBJECTS;
+
+    for mut visibility in &mut hint_query {
+        *visibility = if blocked {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
     }
 }
 
 /// Schedule: Update Bevy System
 ///
-/// updates the text in the top-left (black hole) and top-right (planet)
-/// timer boxes used to indicate the lifetime of the universe and of our
-/// dear planet.
-///
-/// Note the odd format here:
-/// - the query returns a Query<&mut Text> iterator
-/// - the first deref returns the first &mut Text in that query return
-/// - the second deref returns the internal &String struct underlying the Text
-///   which we then alter via the format!(...) macro
-fn update_clock(
+/// Shows the placement notice for PLACEMENT_NOTICE_SECS after a click or tap failed to
+/// place the planet (see PlacementFeedback) and hides it once the planet is placed
+fn update_placement_notice(
     time: Res<Time>,
-    mut total_time: Query<&mut Text, (With<TotalTime>, Without<WorldTime>)>,
-    mut world_time: Query<&mut Text, (With<WorldTime>, Without<TotalTime>)>,
     state: Res<GameState>,
+    mut feedback: ResMut<PlacementFeedback>,
+    mut notice_query: Query<&mut Visibility, With<PlacementNotice>>,
 ) {
-    if state.game_started {
-        if state.game_alive {
-            for mut clock in &mut total_time {
-                //First deref gets the Text object, 2nd gets the internal String
-                **clock = format!("{:.2}", time.elapsed_secs_f64() - state.start_time);
-            }
-        }
+    let shown = feedback.update(time.elapsed_secs_f64()) && !state.planet_placed;
 
-        if state.world_alive {
-            for mut clock in &mut world_time {
-                //First deref gets the Text object, 2nd gets the internal String
-                **clock = format!("{:.2}", time.elapsed_secs_f64() - state.start_time);
-            }
-        }
+    for mut visibility in &mut notice_query {
+        *visibility = if shown {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
     }
 }
 
 /// Schedule: Update Bevy System
 ///
-/// Updates the velocity of all objects on the playing field.
-/// A vec of Velocity structs is built by calculting the new frame's
-/// velocity using the time between frame renderings and then each
-/// object's velocity is updated
-fn update_velocity(
-    time: Res<Time>,
-    mut objects: Query<&mut Movable, With<Movable>>,
-    state: Res<GameState>,
+/// Refreshes the hover inspector with the live stats of the Hovered object and
+/// keeps it beside the mouse cursor. The tooltip is hidden when nothing is hovered
+/// or the hovered object no longer exists (e.g. it was consumed by a merge)
+fn update_inspector(
+    mut hovered: ResMut<Hovered>,
+    objects: Query<&Movable>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    ui_scale: Res<UiScale>,
+    mut inspector_query: Query<(&mut Text, &mut Node, &mut Visibility), With<Inspector>>,
 ) {
-    if state.game_started && state.game_alive {
-        let vec: Vec<&Movable> = objects.iter().collect();
-        let mut velocities: Vec<Velocity> = Vec::new();
+    let subject = hovered.0.and_then(|entity| objects.get(entity).ok());
+    if subject.is_none() {
+        hovered.0 = None;
+    }
 
-        for movable in &objects {
-            velocities.push(movable.update_velocity(&vec, time.delta_secs()));
+    let cursor = window_query
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+
+    for (mut text, mut node, mut visibility) in &mut inspector_query {
+        match (subject, cursor) {
+            (Some(movable), Some(cursor)) => {
+                text.0 = describe(movable);
+                //the cursor is in window pixels, the node in UiScale'd pixels
+                node.left = px(cursor.x / ui_scale.0 + INSPECTOR_OFFSET.x);
+                node.top = px(cursor.y / ui_scale.0 + INSPECTOR_OFFSET.y);
+                *visibility = Visibility::Visible;
+            }
+            _ => *visibility = Visibility::Hidden,
         }
+    }
+}
 
-        for (index, mut movable) in objects.iter_mut().enumerate() {
-            movable.set_velocity(velocities[index].vx, velocities[index].vy);
+/// Schedule: Update Bevy System
+///
+/// Re-fits the HUD to the primary window whenever it is resized (see hud.rs)
+fn scale_hud(
+    mut resized: MessageReader<WindowResized>,
+    window_query: Query<(), With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    for resize in resized.read() {
+        if window_query.contains(resize.window) {
+            ui_scale.0 = hud_scale(resize.height);
         }
     }
 }
 
 /// Schedule: Update Bevy System
 ///
-/// Physically moves the objects on the playing field.
-/// uses the updated velocities as set by the above System and then
-/// moves the objects based upon the frame rate. Note the wrap around
-/// logic to enfource the Spherical Universe concept
-fn update_motion(
-    time: Res<Time>,
-    mut objects: Query<(&mut Movable, &mut Transform), With<Movable>>,
+/// Keeps the launch prompt in step with the GameState: placing the planet,
+/// then aiming and releasing it. Hidden once the simulation has started
+fn update_launch_prompt(
     state: Res<GameState>,
+    mut prompt_query: Query<(&mut Text, &mut Visibility), With<LaunchPrompt>>,
 ) {
-    if state.game_started && state.game_alive {
-        const BOUNDARY: f32 = 0.5 * UNIVERSE_SIZE;
-        let elapsed = time.delta_secs();
-
-        for (mut movable, mut transform) in &mut objects {
-            //println!("{},{}", movable.velocity.vx, movable.velocity.vy);
-
-            movable.position.x_prev = movable.position.x;
-            movable.position.y_prev = movable.position.y;
-            movable.update_location(elapsed);
-
-            //spherical universe wrap around
-            if movable.position.x > BOUNDARY {
-                movable.position.x -= UNIVERSE_SIZE; //off to right
-            } else if movable.position.x < -BOUNDARY {
-                movable.position.x += UNIVERSE_SIZE; //off to left
-            }
-            if movable.position.y > BOUNDARY {
-                movable.position.y -= UNIVERSE_SIZE; // off to top
-            } else if movable.position.y < -BOUNDARY {
-                movable.position.y += UNIVERSE_SIZE; //off to bottom
+    for (mut text, mut visibility) in &mut prompt_query {
+        match state.prompt() {
+            Some(prompt) => {
+                if text.0 != prompt {
+                    text.0 = prompt.to_string();
+                }
+                *visibility = Visibility::Visible;
             }
-
-            transform.translation.x = movable.position.x;
-            transform.translation.y = movable.position.y;
+            None => *visibility = Visibility::Hidden,
         }
     }
 }
 
 /// Schedule: Update Bevy System
 ///
-/// The workhorse of each frame:
-/// iterates through each object and determines if the current object has
-/// collided with another object.
-///
-/// Because this calculation in O(N^2) but is still embaressingly parallel,
-/// the rayon iterator parallelization logic is used to calculate and collect
-/// a CollisionTree in parallel.
-///
-/// 2 collection types are accumulated:
-/// 1. to_despawn = BtreeSet<Entity>: Entities are id integer codes and so the BTreeSet
-///    automatically guarantees that duplicates will be removed. Used for despawning objects from
-///    the graphical display.
-/// 2. to_destroy = CollisionFrame<'_>: see the movable.rs file for definition. In short, this is
-///    a smart-struct used to prevent duplicate collisions and properly coallesce collision results
-fn update_collisions(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    objects: Query<(Entity, &mut Movable), With<Movable>>,
+/// Watches the planet for gravitational slingshots. A close pass begins once any black hole
+/// is within SLINGSHOT_RANGE_FACTOR combined radii of the planet and ends once none are; if
+/// the planet left the pass with at least SLINGSHOT_MIN_GAIN more speed than it entered with,
+/// the gain is logged and the "Slingshot!" notification is shown for SLINGSHOT_DISPLAY_SECS
+fn detect_slingshot(
+    time: Res<Time>,
     state: Res<GameState>,
+    mut tracker: ResMut<SlingshotTracker>,
+    planet_query: Query<&Movable, With<ThePlanet>>,
+    black_holes: Query<&Movable, Without<ThePlanet>>,
+    mut notice_query: Query<(&mut Text, &mut Visibility), With<SlingshotNotice>>,
 ) {
-    // next check for collisions
-    if state.game_started && state.game_alive {
-        //this set is designed so that the order of the two colliding objects doesn't matter
-        //i.e. there will not be duplicates in this list
+    let now = time.elapsed_secs_f64();
 
-        //a lot of this complexity is to remove double counting and to handle group collisions
-        //a group collision would be one where more than 2 items collided together within the last frame -
-        //happens more often than one might think!
+    if now >= tracker.hide_at {
+        for (_, mut visibility) in &mut notice_query {
+            *visibility = Visibility::Hidden;
+        }
+    }
 
-        let to_despawn: Mutex<BTreeSet<Entity>> = Mutex::new(BTreeSet::<Entity>::new());
-        let to_destroy = Mutex::new(CollisionFrame::new());
+    if !state.game_started || !state.game_alive {
+        tracker.reset();
+        return;
+    }
 
-        objects.par_iter().for_each(|(entity, movable)| {
-            let mut set = CollisionSet::new();
-            let mut collide = false;
+    let Ok(planet) = planet_query.single() else {
+        tracker.reset();
+        return;
+    };
 
-            for (_, item) in objects.iter() {
-                if item != movable && item.collided(movable) {
-                    collide = true;
-                    set.append(item);
-                }
-            }
+    let near = black_holes.iter().any(|bh| {
+        bh.position.distance_to(&planet.position)
+            <= SLINGSHOT_RANGE_FACTOR * (bh.size.radius + planet.size.radius)
+    });
 
-            if collide {
-                let mut despawn_lock = to_despawn.lock().unwrap();
-                despawn_lock.insert(entity);
+    if let Some(gain) = tracker.update(planet.speed(), near) {
+        info!("Slingshot! planet gained {gain:.0} speed");
+        tracker.hide_at = now + SLINGSHOT_DISPLAY_SECS;
 
-                set.append(movable);
+        for (mut text, mut visibility) in &mut notice_query {
+            **text = format!("Slingshot! +{gain:.0}");
+            *visibility = Visibility::Visible;
+        }
+    }
+}
 
-                let mut destroy_lock = to_destroy.lock().unwrap();
-                destroy_lock.push(set);
-            }
-        });
+/// Schedule: Update Bevy System
+///
+/// Checks of end of game logic which occurs if only a single black hole remains (maximal
+/// entropy in this universe). In this event, both clocks are guaranteed to stop and the
+/// frame updating will also yeild. The game never ends in SandboxMode. The end of the
+/// game writes a single GameOver message with the run's stats (see SimStats). Part of
+/// the PhysicsPlugin: the counters and the game over buttons are shown by fn update_counters
+fn check_for_gameover(
+    time: Res<Time<Fixed>>,
+    objects: Query<&Movable>,
+    mut state: ResMut<GameState>,
+    sandbox: Res<SandboxMode>,
+    mut stats: ResMut<SimStats>,
+    mut game_over: MessageWriter<GameOver>,
+) {
+    let snapshot = UniverseSnapshot::capture(&objects, &state, &time);
+    stats.record_count(snapshot.body_count, state.game_started);
 
-        let to_despawn = to_despawn.lock().unwrap();
-        for item in to_despawn.iter() {
-            destroy_object(&mut commands, *item);
+    if state.game_started {
+        if snapshot.planet_count == 0 {
+            state.world_alive = false;
         }
-
-        match to_destroy.lock().unwrap().collect() {
-            CollisionResult::Single(n) => {
-                spawn_object(&mut commands, &mut meshes, &mut materials, n);
-            }
-            CollisionResult::NSize(n) => {
-                //then add
-                for new in n {
-                    spawn_object(&mut commands, &mut meshes, &mut materials, new);
-                }
-            }
-            _ => {}
+        //a sandbox never ends
+        if snapshot.body_count < MINIMUM_OBJECTS && !sandbox.0 && state.game_alive {
+            state.game_alive = false;
+            game_over.write(GameOver {
+                survival_time: snapshot.elapsed,
+                peak_count: stats.peak_count,
+                planet_survived: state.world_alive,
+            });
         }
     }
 }
 
 /// Schedule: Update Bevy System
 ///
-/// The world requires a trigger to start the simulation. Here, it is a click onto the
-/// Universe canvas, an (optional) drag, and a release. This system represents the logic
-/// for the click whic will place the (heroic) planet at the location underneath the mouse
-/// cursor.
-///
-/// Most of this confusing logic are just coordinate mappings: the trigger (On<Pointer<Press>>)
-/// stores it's mouse coordinates in viewport coordinates = pixels of the rendering window but we
-/// need World coordinates which represents the universe as seen by the camera
-fn place_planet(
-    trigger: On<Pointer<Press>>,
-    mut state: ResMut<GameState>,
-    camera_query: Single<(&Camera, &GlobalTransform)>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    planet_query: Query<Entity, With<ThePlanet>>,
+/// Updates the black hole and planet counter Text graphics and shows the game over
+/// buttons once fn check_for_gameover has ended the game
+fn update_counters(
+    universe: Universe,
+    mut bh_count_label: Query<&mut Text, (With<BHCounter>, Without<WorldCounter>)>,
+    mut world_count_label: Query<&mut Text, (With<WorldCounter>, Without<BHCounter>)>,
+    state: Res<GameState>,
+    mut interaction_query: Query<&mut Visibility, With<GameOverBtn>>,
+    mut warned: Local<bool>,
 ) {
-    if state.game_started {
+    let snapshot = universe.snapshot();
+
+    //the HUD may be missing, e.g. despawned by an embedding app: the game state is
+    //still tracked by fn check_for_gameover, only the display is skipped
+    let (Ok(mut bh_count), Ok(mut world_count)) =
+        (bh_count_label.single_mut(), world_count_label.single_mut())
+    else {
+        if !*warned {
+            warn!("HUD counters not found: skipping the game over display");
+            *warned = true;
+        }
         return;
-    }
+    };
 
-    let position = trigger.pointer_location.position; //Vec2 in screen coordianates (int, top left is 0,0)
+    //&Text -> Text -> String
+    **bh_count = format!("{}", snapshot.black_hole_count);
+    **world_count = format!("{}", snapshot.planet_count);
 
-    let (camera, camera_transform) = *camera_query;
-    if let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, position) {
-        for entity in planet_query {
-            //prevent any bugs with the click capture
-            destroy_object(&mut commands, entity);
+    //show the game over buttons
+    if state.game_started && !state.game_alive {
+        for mut visibility in &mut interaction_query {
+            *visibility = Visibility::Visible;
         }
+    }
+}
 
-        spawn_object(
-            &mut commands,
-            &mut meshes,
-            &mut materials,
-            Movable::new(&ObjectType::World)
-                .set_position(world_pos.x, world_pos.y)
-                .set_velocity(0.0, 0.0)
-                .set_size(0.0, 50.0)
-                .build(),
-        );
-        state.planet_placed = true;
+/// Schedule: Update Bevy System
+///
+/// Fills the entropy bar with the universe's progress toward heat death (see entropy.rs).
+/// Prior to launch the EntropyMeter follows the universe so that its baseline is the
+/// launched universe; the bar is full exactly when check_for_gameover ends the game
+fn update_entropy_meter(
+    universe: Universe,
+    mut meter: ResMut<EntropyMeter>,
+    mut bar_query: Query<&mut Node, With<EntropyBar>>,
+) {
+    let snapshot = universe.snapshot();
+
+    if !universe.started() {
+        meter.baseline(&snapshot);
+    }
+
+    let fill = meter.fill(&snapshot);
+    for mut node in &mut bar_query {
+        node.width = percent(100.0 * fill);
     }
 }
 
 /// Schedule: Update Bevy System
 ///
-/// This sytem captures the dragging motion after the initial click to
-/// place the planet in the universe. The drag motion will change the velocity
-/// such that the planet's heading will follow the arrow drawn from it's starting
-/// location to the mouse's released location with the addition that the magnitude
-/// of that distance is translated into velocity (kinda like strecthing a rubberband)
-fn planet_dragged(
-    drag: On<Pointer<Drag>>,
+/// Shows the final merge notice while exactly two objects remain in a running universe
+/// and they are about to collide (see fn final_merge_imminent); a stable two-body orbit
+/// keeps it hidden
+fn update_final_merge_notice(
     state: Res<GameState>,
-    mut planet_query: Query<&mut Movable, With<ThePlanet>>,
+    objects: Query<&Movable>,
+    layout: Res<UniverseLayout>,
+    mut notice_query: Query<&mut Visibility, With<FinalMergeNotice>>,
 ) {
-    if state.game_started || planet_query.iter().len() == 0 {
-        return;
+    let imminent = final_merge_pending(&state, &objects, &layout.universe);
+
+    for mut visibility in &mut notice_query {
+        *visibility = if imminent {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
     }
+}
 
-    let mut planet = planet_query.single_mut().unwrap();
-    planet.velocity.vx += drag.delta.x * 10.0; //arb scaling that feels good
-    planet.velocity.vy += -drag.delta.y * 10.0;
+/// not called directly from a system/event loop but is instead a helper function
+/// which returns true while exactly two objects remain in a running universe and they
+/// are about to collide in universe (see fn final_merge_imminent)
+fn final_merge_pending(
+    state: &GameState,
+    objects: &Query<&Movable>,
+    universe: &UniverseConfig,
+) -> bool {
+    let mut remaining = objects.iter();
+    match (remaining.next(), remaining.next(), remaining.next()) {
+        (Some(one), Some(two), None) => {
+            state.game_started && state.game_alive && final_merge_imminent(one, two, universe)
+        }
+        _ => false,
+    }
 }
 
-/// Scheudle: Update Bevy System
+/// Schedule: Update Bevy System
 ///
-/// Initiates the start of the game / simulation. Requires that the user has placed
-/// the planet on the board. Updates the GameState resource to indicate the simluation
-/// has begun and sets the reference start time for the clocks
-fn check_for_start(_trigger: On<Pointer<Release>>, mut state: ResMut<GameState>, time: Res<Time>) {
-    if state.game_started || !state.planet_placed {
-        return;
+/// Flips BulletTime on and off with the `B` key and, while it is on, slows the virtual
+/// clock down to BULLET_TIME_SCALE as the final merge nears (see fn final_merge_pending),
+/// restoring normal speed once the merge has resolved. The ramp follows the real clock
+/// since the virtual one is the clock being slowed. The clock runs at the settings
+/// panel's TimeScale on top of it
+#[allow(clippy::too_many_arguments)]
+fn update_bullet_time(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<GameState>,
+    objects: Query<&Movable>,
+    layout: Res<UniverseLayout>,
+    real: Res<Time<Real>>,
+    mut bullet: ResMut<BulletTime>,
+    time_scale: Res<TimeScale>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if keys.just_pressed(KeyCode::KeyB) {
+        bullet.enabled = !bullet.enabled;
     }
 
-    state.game_started = true;
-    state.start_time = time.elapsed_secs_f64();
+    let scale = time_scale.0
+        * bullet.update(
+            final_merge_pending(&state, &objects, &layout.universe),
+            real.delta_secs(),
+        );
+    if time.relative_speed() != scale {
+        time.set_relative_speed(scale);
+    }
 }
 
 /// Schedule: Update Bevy System
 ///
-/// Checks of end of game logic which occurs if only a single black hole remains (maximal
-/// entropy in this universe). In this event, both clocks are guaranteed to stop and the
-/// frame updating will also yeild.
-///
-/// This System also updates the black hole and planet counter Text graphics
-fn check_for_gameover(
-    objects: Query<(Entity, &Movable), With<Movable>>,
-    mut bh_count_label: Query<&mut Text, (With<BHCounter>, Without<WorldCounter>)>,
-    mut world_count_label: Query<&mut Text, (With<WorldCounter>, Without<BHCounter>)>,
-    mut state: ResMut<GameState>,
-    mut interaction_query: Query<&mut Visibility, With<GameOverBtn>>,
+/// Refreshes the time since the last merge while the game is running and shows the
+/// stable system notice once SimStats considers the run settled. This is only a
+/// notice: the simulation carries on until check_for_gameover ends it
+fn update_merge_stats(
+    time: Res<Time<Fixed>>,
+    state: Res<GameState>,
+    stats: Res<SimStats>,
+    mut clock_query: Query<&mut Text, With<LastMergeClock>>,
+    mut notice_query: Query<&mut Visibility, With<StableNotice>>,
 ) {
-    let mut bh_count: usize = 0;
-    let mut planet_count: usize = 0;
+    let now = time.elapsed_secs_f64();
 
-    for (_, movable) in objects {
-        match movable.otype {
-            ObjectType::BlackHole => bh_count += 1,
-            ObjectType::World => planet_count += 1,
+    if !state.game_started {
+        for mut clock in &mut clock_query {
+            **clock = String::from("0.00");
+        }
+    } else if state.game_alive {
+        for mut clock in &mut clock_query {
+            **clock = format!("{:.2}", stats.since_last_merge(now, state.start_time));
         }
     }
 
-    if state.game_started {
-        if planet_count == 0 {
-            state.world_alive = false;
-        }
-        if bh_count + planet_count <= 1 {
-            state.game_alive = false;
-        }
+    let settled = state.game_started && state.game_alive && stats.settled(now, state.start_time);
+    for mut visibility in &mut notice_query {
+        *visibility = if settled {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
     }
+}
 
-    //&Text -> Text -> String
-    **bh_count_label.single_mut().unwrap() = format!("{}", bh_count);
-    **world_count_label.single_mut().unwrap() = format!("{}", planet_count);
+/// Schedule: Update Bevy System
+///
+/// Keeps the last PlanetConsumed of the run in PlanetFate and, once the game is over,
+/// shows the planet's fate: how long it survived and what consumed it. Prior to launch
+/// (including after a restart) the fate is cleared and hidden
+fn update_planet_fate(
+    state: Res<GameState>,
+    mut consumed: MessageReader<PlanetConsumed>,
+    mut fate: ResMut<PlanetFate>,
+    mut label_query: Query<(&mut Text, &mut Visibility), With<PlanetFateLabel>>,
+) {
+    if let Some(last) = consumed.read().last() {
+        fate.0 = Some(*last);
+    }
+    if !state.game_started {
+        fate.0 = None;
+    }
 
-    //show the restart button
-    if state.game_started && !state.game_alive {
-        let mut visibility = interaction_query.single_mut().unwrap();
-        *visibility = Visibility::Visible;
+    let over = state.game_started && !state.game_alive;
+    for (mut label, mut visibility) in &mut label_query {
+        if over {
+            **label = fate.describe(state.world_alive);
+            *visibility = Visibility::Visible;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+/// Schedule: Update Bevy System
+///
+/// Logs the MergeHistory of the run once the game is over (see the GameOver message),
+/// from which the merge tree of the final black hole can be reconstructed
+fn log_merge_history(mut game_over: MessageReader<GameOver>, history: Res<MergeHistory>) {
+    for over in game_over.read() {
+        info!(
+            "merge history of a {:.2}s run (final objects {:?}):\n{}",
+            over.survival_time,
+            history.finals(),
+            history.dump()
+        );
     }
 }
 
 /// Schedule: Update Bevy System
 ///
-/// Implements the logic for the restart simulation button. The buttons visiblity will only be set to Visible
-/// when the gameover state has been achieved (see fn check_for_gameover). Functionality include here:
+/// Implements the logic for the game over (Retry and New Universe) buttons. The buttons visiblity will only
+/// be set to Visible when the gameover state has been achieved (see fn update_counters). Functionality include here:
 /// 1. Check for button visiblity (meaning game over state achieved)
 /// 2. Change button background during hover, hover leave, and button press
-/// 3. Set the GameState resource flag restart_clicked to true if button is pressed
+/// 3. Set the GameState resource restart_clicked to the button's RestartChoice if it is pressed
 ///    (this signals the wipe and rebuild of the universe; see fn check_for_restart)
 fn button_system(
     mut input_focus: ResMut<InputFocus>,
     mut interaction_query: Query<
-        (Entity, &Interaction, &mut BackgroundColor, &mut Visibility),
-        Changed<Interaction>,
+        (
+            Entity,
+            &Interaction,
+            &mut BackgroundColor,
+            &mut Visibility,
+            &RestartChoice,
+        ),
+        (Changed<Interaction>, With<GameOverBtn>),
     >,
     mut state: ResMut<GameState>,
+    theme: Res<Theme>,
 ) {
-    for (entity, interaction, mut background_color, visibility) in &mut interaction_query {
+    for (entity, interaction, mut background_color, visibility, choice) in &mut interaction_query {
         if *visibility != Visibility::Visible {
             continue;
         }
@@ -977,6 +3553,7 @@ fn button_system(
                     entity,
                     &mut input_focus,
                     &mut background_color,
+                    &theme,
                     BtnState::None,
                 );
             }
@@ -985,6 +3562,7 @@ fn button_system(
                     entity,
                     &mut input_focus,
                     &mut background_color,
+                    &theme,
                     BtnState::Hovered,
                 );
             }
@@ -993,16 +3571,18 @@ fn button_system(
                     entity,
                     &mut input_focus,
                     &mut background_color,
+                    &theme,
                     BtnState::Pressed,
                 );
 
-                state.restart_clicked = true;
+                state.restart_clicked = Some(*choice);
 
                 // reset btn look for new time
                 update_btn(
                     entity,
                     &mut input_focus,
                     &mut background_color,
+                    &theme,
                     BtnState::None,
                 );
             }
@@ -1012,30 +3592,86 @@ fn button_system(
 
 /// Schedule: Update Bevy System
 ///
-/// Handles reseting the playing field after the Restart button has been pressed by the user.
-/// 1. Checks for the GameState::restart_clicked flag and if set,
-/// 2. Destroys all Entities that remain which as Movable
-/// 3. Triggers the fn setup_objects to rebuild the starting map
-/// 4. Resets the GameState
-/// 5. Hides the button until next GameOver state has been reached
+/// Handles reseting the playing field after a game over button has been pressed by the user.
+/// 1. Checks for the GameState::restart_clicked choice and if set,
+/// 2. Picks the universe to rebuild: RestartChoice::Same rebuilds the last universe as it
+///    started (the seed and settings it was generated from, or the objects it was placed
+///    with, see UniverseLayout::again), RestartChoice::New rerolls the seed and uses the
+///    current settings
+/// 3. Hands it to fn apply_new_game, which wipes and rebuilds the playing field
 fn check_for_restart(
     mut state: ResMut<GameState>,
-    mut commands: Commands,
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<ColorMaterial>>,
+    mut new_game: MessageWriter<NewGameConfig>,
     sliders: Query<(&SliderValue, &SliderType)>,
-    objects: Query<Entity, With<Movable>>,
-    mut interaction_query: Query<&mut Visibility, With<GameOverBtn>>,
+    pattern: Res<VelocityPattern>,
+    clusters: Res<ClusterCount>,
+    mut layout: ResMut<UniverseLayout>,
 ) {
-    if state.restart_clicked {
-        for entity in &objects {
-            destroy_object(&mut commands, entity);
+    let Some(choice) = state.restart_clicked.take() else {
+        return;
+    };
+
+    let again = match choice {
+        RestartChoice::Same => layout.again(),
+        RestartChoice::New => {
+            layout.reseed();
+            None
         }
+    };
+
+    //nothing has been generated yet to retry: fall back to the current settings
+    new_game.write(again.unwrap_or_else(|| NewGameConfig::Generated {
+        seed: layout.seed,
+        settings: UniverseSettings::from_sliders(&sliders, *pattern, *clusters),
+        scatters: layout.scatters().to_vec(),
+    }));
+}
 
-        setup_objects(commands, meshes, materials, sliders);
-        state.reset();
+/// fn test_slider_edits_keep_sizes ensures that after several Mass (and Count) slider
+/// edits every black hole on the field is drawn at scale 1.0 with a mesh of exactly its
+/// size.radius, the radius its collisions use, so equal masses look and collide alike
+#[test]
+fn test_slider_edits_keep_sizes() {
+    use crate::objects::headless::HeadlessUniverse;
+
+    let mut universe = HeadlessUniverse::new(PhysicsPlugin::default());
+    let app = &mut universe.app;
+    app.init_resource::<ClusterCount>()
+        .add_systems(Update, update_slider_results);
+    let mass = app
+        .world_mut()
+        .spawn((SliderValue::default(), SliderType::Mass))
+        .id();
+    let count = app
+        .world_mut()
+        .spawn((SliderValue::default(), SliderType::Count))
+        .id();
 
-        let mut visibility = interaction_query.single_mut().unwrap();
-        *visibility = Visibility::Hidden;
+    for (mass_value, count_value) in [(0.9, 0.5), (0.2, 0.5), (0.7, 0.8), (0.4, 1.0)] {
+        let world = app.world_mut();
+        world.get_mut::<SliderValue>(mass).unwrap().value = mass_value;
+        world.get_mut::<SliderValue>(count).unwrap().value = count_value;
+        app.update();
+
+        let world = app.world_mut();
+        let mut black_holes = world.query::<(&Movable, &Transform, &Mesh2d)>();
+        let meshes = world.resource::<Assets<Mesh>>();
+        let mut drawn = 0;
+        for (black_hole, transform, mesh) in black_holes.iter(world) {
+            assert_eq!(transform.scale, Vec3::ONE);
+
+            //every rim vertex of the circle mesh lies at the drawn radius
+            let rim = meshes
+                .get(&mesh.0)
+                .and_then(|mesh| mesh.attribute(Mesh::ATTRIBUTE_POSITION))
+                .and_then(|positions| positions.as_float3())
+                .unwrap()
+                .iter()
+                .map(|[x, y, _]| x.hypot(*y))
+                .fold(0.0, f32::max);
+            assert!((rim - black_hole.size.radius).abs() <= 1e-4 * black_hole.size.radius);
+            drawn += 1;
+        }
+        assert!(drawn > 0);
     }
 }
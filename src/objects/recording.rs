@@ -0,0 +1,373 @@
+//! Recording.rs
+//!
+//! The recording module stores everything needed to reproduce a run exactly:
+//! the universe seed and size, the slider settings, the scattered clusters, the black hole
+//! threshold, the frame of reference, the gravity settings, the planet's shield and its
+//! launch. Because the universe is generated from the seed (see universe.rs) and the
+//! physics run on a fixed timestep, replaying a Recording reproduces the same merges and
+//! the same survival time.
+//!
+//! Recordings are saved as small plain text files of `key value(s)` lines, with one
+//! `scatter` line per scattered cluster
+
+use crate::objects::gamestate::UniverseConfig;
+use crate::objects::movables::{GravityMatrix, IntegratorMode};
+use crate::objects::physics::ReferenceFrame;
+use crate::objects::universe::{ClusterCount, Scatter, UniverseSettings, VelocityPattern};
+use bevy::prelude::*;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+/// Recording struct
+///
+/// - seed = the UniverseLayout seed the black holes were generated from
+/// - universe = the size of the field the black holes were generated onto. Unlike the
+///   rest it shapes the camera and the field spawned at startup, so it is applied to the
+///   plugin itself (see main.rs and `BlackHoleUniverse::with_universe_size`)
+/// - settings = the slider settings the black holes were generated from
+/// - scatters = the clusters scattered on top of them, in order (see UniverseLayout::scatter)
+/// - bh_threshold = the BlackHoleThreshold in effect
+/// - frame = the ReferenceFrame the run was simulated in
+/// - softening = the PhysicsConfig softening the run was simulated with
+/// - gravity_constant = the PhysicsConfig gravity_constant the run was simulated with
+/// - integrator = the PhysicsConfig IntegratorMode the run was simulated with
+/// - gravity_matrix = which object types attracted each other (see GravityMatrix), e.g.
+///   the planet gravity of the settings panel or `BlackHoleUniverse::with_gravity`
+/// - shield_hits = the ShieldHits of the planet's Shield, 0 for none
+/// - planet_position = where the planet was placed, in World coordinates
+/// - planet_velocity = the planet's velocity at launch
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    pub seed: u64,
+    pub universe: UniverseConfig,
+    pub settings: UniverseSettings,
+    pub scatters: Vec<Scatter>,
+    pub bh_threshold: f32,
+    pub frame: ReferenceFrame,
+    pub softening: f32,
+    pub gravity_constant: f32,
+    pub integrator: IntegratorMode,
+    pub gravity_matrix: GravityMatrix,
+    pub shield_hits: u32,
+    pub planet_position: Vec2,
+    pub planet_velocity: Vec2,
+}
+
+impl Recording {
+    /// fn to_text
+    ///
+    /// serializes the recording. f32 values are written with Rust's shortest
+    /// round-trip formatting so that parsing them back is exact
+    pub fn to_text(&self) -> String {
+        let scatters: String = self
+            .scatters
+            .iter()
+            .map(|scatter| {
+                format!(
+                    "scatter {} {} {}\n",
+                    scatter.position.x, scatter.position.y, scatter.seed
+                )
+            })
+            .collect();
+
+        format!(
+            "seed {}\nuniverse {} {}\ncount {}\nmass {}\nvelocity {}\ndensity {}\npattern {}\nclusters {}\n{}threshold {}\nframe {}\nsoftening {}\ngravity {}\nintegrator {}\nattracts {} {} {} {}\nshield {}\nplanet_position {} {}\nplanet_velocity {} {}\n",
+            self.seed,
+            self.universe.width,
+            self.universe.height,
+            self.settings.count,
+            self.settings.mass,
+            self.settings.velocity,
+            self.settings.density,
+            self.settings.pattern.name(),
+            self.settings.clusters.0,
+            scatters,
+            self.bh_threshold,
+            self.frame.name(),
+            self.softening,
+            self.gravity_constant,
+            self.integrator.name(),
+            self.gravity_matrix.black_hole_on_black_hole,
+            self.gravity_matrix.black_hole_on_planet,
+            self.gravity_matrix.planet_on_black_hole,
+            self.gravity_matrix.planet_on_planet,
+            self.shield_hits,
+            self.planet_position.x,
+            self.planet_position.y,
+            self.planet_velocity.x,
+            self.planet_velocity.y,
+        )
+    }
+
+    /// fn parse
+    ///
+    /// inverse of fn to_text. Every key but `scatter`, which may appear any number of
+    /// times, must be present; unknown keys are an error
+    pub fn parse(text: &str) -> Result<Recording> {
+        let mut seed = None;
+        let mut universe = None;
+        let mut count = None;
+        let mut mass = None;
+        let mut velocity = None;
+        let mut density = None;
+        let mut pattern = None;
+        let mut clusters = None;
+        let mut scatters = Vec::new();
+        let mut threshold = None;
+        let mut frame = None;
+        let mut softening = None;
+        let mut gravity = None;
+        let mut integrator = None;
+        let mut attracts = None;
+        let mut shield = None;
+        let mut planet_position = None;
+        let mut planet_velocity = None;
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let mut words = line.split_whitespace();
+            let key = words.next().unwrap_or_default();
+            let values: Vec<&str> = words.collect();
+
+            match key {
+                "seed" => seed = Some(parse_value::<u64>(key, &values)?),
+                "universe" => universe = Some(parse_universe(key, &values)?),
+                "count" => count = Some(parse_value::<f32>(key, &values)?),
+                "mass" => mass = Some(parse_value::<f32>(key, &values)?),
+                "velocity" => velocity = Some(parse_value::<f32>(key, &values)?),
+                "density" => density = Some(parse_value::<f32>(key, &values)?),
+                "pattern" => pattern = Some(parse_pattern(key, &values)?),
+                "clusters" => clusters = Some(parse_clusters(key, &values)?),
+                "scatter" => scatters.push(parse_scatter(key, &values)?),
+                "threshold" => threshold = Some(parse_value::<f32>(key, &values)?),
+                "frame" => frame = Some(parse_frame(key, &values)?),
+                "softening" => softening = Some(parse_value::<f32>(key, &values)?),
+                "gravity" => gravity = Some(parse_value::<f32>(key, &values)?),
+                "integrator" => integrator = Some(parse_integrator(key, &values)?),
+                "attracts" => attracts = Some(parse_gravity_matrix(key, &values)?),
+                "shield" => shield = Some(parse_value::<u32>(key, &values)?),
+                "planet_position" => planet_position = Some(parse_vec2(key, &values)?),
+                "planet_velocity" => planet_velocity = Some(parse_vec2(key, &values)?),
+                _ => return Err(invalid(format!("unknown key `{key}`"))),
+            }
+        }
+
+        Ok(Recording {
+            seed: seed.ok_or_else(|| missing("seed"))?,
+            universe: universe.ok_or_else(|| missing("universe"))?,
+            settings: UniverseSettings {
+                count: count.ok_or_else(|| missing("count"))?,
+                mass: mass.ok_or_else(|| missing("mass"))?,
+                velocity: velocity.ok_or_else(|| missing("velocity"))?,
+                density: density.ok_or_else(|| missing("density"))?,
+                pattern: pattern.ok_or_else(|| missing("pattern"))?,
+                clusters: clusters.ok_or_else(|| missing("clusters"))?,
+            },
+            scatters,
+            bh_threshold: threshold.ok_or_else(|| missing("threshold"))?,
+            frame: frame.ok_or_else(|| missing("frame"))?,
+            softening: softening.ok_or_else(|| missing("softening"))?,
+            gravity_constant: gravity.ok_or_else(|| missing("gravity"))?,
+            integrator: integrator.ok_or_else(|| missing("integrator"))?,
+            gravity_matrix: attracts.ok_or_else(|| missing("attracts"))?,
+            shield_hits: shield.ok_or_else(|| missing("shield"))?,
+            planet_position: planet_position.ok_or_else(|| missing("planet_position"))?,
+            planet_velocity: planet_velocity.ok_or_else(|| missing("planet_velocity"))?,
+        })
+    }
+
+    /// fn save writes the recording to the file at path
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// fn load reads a recording from the file at path
+    pub fn load(path: impl AsRef<Path>) -> Result<Recording> {
+        Recording::parse(&fs::read_to_string(path)?)
+    }
+
+    /// fn file_name is the default file name a recording is saved to
+    pub fn file_name(&self) -> String {
+        format!("universe-{}.rec", self.seed)
+    }
+}
+
+/// Replay struct: Resource
+///
+/// Holds the recording to be replayed at startup, None for a normal game.
+/// The recording is taken (set back to None) once the planet has been launched
+#[derive(Resource, Default)]
+pub struct Replay(pub Option<Recording>);
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn missing(key: &str) -> Error {
+    invalid(format!("missing key `{key}`"))
+}
+
+fn parse_value<T: std::str::FromStr>(key: &str, values: &[&str]) -> Result<T> {
+    match values {
+        [value] => value
+            .parse::<T>()
+            .map_err(|_| invalid(format!("invalid value for `{key}`: `{value}`"))),
+        _ => Err(invalid(format!("`{key}` expects 1 value"))),
+    }
+}
+
+fn parse_pattern(key: &str, values: &[&str]) -> Result<VelocityPattern> {
+    let name = parse_value::<String>(key, values)?;
+    VelocityPattern::from_name(&name)
+        .ok_or_else(|| invalid(format!("invalid value for `{key}`: `{name}`")))
+}
+
+fn parse_universe(key: &str, values: &[&str]) -> Result<UniverseConfig> {
+    let size = parse_vec2(key, values)?;
+    if size.x > 0.0 && size.y > 0.0 {
+        Ok(UniverseConfig {
+            width: size.x,
+            height: size.y,
+        })
+    } else {
+        Err(invalid(format!("invalid value for `{key}`: `{size}`")))
+    }
+}
+
+fn parse_frame(key: &str, values: &[&str]) -> Result<ReferenceFrame> {
+    let name = parse_value::<String>(key, values)?;
+    ReferenceFrame::from_name(&name)
+        .ok_or_else(|| invalid(format!("invalid value for `{key}`: `{name}`")))
+}
+
+fn parse_integrator(key: &str, values: &[&str]) -> Result<IntegratorMode> {
+    let name = parse_value::<String>(key, values)?;
+    IntegratorMode::from_name(&name)
+        .ok_or_else(|| invalid(format!("invalid value for `{key}`: `{name}`")))
+}
+
+/// keyed `attracts`, the four flags of the GravityMatrix in field order
+fn parse_gravity_matrix(key: &str, values: &[&str]) -> Result<GravityMatrix> {
+    match values {
+        [bh_bh, bh_planet, planet_bh, planet_planet] => Ok(GravityMatrix {
+            black_hole_on_black_hole: parse_value::<bool>(key, &[*bh_bh])?,
+            black_hole_on_planet: parse_value::<bool>(key, &[*bh_planet])?,
+            planet_on_black_hole: parse_value::<bool>(key, &[*planet_bh])?,
+            planet_on_planet: parse_value::<bool>(key, &[*planet_planet])?,
+        }),
+        _ => Err(invalid(format!("`{key}` expects 4 values"))),
+    }
+}
+
+fn parse_clusters(key: &str, values: &[&str]) -> Result<ClusterCount> {
+    let count = parse_value::<u8>(key, values)?;
+    if (1..=ClusterCount::MAX_CLUSTERS).contains(&count) {
+        Ok(ClusterCount(count))
+    } else {
+        Err(invalid(format!("invalid value for `{key}`: `{count}`")))
+    }
+}
+
+/// keyed `scatter`, the click position then the seed of one cluster
+fn parse_scatter(key: &str, values: &[&str]) -> Result<Scatter> {
+    match values {
+        [x, y, seed] => Ok(Scatter {
+            position: parse_vec2(key, &[*x, *y])?,
+            seed: parse_value::<u64>(key, &[*seed])?,
+        }),
+        _ => Err(invalid(format!("`{key}` expects 3 values"))),
+    }
+}
+
+fn parse_vec2(key: &str, values: &[&str]) -> Result<Vec2> {
+    match values {
+        [x, y] => Ok(Vec2::new(
+            parse_value::<f32>(key, &[*x])?,
+            parse_value::<f32>(key, &[*y])?,
+        )),
+        _ => Err(invalid(format!("`{key}` expects 2 values"))),
+    }
+}
+
+/// fn test_round_trip ensures a recording survives being written and parsed
+/// bit for bit, and that malformed recordings are rejected
+#[test]
+fn test_round_trip() {
+    let recording = Recording {
+        seed: u64::MAX - 3,
+        universe: UniverseConfig {
+            width: 40_000.0,
+            height: 25_000.0 / 3.0,
+        },
+        settings: UniverseSettings {
+            count: 0.1,
+            mass: 1.0 / 3.0,
+            velocity: 0.75,
+            density: 0.0,
+            pattern: VelocityPattern::Tangential,
+            clusters: ClusterCount(2),
+        },
+        scatters: vec![
+            Scatter {
+                position: Vec2::new(-4_000.5, 1.0 / 3.0),
+                seed: u64::MAX,
+            },
+            Scatter {
+                position: Vec2::new(250.0, -9_999.9),
+                seed: 0,
+            },
+        ],
+        bh_threshold: 12.5,
+        frame: ReferenceFrame::CenterOfMass,
+        softening: 1000.0 / 7.0,
+        gravity_constant: 2.0E8 / 3.0,
+        integrator: IntegratorMode::Verlet,
+        gravity_matrix: GravityMatrix {
+            black_hole_on_planet: false,
+            planet_on_planet: false,
+            ..GravityMatrix::default()
+        },
+        shield_hits: 3,
+        planet_position: Vec2::new(-1234.567, 0.1 + 0.2),
+        planet_velocity: Vec2::new(f32::MIN_POSITIVE, -987.654_3),
+    };
+
+    assert_eq!(Recording::parse(&recording.to_text()).unwrap(), recording);
+
+    assert!(Recording::parse("seed 1\n").is_err());
+    assert!(
+        Recording::parse(&recording.to_text().replace("universe 40000", "universe -1")).is_err()
+    );
+    assert!(Recording::parse(&recording.to_text().replace("clusters 2", "clusters 0")).is_err());
+    assert!(Recording::parse(&recording.to_text().replace("seed", "sed")).is_err());
+    assert!(Recording::parse(&recording.to_text().replace("CenterOfMass", "Galactic")).is_err());
+    assert!(Recording::parse(&recording.to_text().replace("Verlet", "RungeKutta")).is_err());
+    assert!(
+        Recording::parse(
+            &recording
+                .to_text()
+                .replace("attracts true false", "attracts yes false")
+        )
+        .is_err()
+    );
+    assert!(Recording::parse(&recording.to_text().replace("true false true", "true")).is_err());
+    assert!(
+        Recording::parse(&recording.to_text().replace(" 0\nthreshold", "\nthreshold")).is_err()
+    );
+
+    //planet gravity off, as the settings panel switches it
+    let no_planet_gravity = Recording {
+        gravity_matrix: GravityMatrix {
+            planet_on_black_hole: false,
+            planet_on_planet: false,
+            ..GravityMatrix::default()
+        },
+        scatters: Vec::new(),
+        ..recording.clone()
+    };
+    assert_eq!(
+        Recording::parse(&no_planet_gravity.to_text()).unwrap(),
+        no_planet_gravity
+    );
+    assert!(Recording::parse(&recording.to_text().replace("planet_velocity", "mass")).is_err());
+}
@@ -0,0 +1,181 @@
+//! Settings.rs
+//!
+//! The settings module defines the in-game settings panel: an overlay toggled with the
+//! `Escape` key which pauses the simulation while open and gathers the runtime toggles
+//! (each also bound to its own key), the physics constants and the time scale in one
+//! place. Input to the universe behind the panel is ignored while it is open. The panel
+//! is spawned and driven by plugins.rs: the toggles are game over style buttons (see
+//! button.rs) and the constants are slider bars (see sliders.rs)
+
+use crate::objects::sliders::Range;
+use bevy::prelude::*;
+
+/// Softening Allowed Range:
+/// Slider.min = lower, Slider.max = upper. The slider is logarithmic: the default
+/// softening (see PhysicsConfig) sits at the middle of the bar
+pub const SOFTENING_RNG: Range<f32> = Range {
+    lower: 10.0,
+    upper: 100_000.0,
+};
+
+/// fn softening_from_slider returns the softening represented by the Softening
+/// slider's value [0-1], logarithmically between SOFTENING_RNG.lower and upper
+pub fn softening_from_slider(value: f32) -> f32 {
+    SOFTENING_RNG.lower * (SOFTENING_RNG.upper / SOFTENING_RNG.lower).powf(value.clamp(0.0, 1.0))
+}
+
+/// fn slider_from_softening is the inverse of softening_from_slider: the slider value
+/// [0-1] of a softening, clamped to the ends of the bar outside of SOFTENING_RNG
+pub fn slider_from_softening(softening: f32) -> f32 {
+    ((softening / SOFTENING_RNG.lower).ln() / (SOFTENING_RNG.upper / SOFTENING_RNG.lower).ln())
+        .clamp(0.0, 1.0)
+}
+
+/// Time Scale Allowed Range:
+/// Slider.min = lower, Slider.max = upper. The slider is logarithmic: normal speed
+/// (see TimeScale) sits at the middle of the bar
+pub const TIME_SCALE_RNG: Range<f32> = Range {
+    lower: 0.25,
+    upper: 4.0,
+};
+
+/// fn time_scale_from_slider returns the time scale represented by the Time Scale
+/// slider's value [0-1], logarithmically between TIME_SCALE_RNG.lower and upper
+pub fn time_scale_from_slider(value: f32) -> f32 {
+    TIME_SCALE_RNG.lower * (TIME_SCALE_RNG.upper / TIME_SCALE_RNG.lower).powf(value.clamp(0.0, 1.0))
+}
+
+/// fn slider_from_time_scale is the inverse of time_scale_from_slider: the slider value
+/// [0-1] of a time scale, clamped to the ends of the bar outside of TIME_SCALE_RNG
+pub fn slider_from_time_scale(scale: f32) -> f32 {
+    ((scale / TIME_SCALE_RNG.lower).ln() / (TIME_SCALE_RNG.upper / TIME_SCALE_RNG.lower).ln())
+        .clamp(0.0, 1.0)
+}
+
+/// TimeScale struct: Resource
+///
+/// The relative speed of the virtual clock chosen on the settings panel, 1.0 by default.
+/// The virtual clock drives the fixed physics clock, so the universe plays faster or
+/// slower while every physics step stays the same: a run and its replay still agree.
+/// BulletTime slows the clock down further on top of it
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale(1.0)
+    }
+}
+
+/// SettingsOpen struct: Resource
+///
+/// True while the settings panel is shown, during which the simulation is paused
+#[derive(Resource, Default)]
+pub struct SettingsOpen(pub bool);
+
+/// fn settings_closed is a run condition: true unless the settings panel is open. The
+/// systems handling input to the universe behind the panel only run while it holds
+pub fn settings_closed(open: Res<SettingsOpen>) -> bool {
+    !open.0
+}
+
+/// SettingsPanel struct: Component
+///
+/// Used for querying the Bevy Node of the settings overlay
+#[derive(Component)]
+pub struct SettingsPanel;
+
+/// Setting enum: Component
+///
+/// The runtime toggle flipped by a settings panel button, with its own key binding
+/// (Theme instead cycles through the presets)
+/// 1. Sandbox - SandboxMode (`S`)
+/// 2. Lensing - the lensing halos (`L`)
+/// 3. InfluenceRings - the planet's capture rings (`I`)
+/// 4. CenterOfMass - the CenterOfMass ReferenceFrame (`F`)
+/// 5. PlanetGravity - whether planets attract anything, see GravityMatrix. Like the frame
+///    and the integrator it is part of a Recording, and so locked once launched
+/// 6. EdgeIndicators - the markers of the spherical wrap, see edges.rs (`E`)
+/// 7. BulletTime - the slow motion final merge, see BulletTime (`B`)
+/// 8. Verlet - the Verlet IntegratorMode instead of the default Euler one
+/// 9. Theme - the color Theme, see Theme::presets
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Sandbox,
+    Lensing,
+    InfluenceRings,
+    CenterOfMass,
+    PlanetGravity,
+    EdgeIndicators,
+    BulletTime,
+    Verlet,
+    Theme,
+}
+
+impl Setting {
+    /// every setting, in the order listed on the panel
+    pub const ALL: [Setting; 9] = [
+        Setting::Sandbox,
+        Setting::Lensing,
+        Setting::InfluenceRings,
+        Setting::CenterOfMass,
+        Setting::PlanetGravity,
+        Setting::EdgeIndicators,
+        Setting::BulletTime,
+        Setting::Verlet,
+        Setting::Theme,
+    ];
+
+    /// fn name returns the setting's display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Setting::Sandbox => "Sandbox",
+            Setting::Lensing => "Lensing",
+            Setting::InfluenceRings => "Influence Rings",
+            Setting::CenterOfMass => "Center of Mass Frame",
+            Setting::PlanetGravity => "Planet Gravity",
+            Setting::EdgeIndicators => "Edge Indicators",
+            Setting::BulletTime => "Bullet Time",
+            Setting::Verlet => "Verlet Integrator",
+            Setting::Theme => "Theme",
+        }
+    }
+
+    /// fn label returns the text of the setting's button while it is on (or off)
+    pub fn label(&self, on: bool) -> String {
+        self.choice_label(if on { "On" } else { "Off" })
+    }
+
+    /// fn choice_label returns the text of the setting's button showing choice, e.g. the
+    /// name of the current Theme
+    pub fn choice_label(&self, choice: &str) -> String {
+        format!("{}: {}", self.name(), choice)
+    }
+}
+
+/// fn test_softening_slider ensures the default softening (and normal speed) sits mid-bar
+/// and the slider mapping round trips, clamping softenings outside of SOFTENING_RNG
+#[test]
+fn test_softening_slider() {
+    use crate::objects::movables::PhysicsConfig;
+
+    let default = PhysicsConfig::default().softening;
+    assert!((slider_from_softening(default) - 0.5).abs() < 1e-6);
+    assert!((softening_from_slider(0.5) - default).abs() < 1e-2);
+    assert_eq!(softening_from_slider(0.0), SOFTENING_RNG.lower);
+
+    for value in [0.1, 0.25, 0.8, 1.0] {
+        assert!((slider_from_softening(softening_from_slider(value)) - value).abs() < 1e-5);
+    }
+    assert_eq!(slider_from_softening(1.0), 0.0);
+    assert_eq!(slider_from_softening(1.0e9), 1.0);
+    assert!((slider_from_time_scale(1.0) - 0.5).abs() < 1e-6);
+    assert!((time_scale_from_slider(0.5) - 1.0).abs() < 1e-6);
+    assert_eq!(time_scale_from_slider(1.0), TIME_SCALE_RNG.upper);
+    assert_eq!(slider_from_time_scale(100.0), 1.0);
+    assert_eq!(
+        Setting::CenterOfMass.label(true),
+        "Center of Mass Frame: On"
+    );
+    assert_eq!(Setting::Theme.choice_label("Solarized"), "Theme: Solarized");
+}
@@ -0,0 +1,139 @@
+//! Shield.rs
+//!
+//! The shield module makes the planet optionally forgiving. A shielded planet which
+//! only grazes a black hole (their hitboxes barely overlap) loses one hit of its Shield
+//! and rebounds instead of being swallowed. A direct hit, or a graze once the hits
+//! have run out, consumes the planet as usual. Configured with
+//! `BlackHoleUniverse::with_shield` and applied by the collision system in plugins.rs,
+//! which also draws the remaining hits as a ring around the planet
+
+use crate::objects::gamestate::UniverseConfig;
+use crate::objects::movables::{Movable, ObjectType};
+use crate::objects::traits::collisions::{CollisionDetection, Shapes};
+use bevy::prelude::*;
+
+/// A collision is a graze while the closest approach of the two hitboxes stays beyond
+/// this fraction of their contact distance: a shallow overlap rather than a direct hit
+pub const GRAZE_FRACTION: f32 = 0.5;
+
+/// The width of the shield ring added around the planet's hitbox per remaining hit
+pub const SHIELD_RING_SPACING: f32 = 15.0;
+
+/// ShieldHits struct: Resource
+///
+/// The hits the player's planet's Shield starts with. 0 (the default) gives it no
+/// Shield, so any contact with a black hole consumes it
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShieldHits(pub u32);
+
+/// Shield struct: Component
+///
+/// Attached to the player's planet when it is spawned, see ShieldHits
+/// - hits = the grazes the planet can still survive
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shield {
+    pub hits: u32,
+}
+
+impl Shield {
+    /// fn ring_radius returns the radius of the ring drawn around planet for the
+    /// remaining hits: it shrinks onto the hitbox as the hits run out
+    pub fn ring_radius(&self, planet: &Movable) -> f32 {
+        let Shapes::Circle(radius) = planet.get_hitbox();
+        radius + SHIELD_RING_SPACING * self.hits as f32
+    }
+}
+
+/// fn is_graze
+///
+/// returns true if one and two collided (see CollisionDetection::collided) but their
+/// closest approach stayed beyond GRAZE_FRACTION of their contact distance
+pub fn is_graze(one: &Movable, two: &Movable) -> bool {
+    let distance = one.minimum_distance(&two.get_position());
+    distance <= one.contact_distance(two) && distance > GRAZE_FRACTION * one.contact_distance(two)
+}
+
+/// fn shielded_graze
+///
+/// given a planet and every object it collided with during the frame, returns the
+/// planet rebounded off the black hole it grazed: pushed back out of contact (see
+/// Movable::clear_bubble) with its velocity relative to the black hole reflected away
+/// from it. None whenever the shield can not help: the planet hit nothing, hit a black
+/// hole directly, or hit several objects (or another planet) at once
+pub fn shielded_graze(
+    planet: &Movable,
+    colliding: &[&Movable],
+    universe: &UniverseConfig,
+) -> Option<Movable> {
+    let [black_hole] = colliding else {
+        return None;
+    };
+    if black_hole.otype != ObjectType::BlackHole || !is_graze(planet, black_hole) {
+        return None;
+    }
+
+    let (dx, dy) = planet.shortest_offset(black_hole, universe);
+    let normal = -Vec2::new(dx, dy).normalize_or(Vec2::NEG_X);
+    let relative = Vec2::new(
+        planet.velocity.vx - black_hole.velocity.vx,
+        planet.velocity.vy - black_hole.velocity.vy,
+    );
+    let approach = relative.dot(normal);
+    let velocity = if approach < 0.0 {
+        Vec2::new(planet.velocity.vx, planet.velocity.vy) - 2.0 * approach * normal
+    } else {
+        Vec2::new(planet.velocity.vx, planet.velocity.vy)
+    };
+
+    let mut rebound = planet.build();
+    rebound.set_velocity(velocity.x, velocity.y);
+    rebound.clear_bubble(black_hole, 1.0, universe);
+    Some(rebound)
+}
+
+/// fn test_shielded_graze ensures a planet skimming a black hole is rebounded out of
+/// contact and away from it, while a direct hit, a crowded collision and a planet
+/// which hit nothing are left to the usual collision
+#[test]
+fn test_shielded_graze() {
+    let universe = UniverseConfig::default();
+    let planet_at = |x: f32, y: f32, vx: f32| {
+        let mut planet = Movable::new(&ObjectType::World)
+            .set_position(x, y)
+            .set_size(0.0, 50.0)
+            .build();
+        //moving along +x over the last frame
+        planet.set_velocity(vx, 0.0);
+        planet.position.x_prev = x - 10.0;
+        planet
+    };
+    let black_hole = Movable::new(&ObjectType::BlackHole)
+        .set_position(0.0, 0.0)
+        .set_mass(10.0)
+        .build();
+    let contact = planet_at(0.0, 0.0, 0.0).contact_distance(&black_hole);
+
+    //skimming past below the black hole, just inside the contact distance
+    let grazing = planet_at(0.0, -0.9 * contact, 300.0);
+    assert!(is_graze(&grazing, &black_hole));
+    let rebound =
+        shielded_graze(&grazing, &[&black_hole], &universe).expect("a graze is survivable");
+    assert!(!rebound.collided(&black_hole));
+    assert_eq!(rebound.get_id(), grazing.get_id());
+    assert_eq!(rebound.velocity.vx, 300.0);
+
+    //heading into the black hole: the approach is reflected
+    let mut diving = planet_at(0.0, -0.9 * contact, 0.0);
+    diving.set_velocity(0.0, 200.0);
+    let rebound = shielded_graze(&diving, &[&black_hole], &universe).unwrap();
+    assert!(rebound.velocity.vy < 0.0);
+
+    let direct = planet_at(0.0, -0.1 * contact, 300.0);
+    assert!(!is_graze(&direct, &black_hole));
+    assert!(shielded_graze(&direct, &[&black_hole], &universe).is_none());
+    assert!(shielded_graze(&grazing, &[&black_hole, &black_hole], &universe).is_none());
+    assert!(shielded_graze(&grazing, &[], &universe).is_none());
+
+    let shield = Shield { hits: 2 };
+    assert!(shield.ring_radius(&grazing) > Shield { hits: 1 }.ring_radius(&grazing));
+}
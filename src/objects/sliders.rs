@@ -5,6 +5,7 @@
 //! also contain some constants and structs that assign bounds to each of
 //! the rendered sliders.
 
+use crate::objects::theme::Theme;
 use bevy::prelude::*;
 
 pub const SLIDERWIDTH: f32 = 100.0; //physical width of sliders, in pixels
@@ -46,6 +47,14 @@ pub const BLACKHOLE_VEL_RNG: Range<f32> = Range {
     upper: 1_000.0,
 };
 
+/// fn count_from_slider returns the number of black holes represented by the
+/// Count slider's value [0-1], never fewer than BLACKHOLE_COUNT_RNG.lower
+pub fn count_from_slider(value: f32) -> u32 {
+    (value * BLACKHOLE_COUNT_RNG.upper as f32)
+        .max(BLACKHOLE_COUNT_RNG.lower as f32)
+        .round() as u32
+}
+
 /// SliderValue struct: Component
 ///
 /// Stores the slider's current value and it's previous value.
@@ -79,6 +88,17 @@ pub enum SliderType {
     Mass,
     Density,
     Velocity,
+    Launch,
+    Softening,
+    TimeScale,
+}
+
+impl SliderType {
+    /// fn in_settings_panel is true for the sliders of the settings panel (see settings.rs)
+    /// rather than the HUD
+    pub fn in_settings_panel(&self) -> bool {
+        matches!(self, SliderType::Softening | SliderType::TimeScale)
+    }
 }
 
 /// SliderBkg Struct: Component
@@ -146,8 +166,8 @@ pub struct SliderGraphic {
 /// fn generate_slider returns a SliderGraphic Struct Bundle
 ///
 /// Convience function for prepraing the Geometry, layout, text, graphics, and color  
-/// for a slider bar. Called during `fn setup_hub` in plugins.rs
-pub fn generate_slider(stype: SliderType, text: &str) -> SliderGraphic {
+/// for a slider bar, colored by theme. Called during `fn setup_hub` in plugins.rs
+pub fn generate_slider(theme: &Theme, stype: SliderType, text: &str) -> SliderGraphic {
     let base = SliderBase {
         node: Node {
             height: px(50.0),
@@ -158,8 +178,8 @@ pub fn generate_slider(stype: SliderType, text: &str) -> SliderGraphic {
             justify_content: JustifyContent::Center,
             ..default()
         },
-        bordercolor: BorderColor::all(Color::WHITE),
-        outline: Outline::new(px(1), Val::ZERO, Color::WHITE),
+        bordercolor: BorderColor::all(theme.highlight),
+        outline: Outline::new(px(1), Val::ZERO, theme.highlight),
         bevy_identifier: stype,
     };
 
@@ -169,7 +189,7 @@ pub fn generate_slider(stype: SliderType, text: &str) -> SliderGraphic {
             font_size: 16.0,
             ..default()
         },
-        color: TextColor(Color::WHITE),
+        color: TextColor(theme.highlight),
         layout: TextLayout::new_with_justify(Justify::Center),
     };
 
@@ -182,8 +202,18 @@ pub fn generate_slider(stype: SliderType, text: &str) -> SliderGraphic {
             width: px(SLIDERWIDTH / 2.0),
             ..default()
         },
-        color: BackgroundColor(Color::linear_rgba(0.0, 0.4, 0.0, 1.0)),
+        color: BackgroundColor(theme.slider_fill),
     };
 
     SliderGraphic { base, text, bkg }
 }
+
+/// fn test_count_from_slider ensures the Count slider can never map to fewer
+/// black holes than BLACKHOLE_COUNT_RNG.lower, even at the bottom of the bar
+#[test]
+fn test_count_from_slider() {
+    assert_eq!(count_from_slider(0.0), BLACKHOLE_COUNT_RNG.lower);
+    assert_eq!(count_from_slider(0.01), BLACKHOLE_COUNT_RNG.lower);
+    assert_eq!(count_from_slider(0.5), 50);
+    assert_eq!(count_from_slider(1.0), BLACKHOLE_COUNT_RNG.upper);
+}
@@ -0,0 +1,88 @@
+//! Slingshot.rs
+//!
+//! The slingshot module detects gravitational slingshot maneuvers: the planet
+//! passing close to a black hole and leaving with significantly more speed than
+//! it arrived with. Used by plugins.rs to pop a HUD notification
+
+use bevy::prelude::*;
+
+/// A black hole is considered close to the planet if their centers are within
+/// this multiple of their combined radii
+pub const SLINGSHOT_RANGE_FACTOR: f32 = 20.0;
+
+/// The minimum speed, gained over a single close pass, that counts as a slingshot
+pub const SLINGSHOT_MIN_GAIN: f32 = 500.0;
+
+/// How long, in seconds, the slingshot notification remains on screen
+pub const SLINGSHOT_DISPLAY_SECS: f64 = 2.0;
+
+/// SlingshotNotice struct: Component
+///
+/// Used for querying the Bevy Text entity which displays the slingshot notification
+#[derive(Component)]
+pub struct SlingshotNotice;
+
+/// SlingshotTracker struct: Resource
+///
+/// Follows the planet's speed during close approaches to black holes
+/// - entry_speed = planet's speed when the current close pass began, None if not close
+/// - hide_at = elapsed seconds at which the current notification should be hidden
+#[derive(Resource, Default)]
+pub struct SlingshotTracker {
+    pub entry_speed: Option<f32>,
+    pub hide_at: f64,
+}
+
+impl SlingshotTracker {
+    /// fn update
+    ///
+    /// given the planet's current speed and whether a black hole is currently close,
+    /// returns Some(speed gained) at the end of a close pass that gained at least
+    /// SLINGSHOT_MIN_GAIN and None otherwise
+    pub fn update(&mut self, speed: f32, near: bool) -> Option<f32> {
+        match (self.entry_speed, near) {
+            (None, true) => {
+                self.entry_speed = Some(speed);
+                None
+            }
+            (Some(entry), false) => {
+                self.entry_speed = None;
+                let gain = speed - entry;
+                if gain >= SLINGSHOT_MIN_GAIN {
+                    Some(gain)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// fn reset
+    ///
+    /// forgets any close pass in progress; used when the planet is destroyed or replaced
+    pub fn reset(&mut self) {
+        self.entry_speed = None;
+    }
+}
+
+/// fn test_slingshot_tracker ensures that only a close pass which gained enough
+/// speed is reported, and only once the pass has ended
+#[test]
+fn test_slingshot_tracker() {
+    let mut tracker = SlingshotTracker::default();
+
+    //a casual drift that never comes close
+    assert_eq!(tracker.update(100.0, false), None);
+    assert_eq!(tracker.update(5_000.0, false), None);
+
+    //a close pass that gains too little
+    assert_eq!(tracker.update(1_000.0, true), None);
+    assert_eq!(tracker.update(1_200.0, true), None);
+    assert_eq!(tracker.update(1_100.0, false), None);
+
+    //a genuine slingshot
+    assert_eq!(tracker.update(1_000.0, true), None);
+    assert_eq!(tracker.update(2_500.0, true), None);
+    assert_eq!(tracker.update(2_400.0, false), Some(1_400.0));
+}
@@ -0,0 +1,150 @@
+//! Snapshot.rs
+//!
+//! The snapshot module aggregates the state of the whole universe into a single
+//! UniverseSnapshot struct: object counts, mass, center of mass, momentum and the
+//! elapsed simulation time. HUD readouts, statistics and tests all obtain the same
+//! aggregate data from here rather than each looping over the objects themselves
+
+use crate::objects::gamestate::GameState;
+use crate::objects::movables::{Movable, ObjectType};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+/// UniverseSnapshot struct
+///
+/// Summary of the universe at one moment
+/// - body_count = number of objects (black holes + planets)
+/// - black_hole_count = number of black holes
+/// - planet_count = number of planets
+/// - total_mass = summed mass of every object
+/// - center_of_mass = mass-weighted mean position (origin if massless)
+/// - total_momentum = summed mass * velocity of every object
+/// - kinetic_energy = summed 0.5 * mass * speed^2 of every object
+/// - elapsed = seconds since the simulation started, 0.0 prior to the start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UniverseSnapshot {
+    pub body_count: usize,
+    pub black_hole_count: usize,
+    pub planet_count: usize,
+    pub total_mass: f32,
+    pub center_of_mass: Vec2,
+    pub total_momentum: Vec2,
+    pub kinetic_energy: f32,
+    pub elapsed: f64,
+}
+
+impl UniverseSnapshot {
+    /// fn from_movables
+    ///
+    /// builds a snapshot from any collection of Movables and the elapsed simulation time.
+    /// This is the one place the aggregation is performed
+    pub fn from_movables<'a>(objects: impl IntoIterator<Item = &'a Movable>, elapsed: f64) -> Self {
+        let mut snapshot = UniverseSnapshot {
+            body_count: 0,
+            black_hole_count: 0,
+            planet_count: 0,
+            total_mass: 0.0,
+            center_of_mass: Vec2::ZERO,
+            total_momentum: Vec2::ZERO,
+            kinetic_energy: 0.0,
+            elapsed,
+        };
+        let mut weighted_position = Vec2::ZERO;
+
+        for movable in objects {
+            snapshot.body_count += 1;
+            match movable.otype {
+                ObjectType::BlackHole => snapshot.black_hole_count += 1,
+                ObjectType::World => snapshot.planet_count += 1,
+            }
+
+            let mass = movable.size.mass;
+            snapshot.total_mass += mass;
+            weighted_position += mass * Vec2::new(movable.position.x, movable.position.y);
+            let velocity = Vec2::new(movable.velocity.vx, movable.velocity.vy);
+            snapshot.total_momentum += mass * velocity;
+            snapshot.kinetic_energy += 0.5 * mass * velocity.length_squared();
+        }
+
+        if snapshot.total_mass > 0.0 {
+            snapshot.center_of_mass = weighted_position / snapshot.total_mass;
+        }
+
+        snapshot
+    }
+
+    /// fn capture
+    ///
+    /// builds a snapshot for use inside a Bevy system: the elapsed time is measured
+    /// from GameState::start_time, on the fixed physics clock, once the game has started
+    pub fn capture<'a>(
+        objects: impl IntoIterator<Item = &'a Movable>,
+        state: &GameState,
+        time: &Time<Fixed>,
+    ) -> Self {
+        let elapsed = if state.game_started {
+            time.elapsed_secs_f64() - state.start_time
+        } else {
+            0.0
+        };
+
+        UniverseSnapshot::from_movables(objects, elapsed)
+    }
+}
+
+/// Universe struct: SystemParam
+///
+/// Add `universe: Universe` to a system's parameters and call `universe.snapshot()`
+/// to obtain the current UniverseSnapshot. Systems which already hold a mutable
+/// GameState must call UniverseSnapshot::capture directly instead
+#[derive(SystemParam)]
+pub struct Universe<'w, 's> {
+    objects: Query<'w, 's, &'static Movable>,
+    state: Res<'w, GameState>,
+    time: Res<'w, Time<Fixed>>,
+}
+
+impl Universe<'_, '_> {
+    /// fn snapshot returns the UniverseSnapshot of the current frame
+    pub fn snapshot(&self) -> UniverseSnapshot {
+        UniverseSnapshot::capture(self.objects.iter(), &self.state, &self.time)
+    }
+
+    /// fn started returns whether the simulation has been launched
+    pub fn started(&self) -> bool {
+        self.state.game_started
+    }
+}
+
+/// fn test_snapshot ensures a snapshot counts, weighs and sums a small
+/// universe correctly without Bevy
+#[test]
+fn test_snapshot() {
+    let objects = [
+        Movable::new(&ObjectType::BlackHole)
+            .set_position(-100.0, 0.0)
+            .set_velocity(10.0, 0.0)
+            .set_mass(10.0)
+            .build(),
+        Movable::new(&ObjectType::BlackHole)
+            .set_position(100.0, 0.0)
+            .set_velocity(-10.0, 5.0)
+            .set_mass(10.0)
+            .build(),
+        Movable::new(&ObjectType::World)
+            .set_position(0.0, 500.0)
+            .set_size(0.0, 50.0)
+            .build(),
+    ];
+
+    let snapshot = UniverseSnapshot::from_movables(&objects, 1.5);
+
+    assert_eq!(snapshot.body_count, 3);
+    assert_eq!(snapshot.black_hole_count, 2);
+    assert_eq!(snapshot.planet_count, 1);
+    assert_eq!(snapshot.total_mass, 20.0);
+    assert_eq!(snapshot.center_of_mass, Vec2::ZERO);
+    assert_eq!(snapshot.total_momentum, Vec2::new(0.0, 50.0));
+    assert_eq!(snapshot.kinetic_energy, 1125.0);
+    assert_eq!(snapshot.elapsed, 1.5);
+}
@@ -0,0 +1,122 @@
+//! Stats.rs
+//!
+//! The stats module tracks running statistics of the simulation: the time of the last
+//! merge, from which a run which has settled into stable orbits (and so may never end
+//! on its own) is detected, and the peak object count of the run. Used by plugins.rs
+//! for the HUD readout and the GameOver message
+
+use bevy::prelude::*;
+
+/// The default number of seconds without a merge after which a run is considered settled
+pub const STALE_TIMEOUT_SECS: f64 = 60.0;
+
+/// LastMergeClock struct: Component
+///
+/// Used for querying the Bevy Text entity which contains the time elapsed since the
+/// last merge (or since launch, if nothing has merged yet)
+#[derive(Component)]
+pub struct LastMergeClock;
+
+/// StableNotice struct: Component
+///
+/// Used for querying the Bevy Text entity which announces that the run has settled
+#[derive(Component)]
+pub struct StableNotice;
+
+/// SimStats struct: Resource
+///
+/// - last_merge_time = the simulation time (see Time<Fixed>) of the last collision
+/// - stale_timeout = seconds without a collision after which the run is settled. None never
+///   settles. Settling only raises a notice: the simulation keeps running
+/// - peak_count = the most objects on the field at once during the run (see GameOver)
+#[derive(Resource)]
+pub struct SimStats {
+    pub last_merge_time: f64,
+    pub stale_timeout: Option<f64>,
+    pub peak_count: usize,
+}
+
+/// Default settles after STALE_TIMEOUT_SECS
+impl Default for SimStats {
+    fn default() -> Self {
+        SimStats {
+            last_merge_time: 0.0,
+            stale_timeout: Some(STALE_TIMEOUT_SECS),
+            peak_count: 0,
+        }
+    }
+}
+
+impl SimStats {
+    /// fn record_merge
+    ///
+    /// records a collision (a merge or a split) at time now
+    pub fn record_merge(&mut self, now: f64) {
+        self.last_merge_time = now;
+    }
+
+    /// fn record_count
+    ///
+    /// records count objects on the field. Prior to launch (started is false) the peak
+    /// follows the field, so the peak of a run starts from the launched universe
+    pub fn record_count(&mut self, count: usize, started: bool) {
+        self.peak_count = if started {
+            self.peak_count.max(count)
+        } else {
+            count
+        };
+    }
+
+    /// fn since_last_merge
+    ///
+    /// returns the seconds elapsed at time now since the last collision of the run
+    /// launched at start_time. A run without a collision counts from its launch, so
+    /// merges left over from a previous run are ignored
+    pub fn since_last_merge(&self, now: f64, start_time: f64) -> f64 {
+        now - self.last_merge_time.max(start_time)
+    }
+
+    /// fn settled
+    ///
+    /// returns true if the run launched at start_time has gone stale_timeout seconds
+    /// without a collision at time now
+    pub fn settled(&self, now: f64, start_time: f64) -> bool {
+        self.stale_timeout
+            .is_some_and(|timeout| self.since_last_merge(now, start_time) >= timeout)
+    }
+}
+
+/// fn test_settled ensures the clock counts from launch or the last merge, whichever is
+/// later, and that only a run without a merge for stale_timeout seconds is settled
+#[test]
+fn test_settled() {
+    let mut stats = SimStats::default();
+
+    //a merge from a previous run is ignored
+    stats.record_merge(5.0);
+    assert_eq!(stats.since_last_merge(20.0, 10.0), 10.0);
+
+    stats.record_merge(30.0);
+    assert_eq!(stats.since_last_merge(45.0, 10.0), 15.0);
+    assert!(!stats.settled(30.0 + STALE_TIMEOUT_SECS - 1.0, 10.0));
+    assert!(stats.settled(30.0 + STALE_TIMEOUT_SECS, 10.0));
+
+    stats.stale_timeout = None;
+    assert!(!stats.settled(1_000.0, 10.0));
+}
+
+/// fn test_peak_count ensures the peak follows the field prior to launch and only
+/// ever grows during the run
+#[test]
+fn test_peak_count() {
+    let mut stats = SimStats::default();
+
+    stats.record_count(40, false);
+    stats.record_count(12, false);
+    assert_eq!(stats.peak_count, 12);
+
+    stats.record_count(12, true);
+    stats.record_count(15, true);
+    stats.record_count(3, true);
+    assert_eq!(stats.peak_count, 15);
+}
@@ -0,0 +1,321 @@
+//! Theme.rs
+//!
+//! The theme module gathers the palette of the simulation in one place: the field,
+//! the objects, the HUD, the sliders and the buttons. Every spawn site in plugins.rs,
+//! sliders.rs and button.rs reads its color from the Theme resource, which is chosen
+//! at startup from one of the presets (see `BlackHoleUniverse::with_theme`)
+
+use bevy::prelude::*;
+
+/// Theme struct: Resource
+///
+/// - name = the preset's name, as accepted by Theme::named
+/// - background / border = the field of space-time and the wall around it
+/// - black_hole / planet / lens = the objects and the lensing halo (see lensing.rs)
+/// - ring = the planet's influence rings
+/// - highlight = slider borders and text, button borders and the gamepad cursor
+/// - hud_label / hud_value / hud_text = the HUD captions, readouts and informational text
+/// - hud_success / hud_alert = good news (a slingshot, a stable orbit) and warnings
+/// - meter_track = the empty part of the entropy bar
+/// - slider_fill = the filled part of a slider bar
+/// - button / button_hovered / button_pressed / button_text = the game over buttons
+///
+/// The alpha of the text colors is overridden where a readout is dimmer or brighter
+/// than the rest of the HUD, so only their hue comes from the theme
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color,
+    pub border: Color,
+    pub black_hole: Color,
+    pub planet: Color,
+    pub lens: Color,
+    pub ring: Color,
+    pub highlight: Color,
+    pub hud_label: Color,
+    pub hud_value: Color,
+    pub hud_text: Color,
+    pub hud_success: Color,
+    pub hud_alert: Color,
+    pub meter_track: Color,
+    pub slider_fill: Color,
+    pub button: Color,
+    pub button_hovered: Color,
+    pub button_pressed: Color,
+    pub button_text: Color,
+}
+
+/// Default is the Classic theme
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::classic()
+    }
+}
+
+impl Theme {
+    /// fn classic
+    ///
+    /// the original look of the simulation: grey black holes and a green planet on black
+    pub fn classic() -> Self {
+        Theme {
+            name: "Classic",
+            background: Color::linear_rgb(0.0, 0.0, 0.0),
+            border: Color::linear_rgb(0.9, 0.3, 0.3),
+            black_hole: Color::linear_rgb(0.9, 0.9, 0.9),
+            planet: Color::linear_rgb(0.0, 0.9, 0.0),
+            lens: Color::linear_rgb(0.5, 0.6, 1.0),
+            ring: Color::srgba(1.0, 0.3, 0.3, 0.35),
+            highlight: Color::WHITE,
+            hud_label: Color::linear_rgba(0.5, 0.5, 0.0, 0.5),
+            hud_value: Color::linear_rgba(1.0, 0.5, 0.0, 0.25),
+            hud_text: Color::linear_rgba(0.9, 0.9, 0.9, 0.8),
+            hud_success: Color::linear_rgba(0.0, 0.9, 0.0, 0.8),
+            hud_alert: Color::linear_rgba(0.9, 0.3, 0.3, 0.8),
+            meter_track: Color::linear_rgba(0.3, 0.3, 0.3, 0.5),
+            slider_fill: Color::linear_rgba(0.0, 0.4, 0.0, 1.0),
+            button: Color::srgb(0.15, 0.15, 0.15),
+            button_hovered: Color::srgb(0.25, 0.25, 0.25),
+            button_pressed: Color::srgb(0.35, 0.55, 0.35),
+            button_text: Color::srgb(0.9, 0.9, 0.9),
+        }
+    }
+
+    /// fn high_contrast
+    ///
+    /// saturated, fully opaque colors for low vision or bright rooms: a pale
+    /// yellow-green planet stands out from both the black field and the white black holes
+    pub fn high_contrast() -> Self {
+        Theme {
+            name: "High Contrast",
+            background: Color::BLACK,
+            border: Color::srgb(1.0, 0.0, 0.0),
+            black_hole: Color::WHITE,
+            planet: Color::srgb(0.6, 1.0, 0.2),
+            lens: Color::srgb(0.3, 0.8, 1.0),
+            ring: Color::srgba(1.0, 0.2, 0.2, 0.7),
+            highlight: Color::WHITE,
+            hud_label: Color::srgb(1.0, 1.0, 0.0),
+            hud_value: Color::srgb(1.0, 0.6, 0.0),
+            hud_text: Color::WHITE,
+            hud_success: Color::srgb(0.2, 1.0, 0.2),
+            hud_alert: Color::srgb(1.0, 0.2, 0.2),
+            meter_track: Color::srgb(0.4, 0.4, 0.4),
+            slider_fill: Color::srgb(0.0, 0.6, 0.0),
+            button: Color::BLACK,
+            button_hovered: Color::srgb(0.3, 0.3, 0.3),
+            button_pressed: Color::srgb(0.0, 0.6, 0.0),
+            button_text: Color::WHITE,
+        }
+    }
+
+    /// fn solarized
+    ///
+    /// Ethan Schoonover's Solarized dark palette, see https://ethanschoonover.com/solarized/
+    pub fn solarized() -> Self {
+        Theme {
+            name: "Solarized",
+            background: Color::srgb_u8(0x00, 0x2b, 0x36),
+            border: Color::srgb_u8(0xdc, 0x32, 0x2f),
+            black_hole: Color::srgb_u8(0xee, 0xe8, 0xd5),
+            planet: Color::srgb_u8(0x85, 0x99, 0x00),
+            lens: Color::srgb_u8(0x26, 0x8b, 0xd2),
+            ring: Color::srgba_u8(0xd3, 0x36, 0x82, 0x90),
+            highlight: Color::srgb_u8(0x93, 0xa1, 0xa1),
+            hud_label: Color::srgba_u8(0xb5, 0x89, 0x00, 0xb0),
+            hud_value: Color::srgba_u8(0xcb, 0x4b, 0x16, 0x90),
+            hud_text: Color::srgba_u8(0x93, 0xa1, 0xa1, 0xd0),
+            hud_success: Color::srgba_u8(0x85, 0x99, 0x00, 0xd0),
+            hud_alert: Color::srgba_u8(0xdc, 0x32, 0x2f, 0xd0),
+            meter_track: Color::srgba_u8(0x58, 0x6e, 0x75, 0x80),
+            slider_fill: Color::srgb_u8(0x2a, 0xa1, 0x98),
+            button: Color::srgb_u8(0x07, 0x36, 0x42),
+            button_hovered: Color::srgb_u8(0x58, 0x6e, 0x75),
+            button_pressed: Color::srgb_u8(0x2a, 0xa1, 0x98),
+            button_text: Color::srgb_u8(0xee, 0xe8, 0xd5),
+        }
+    }
+
+    /// fn presets
+    ///
+    /// returns every built-in theme, Classic first
+    pub fn presets() -> [Theme; 3] {
+        [Theme::classic(), Theme::high_contrast(), Theme::solarized()]
+    }
+
+    /// fn next
+    ///
+    /// returns the preset after this one (see fn presets), wrapping back to Classic.
+    /// A custom theme, which is not a preset, is followed by Classic as well
+    pub fn next(&self) -> Theme {
+        let presets = Theme::presets();
+        match presets.iter().position(|theme| theme.name == self.name) {
+            Some(index) => presets[(index + 1) % presets.len()],
+            None => presets[0],
+        }
+    }
+
+    /// fn recolor_field
+    ///
+    /// returns color, spawned on the field (a mesh of the space-time, its border, an
+    /// object or a halo) with the previous theme, as drawn with self instead, see fn recolor
+    pub fn recolor_field(&self, previous: &Theme, color: Color) -> Color {
+        let roles: [fn(&Theme) -> Color; 6] = [
+            |theme| theme.background,
+            |theme| theme.border,
+            |theme| theme.black_hole,
+            |theme| theme.planet,
+            |theme| theme.lens,
+            |theme| theme.ring,
+        ];
+        self.recolor(previous, color, &roles)
+    }
+
+    /// fn recolor_hud
+    ///
+    /// returns color, spawned in the HUD (a text, a slider, a button or the settings
+    /// panel) with the previous theme, as drawn with self instead, see fn recolor
+    pub fn recolor_hud(&self, previous: &Theme, color: Color) -> Color {
+        let roles: [fn(&Theme) -> Color; 13] = [
+            |theme| theme.highlight,
+            |theme| theme.hud_label,
+            |theme| theme.hud_value,
+            |theme| theme.hud_text,
+            |theme| theme.hud_success,
+            |theme| theme.hud_alert,
+            |theme| theme.meter_track,
+            |theme| theme.slider_fill,
+            |theme| theme.button,
+            |theme| theme.button_hovered,
+            |theme| theme.button_pressed,
+            |theme| theme.button_text,
+            |theme| theme.background,
+        ];
+        self.recolor(previous, color, &roles)
+    }
+
+    /// fn recolor: private!
+    ///
+    /// swaps color for self's color of the first of roles whose hue (red, green and blue)
+    /// it shares in the previous theme. The alpha follows the theme as well, unless the
+    /// spawn site overrode it (see the Theme struct), in which case it is kept. A color
+    /// taken from none of the roles is returned unchanged
+    fn recolor(&self, previous: &Theme, color: Color, roles: &[fn(&Theme) -> Color]) -> Color {
+        let drawn = color.to_srgba();
+        let same_hue = |one: Srgba| {
+            (one.red - drawn.red).abs() < 1e-3
+                && (one.green - drawn.green).abs() < 1e-3
+                && (one.blue - drawn.blue).abs() < 1e-3
+        };
+
+        let Some(role) = roles
+            .iter()
+            .find(|role| same_hue(role(previous).to_srgba()))
+        else {
+            return color;
+        };
+        if (role(previous).alpha() - drawn.alpha).abs() < 1e-3 {
+            role(self)
+        } else {
+            role(self).with_alpha(drawn.alpha)
+        }
+    }
+
+    /// fn named
+    ///
+    /// returns the preset whose name matches, ignoring case, spaces, dashes and
+    /// underscores (so "high-contrast" finds "High Contrast"), or None
+    pub fn named(name: &str) -> Option<Theme> {
+        let normalize = |s: &str| {
+            s.chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '_'))
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        };
+        let wanted = normalize(name);
+        Theme::presets()
+            .into_iter()
+            .find(|theme| normalize(theme.name) == wanted)
+    }
+}
+
+/// fn contrast_ratio
+///
+/// returns the WCAG contrast ratio of two opaque colors, from 1.0 (identical luminance)
+/// to 21.0 (white on black). See https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+pub fn contrast_ratio(one: Color, two: Color) -> f32 {
+    let luminance = |color: Color| {
+        let linear = color.to_linear();
+        0.2126 * linear.red + 0.7152 * linear.green + 0.0722 * linear.blue
+    };
+    let (one, two) = (luminance(one), luminance(two));
+    (one.max(two) + 0.05) / (one.min(two) + 0.05)
+}
+
+/// fn test_theme ensures the default is the Classic look, every preset can be found by
+/// name and the High Contrast planet stands out further from its field than the Classic one
+#[test]
+fn test_theme() {
+    assert_eq!(Theme::default(), Theme::classic());
+    assert_eq!(Theme::classic().planet, Color::linear_rgb(0.0, 0.9, 0.0));
+
+    for theme in Theme::presets() {
+        assert_eq!(Theme::named(theme.name), Some(theme));
+    }
+    assert_eq!(Theme::named("high-contrast"), Some(Theme::high_contrast()));
+    assert_eq!(Theme::named("neon"), None);
+
+    let classic = Theme::classic();
+    let high = Theme::high_contrast();
+    assert!(
+        contrast_ratio(high.planet, high.background)
+            > contrast_ratio(classic.planet, classic.background) + 2.0
+    );
+    assert!(contrast_ratio(high.planet, high.black_hole) > 1.0);
+}
+
+/// fn test_theme_change ensures the presets cycle and that switching themes repaints
+/// every color by its role, keeping an alpha the spawn site overrode
+#[test]
+fn test_theme_change() {
+    let [classic, high, solarized] = Theme::presets();
+    assert_eq!(classic.next(), high);
+    assert_eq!(high.next(), solarized);
+    assert_eq!(solarized.next(), classic);
+    let custom = Theme {
+        name: "Custom",
+        ..solarized
+    };
+    assert_eq!(custom.next(), classic);
+
+    assert_eq!(
+        solarized.recolor_field(&classic, classic.black_hole),
+        solarized.black_hole
+    );
+    assert_eq!(
+        solarized.recolor_field(&classic, classic.background),
+        solarized.background
+    );
+    assert_eq!(
+        solarized.recolor_hud(&classic, classic.hud_label),
+        solarized.hud_label
+    );
+    assert_eq!(
+        solarized.recolor_hud(&classic, classic.button_text),
+        solarized.button_text
+    );
+
+    //a halo drawn at its own alpha keeps it
+    let halo = classic.lens.with_alpha(0.3);
+    assert_eq!(
+        high.recolor_field(&classic, halo).to_srgba(),
+        high.lens.to_srgba().with_alpha(0.3)
+    );
+
+    //a color the theme did not pick is left alone
+    let other = Color::srgb(0.123, 0.456, 0.789);
+    assert_eq!(solarized.recolor_hud(&classic, other), other);
+    assert_eq!(
+        classic.recolor_field(&classic, classic.planet),
+        classic.planet
+    );
+}
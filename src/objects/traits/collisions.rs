@@ -5,14 +5,67 @@
 //! arises when two objects are moving at sufficient velocity compared to the frame rate
 //! so that the pass through eachother completly within that frame. Line segment logic
 //! is implemented herein to determine if two objects passed through eachother between frames
+//!
+//! None of it is specific to this game: the module is re-exported at the crate root as
+//! `black_hole_universe::collisions` so other crates can use the swept collision test for
+//! their own objects by implementing CollisionDetection:
+//!
+//! ```
+//! use black_hole_universe::collisions::{CollisionDetection, Position, Shapes};
+//!
+//! struct Ball {
+//!     pos: Position,
+//!     radius: f32,
+//! }
+//!
+//! impl CollisionDetection for Ball {
+//!     fn get_position(&self) -> Position {
+//!         self.pos
+//!     }
+//!
+//!     fn get_hitbox(&self) -> Shapes {
+//!         Shapes::Circle(self.radius)
+//!     }
+//! }
+//!
+//! //two fast balls cross paths within one frame: they are far apart at both ends
+//! //of the frame, but their paths intersect, so the swept test reports the hit
+//! let across = Ball {
+//!     pos: Position { x_prev: -100.0, y_prev: 0.0, x: 100.0, y: 0.0 },
+//!     radius: 1.0,
+//! };
+//! let down = Ball {
+//!     pos: Position { x_prev: 0.0, y_prev: 100.0, x: 0.0, y: -100.0 },
+//!     radius: 1.0,
+//! };
+//! assert!(across.collided(&down));
+//!
+//! //a ball whose path passes 5 units beside the other never touches it
+//! let beside = Ball {
+//!     pos: Position { x_prev: -100.0, y_prev: 5.0, x: 100.0, y: 5.0 },
+//!     radius: 1.0,
+//! };
+//! let still = Ball {
+//!     pos: Position::new(0.0, 0.0),
+//!     radius: 1.0,
+//! };
+//! assert!(!beside.collided(&still));
+//! assert_eq!(beside.minimum_distance(&still.get_position()), 5.0);
+//! ```
 
 use bevy::{math::FloatPow, prelude::*};
 
-/// Shapes enum: todo()!
+/// Points within this fraction of the segment's largest coordinate (at least 1.0) of a
+/// segment are considered on it, absorbing the floating point error of the intersection
+/// calculations. Relative, since an f32 ulp alone is ~1e-3 at the edge of the universe
+const SEGMENT_TOLERANCE: f32 = 1.0e-5;
+
+/// Shapes enum
 ///
 /// implements a geometric shape used to determine a collision by defining the
-/// boundary of the object via this geometry primitive. ToDo: implement more shapes
-/// to the universe
+/// boundary of the object via this geometry primitive. Marked non_exhaustive so that
+/// more shapes can be added to the universe without breaking other crates' matches
+#[non_exhaustive]
 pub enum Shapes {
     Circle(f32), //radius
                  //Square { width: f32 },
@@ -90,26 +143,53 @@ pub struct LineSegment<'a> {
 
 /// impl block for LinSegment used to calculate distance via method interface
 impl<'a> LineSegment<'a> {
+    /// fn tolerance: private!
+    ///
+    /// returns the distance within which a point counts as on this segment: SEGMENT_TOLERANCE
+    /// scaled by the magnitude of the segment's coordinates, so that it always spans
+    /// more than the rounding error of a point calculated out there
+    fn tolerance(&self) -> f32 {
+        let magnitude = self
+            .pos
+            .x
+            .abs()
+            .max(self.pos.y.abs())
+            .max(self.pos.x_prev.abs())
+            .max(self.pos.y_prev.abs())
+            .max(1.0);
+        SEGMENT_TOLERANCE * magnitude
+    }
+
+    /// fn contains: private!
+    ///
+    /// returns true if the point (x, y), known to lie on this segment's line, is
+    /// between the segment's endpoints (within the tolerance above)
+    fn contains(&self, x: f32, y: f32) -> bool {
+        let tolerance = self.tolerance();
+        let within_x = self.pos.x.min(self.pos.x_prev) - tolerance <= x
+            && self.pos.x.max(self.pos.x_prev) + tolerance >= x;
+        let within_y = self.pos.y.min(self.pos.y_prev) - tolerance <= y
+            && self.pos.y.max(self.pos.y_prev) + tolerance >= y;
+
+        within_x && within_y
+    }
+
     /// fn distance_to_pt
     ///
     /// returns the nearest distance of this line segment to the given point.
     /// This function is used to determine if an intersection occured between frames
     fn distance_to_pt(&self, x: f32, y: f32) -> f32 {
         //https://www.splashlearn.com/math-vocabulary/distance-of-a-point-from-a-line#:~:text=The%20shortest%20distance%20between%20point%20and%20line,drawn%20from%20the%20point%20to%20the%20line.
-        let factor = (self.a * x + self.b * y + self.c) / (self.a * self.a + self.b * self.b); //https://en.wikipedia.org/wiki/Distance_from_a_point_to_a_line
-        let x_on_line = x - self.a * factor; //point on line closest to the given point
-        let y_on_line = y - self.b * factor;
+        let norm = self.a * self.a + self.b * self.b;
+        let offset = self.a * x + self.b * y - self.c; //zero on the line a*x + b*y = c
+        let x_on_line = x - self.a * offset / norm; //point on line closest to the given point
+        let y_on_line = y - self.b * offset / norm; //https://en.wikipedia.org/wiki/Distance_from_a_point_to_a_line
         // the point (x_on_line,y_on_line) can either be one of the endpoints or somewhere
         // in the interior of the line segment:
 
         //now need to check that x,y is on this line SEGMENT (self.x, self.y), (self.x_prev, self.y_prev)
-        let within_x: bool = self.pos.x.min(self.pos.x_prev) <= x_on_line
-            && self.pos.x.max(self.pos.x_prev) >= x_on_line;
-        let within_y: bool = self.pos.y.min(self.pos.y_prev) <= y_on_line
-            && self.pos.y.max(self.pos.y_prev) >= y_on_line;
-
-        if within_x & within_y {
-            (self.a * x + self.b * y + self.c).abs() / (self.a * self.a + self.b * self.b).sqrt() //distance
+        if self.contains(x_on_line, y_on_line) {
+            offset.abs() / norm.sqrt() //distance
         } else {
             //must be one endpoint is closest to this point
             let d1 = (self.pos.x - x).squared() + (self.pos.y - y).squared();
@@ -139,7 +219,7 @@ pub trait CollisionDetection {
     /// hit box of the object (typically it's area in 2D or volume in 3D).
     fn get_hitbox(&self) -> Shapes;
 
-    /// fn minimum_distance(&self, two: &Position) -> Option<f32>
+    /// fn minimum_distance(&self, two: &Position) -> f32
     ///
     /// Given a Position, determines the minimum distance from the two line segments
     /// defined by the Positions (x,y) and (x_prev, y_prev) parameters. This is performed by
@@ -149,40 +229,56 @@ pub trait CollisionDetection {
     /// 3. If lines do intersect, then the intersection point is calculated and checked to be within the segment
     /// 3. If interseciton pt is outside the segments, the LineSegment::distance_to_pt method is again invoked
     ///
+    /// A Position which hasn't moved has no line segment and is treated as a single point: the
+    /// distance is then from that point to the other's segment, or between the two points if
+    /// neither has moved (e.g. two objects spawned at the same coordinates)
+    ///
     /// See the following link for reference:
     /// https://www.topcoder.com/thrive/articles/Geometry%20Concepts%20part%202:%20%20Line%20Intersection%20and%20its%20Applications
-    fn minimum_distance(&self, two: &Position) -> Option<f32> {
+    fn minimum_distance(&self, two: &Position) -> f32 {
         let one = self.get_position();
-        let l1: LineSegment = one.gen_line_segment()?;
-        let l2: LineSegment = two.gen_line_segment()?;
+        let (l1, l2) = match (one.gen_line_segment(), two.gen_line_segment()) {
+            (Some(l1), Some(l2)) => (l1, l2),
+            (Some(l1), None) => return l1.distance_to_pt(two.x, two.y),
+            (None, Some(l2)) => return l2.distance_to_pt(one.x, one.y),
+            (None, None) => return one.distance_to(two),
+        };
 
-        let det = (l1.b * l2.a) - (l2.b * l1.a);
+        let det = (l1.a * l2.b) - (l2.a * l1.b);
 
         // if det == 0 then lines are parallel
         if det != 0.0 {
             // if not parallel, then they must intersect eventually...
-            // these are intesections of the infinite lines:
-            let int_x = (l2.b * l1.c - l1.b * l2.c) / det;
+            // these are intesections of the infinite lines (Cramer's rule):
+            let int_x = (l1.c * l2.b - l2.c * l1.b) / det;
             let int_y = (l1.a * l2.c - l2.a * l1.c) / det;
 
-            // now need to check if that intersection points is one the segment:
-            if one.x.min(one.x_prev) >= int_x
-                && one.x.max(one.x_prev) <= int_x
-                && one.y.min(one.y_prev) >= int_y
-                && one.y.max(one.y_prev) <= int_y
-            {
+            // now need to check if that intersection point is on both segments:
+            if l1.contains(int_x, int_y) && l2.contains(int_x, int_y) {
                 //then they intersect, so minimum distance is 0
-                return Some(0.0f32);
+                return 0.0f32;
             }
         }
 
         // lines are parallel and so a1=a2 and b1=b2 or line segements do not intesect.
-        // either way, solve by finding closest endpoint to the other line (the closest
-        // point must be one of the endpoints now)
-        Some(
-            l1.distance_to_pt(two.x, two.y)
-                .min(l1.distance_to_pt(two.x_prev, two.y_prev)),
-        )
+        // either way, the closest approach must involve an endpoint of one of the
+        // segments, so solve by finding the closest endpoint to the other segment
+        l1.distance_to_pt(two.x, two.y)
+            .min(l1.distance_to_pt(two.x_prev, two.y_prev))
+            .min(l2.distance_to_pt(one.x, one.y))
+            .min(l2.distance_to_pt(one.x_prev, one.y_prev))
+    }
+
+    /// fn contact_distance(&self, other: &dyn CollisionDetection) -> f32
+    ///
+    /// the separation between the two positions at which the hitboxes begin to
+    /// intersect, i.e. the threshold used by collided below
+    fn contact_distance(&self, other: &dyn CollisionDetection) -> f32 {
+        match self.get_hitbox() {
+            Shapes::Circle(r1) => match other.get_hitbox() {
+                Shapes::Circle(r2) => r1 + r2,
+            },
+        }
     }
 
     /// fn collided(&self, other: &dyn CollisionDetection) -> bool
@@ -196,22 +292,99 @@ pub trait CollisionDetection {
     /// 3. If this minimum distance is within the intersection region of the hitboxes, returns true
     ///    and otherwise false
     fn collided(&self, other: &dyn CollisionDetection) -> bool {
-        let my_hitbox = self.get_hitbox();
-
         let other_position = other.get_position();
-        let other_hitbox = other.get_hitbox();
 
-        let min_r = self.minimum_distance(&other_position);
-        if min_r.is_none() {
-            return false;
-        }
+        self.minimum_distance(&other_position) <= self.contact_distance(other)
+    }
+}
 
-        let min_r = min_r.unwrap();
+/// fn test_minimum_distance ensures the swept distance is correct for paths away from
+/// the origin: crossing paths touch, and parallel or diverging paths are measured from
+/// the closest endpoint of either segment
+#[test]
+fn test_minimum_distance() {
+    struct Dot(Position);
 
-        match my_hitbox {
-            Shapes::Circle(r1) => match other_hitbox {
-                Shapes::Circle(r2) => min_r <= r1 + r2,
-            },
+    impl CollisionDetection for Dot {
+        fn get_position(&self) -> Position {
+            self.0
+        }
+
+        fn get_hitbox(&self) -> Shapes {
+            Shapes::Circle(1.0)
         }
     }
+
+    let path = |x_prev, y_prev, x, y| Position {
+        x_prev,
+        y_prev,
+        x,
+        y,
+    };
+
+    //crossing at (50, 20)
+    let across = Dot(path(0.0, 20.0, 100.0, 20.0));
+    assert_eq!(across.minimum_distance(&path(40.0, 30.0, 60.0, 10.0)), 0.0);
+
+    //parallel, 10 apart
+    assert!((across.minimum_distance(&path(0.0, 30.0, 100.0, 30.0)) - 10.0).abs() < 1.0e-3);
+
+    //the lines cross beyond the end of both segments: the closest approach is from
+    //the other path's endpoint (110, 25) to this path's endpoint (100, 20)
+    let distance = across.minimum_distance(&path(120.0, 30.0, 110.0, 25.0));
+    assert!((distance - 125.0f32.sqrt()).abs() < 1.0e-3);
+
+    //this path's endpoint (100, 20) is closest to the interior of the other path
+    let distance = across.minimum_distance(&path(103.0, 0.0, 103.0, 40.0));
+    assert!((distance - 3.0).abs() < 1.0e-3);
+}
+
+/// fn test_segment_geometry ensures the LineSegment primitives hold away from the origin:
+/// the distance to a point uses the line a*x + b*y = c (not a*x + b*y + c = 0), the
+/// projection is only used while it falls on the segment, and the on-segment
+/// tolerance grows with the coordinates so rounding near the edge of the universe
+/// is absorbed while a real miss near the origin is not
+#[test]
+fn test_segment_geometry() {
+    let segment = |x_prev, y_prev, x, y| Position {
+        x_prev,
+        y_prev,
+        x,
+        y,
+    };
+
+    //the horizontal path y = 20 from x = 0 to x = 100: a*x + b*y = c with c != 0
+    let path = segment(0.0, 20.0, 100.0, 20.0);
+    let line = path.gen_line_segment().unwrap();
+    assert!((line.a * 50.0 + line.b * 20.0 - line.c).abs() < 1.0e-3);
+    assert!((line.distance_to_pt(50.0, 35.0) - 15.0).abs() < 1.0e-3);
+    assert!((line.distance_to_pt(50.0, 5.0) - 15.0).abs() < 1.0e-3);
+
+    //past the end of the segment the distance is to the endpoint (100, 20)
+    assert!((line.distance_to_pt(103.0, 24.0) - 5.0).abs() < 1.0e-3);
+
+    //the intersection of two paths is on both of them
+    let across = segment(10_000.0, 20.0, 10_100.0, 20.0);
+    let down = segment(10_050.0, 120.0, 10_050.0, -80.0);
+    let l1 = across.gen_line_segment().unwrap();
+    let l2 = down.gen_line_segment().unwrap();
+    let det = l1.a * l2.b - l2.a * l1.b;
+    let int_x = (l1.c * l2.b - l2.c * l1.b) / det;
+    let int_y = (l1.a * l2.c - l2.a * l1.c) / det;
+    assert!((int_x - 10_050.0).abs() < 1.0e-2 && (int_y - 20.0).abs() < 1.0e-2);
+    assert!(l1.contains(int_x, int_y) && l2.contains(int_x, int_y));
+
+    //a few ulps past the end of a path at the edge of the universe is still on it...
+    let edge = segment(12_000.0, 0.0, 12_500.0, 0.0);
+    let edge_line = edge.gen_line_segment().unwrap();
+    let past = f32::from_bits(12_500.0f32.to_bits() + 4);
+    assert!(past - 12_500.0 > 1.0e-3);
+    assert!(edge_line.contains(past, 0.0));
+    assert!(!edge_line.contains(12_501.0, 0.0));
+
+    //...while near the origin the same margin is a miss
+    let short = segment(0.0, 0.0, 0.5, 0.0);
+    let short_line = short.gen_line_segment().unwrap();
+    assert!(short_line.contains(0.5 + 5.0e-6, 0.0));
+    assert!(!short_line.contains(0.5 + 5.0e-4, 0.0));
 }
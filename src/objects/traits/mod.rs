@@ -3,3 +3,4 @@
 //! For exposing the modules in the objects folder to
 //! eachother and to parent modules
 pub mod collisions;
+pub mod outcomes;
@@ -0,0 +1,52 @@
+//! Outcomes.rs
+//!
+//! outcomes module defines what a collision produces. Rather than hard-coding the
+//! result of every pairing of object types into the collision processing, each
+//! object type declares the outcome of colliding with any other type through the
+//! CollisionBehavior trait
+
+/// CollisionOutcome enum
+///
+/// The possible results of a collision. The variants are ordered by precedence:
+/// when a collision involves several objects, the highest-precedence outcome
+/// declared by any pair within it decides the fate of the whole group
+/// 1. Split - each object breaks apart into fragments
+/// 2. Bounce - objects rebound elastically off one another
+/// 3. Merge - all objects coalesce into a single object
+///
+/// allow(dead_code) used to prevent warnings on the unused Bounce variant: intended in future use
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CollisionOutcome {
+    Split,
+    Bounce,
+    Merge,
+}
+
+/// CollisionBehavior Trait
+///
+/// Implement this trait for the type that identifies an object's kind. Implementers
+/// need only define fn collision_outcome: adding a new kind of object means declaring
+/// its outcome against every other kind here, nothing else
+pub trait CollisionBehavior {
+    /// fn collision_outcome(&self, other: &Self) -> CollisionOutcome : Abstract!
+    ///
+    /// Must be defined by the implementor. Returns the outcome of self colliding with
+    /// other; implementors should make this symmetric
+    fn collision_outcome(&self, other: &Self) -> CollisionOutcome;
+}
+
+/// fn group_outcome
+///
+/// returns the outcome of a collision of objects of the given kinds together: the
+/// highest-precedence outcome of any pair of them. Fewer than 2 kinds never merge or
+/// bounce and so Split is returned
+pub fn group_outcome<T: CollisionBehavior>(kinds: &[T]) -> CollisionOutcome {
+    let mut outcome = CollisionOutcome::Split;
+    for (index, one) in kinds.iter().enumerate() {
+        for two in kinds.iter().skip(index + 1) {
+            outcome = outcome.max(one.collision_outcome(two));
+        }
+    }
+    outcome
+}
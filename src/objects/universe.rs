@@ -0,0 +1,1070 @@
+//! Universe.rs
+//!
+//! The universe module generates the starting black holes as a pure function of a
+//! seed and the slider settings, so that a universe can be reproduced exactly (see
+//! recording.rs). Position, velocity and mass are each drawn from their own seeded
+//! stream: moving one slider only changes its own attribute and leaves the others,
+//! and the first N black holes, untouched.
+//!
+//! The VelocityPattern chooses how the sampled velocities are directed: randomly,
+//! tangential to the center (a rotating disk), radially outward (an expanding cluster)
+//! or as a big bang (a compact cluster bursting apart).
+//! The ClusterCount splits the black holes between one or more clusters (galaxies).
+//! Before launch, further clusters can be scattered by hand (see UniverseSettings::scatter)
+
+use crate::objects::gamestate::{UNIVERSE_SIZE, UniverseConfig};
+use crate::objects::gauss::{Gauss, GaussBoundary, GaussMixture};
+use crate::objects::movables::{Movable, ObjectType};
+use crate::objects::sliders::{
+    BLACKHOLE_MASS_RNG, BLACKHOLE_VEL_RNG, POSSTDEVMIN, SliderType, SliderValue, VELSTDEVMIN,
+    count_from_slider,
+};
+use crate::objects::traits::collisions::CollisionDetection;
+use bevy::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::f32::consts::TAU;
+
+/// The number of black holes scattered by one Shift + click (see UniverseSettings::scatter)
+pub const SCATTER_COUNT: usize = 8;
+
+/// The standard deviation of the scattered positions around the click
+pub const SCATTER_SPREAD: f32 = UNIVERSE_SIZE / 50.0;
+
+/// VelocityPattern enum: Resource
+///
+/// How the starting velocities of the black holes are directed. The speeds are
+/// sampled identically for every pattern, only their direction differs:
+/// 1. Random - every black hole heads in a random direction
+/// 2. Tangential - perpendicular to the line from the center: a swirling, galaxy-like disk
+/// 3. Radial - directly away from the center: an expanding cluster
+/// 4. BigBang - every black hole starts packed near the center of its cluster
+///    (BIG_BANG_COMPRESSION), yet clear of the others, and bursts radially out of that
+///    center BIG_BANG_ENERGY times faster, with the universe's clock ramping
+///    up over the first BIG_BANG_SECS (see fn time_scale). The Velocity slider sets the
+///    energy of the burst: gentle settings fall back together under their own gravity
+///
+/// Unlike the other patterns BigBang also moves the sampled positions, see fn place
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VelocityPattern {
+    #[default]
+    Random,
+    Tangential,
+    Radial,
+    BigBang,
+}
+
+/// The fraction of its sampled distance from its cluster's center at which a BigBang black
+/// hole starts. One which would then overlap another is pushed further out along the same
+/// line until it is clear of them all (see UniverseSettings::black_holes)
+pub const BIG_BANG_COMPRESSION: f32 = 0.2;
+
+/// The factor by which a BigBang stretches the sampled speeds (still capped at MAXVELOCITY)
+pub const BIG_BANG_ENERGY: f32 = 5.0;
+
+/// The seconds after launch over which a BigBang's clock ramps up to full speed
+pub const BIG_BANG_SECS: f64 = 1.0;
+
+impl VelocityPattern {
+    /// fn next returns the pattern following self, cycling back to Random
+    pub fn next(&self) -> Self {
+        match self {
+            VelocityPattern::Random => VelocityPattern::Tangential,
+            VelocityPattern::Tangential => VelocityPattern::Radial,
+            VelocityPattern::Radial => VelocityPattern::BigBang,
+            VelocityPattern::BigBang => VelocityPattern::Random,
+        }
+    }
+
+    /// fn name returns the pattern's display (and recording) name
+    pub fn name(&self) -> &'static str {
+        match self {
+            VelocityPattern::Random => "Random",
+            VelocityPattern::Tangential => "Tangential",
+            VelocityPattern::Radial => "Radial",
+            VelocityPattern::BigBang => "BigBang",
+        }
+    }
+
+    /// fn from_name is the inverse of fn name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            VelocityPattern::Random,
+            VelocityPattern::Tangential,
+            VelocityPattern::Radial,
+            VelocityPattern::BigBang,
+        ]
+        .into_iter()
+        .find(|pattern| pattern.name() == name)
+    }
+
+    /// fn direct
+    ///
+    /// redirects the randomly sampled velocity (vx, vy) of a black hole at (x, y) from
+    /// the pattern's origin (see fn origin) according to the pattern, keeping its speed
+    pub fn direct(&self, x: f32, y: f32, vx: f32, vy: f32) -> (f32, f32) {
+        let speed = vx.hypot(vy);
+        let theta = y.atan2(x);
+
+        match self {
+            VelocityPattern::Random => (vx, vy),
+            VelocityPattern::Tangential => (-speed * theta.sin(), speed * theta.cos()),
+            VelocityPattern::Radial => (speed * theta.cos(), speed * theta.sin()),
+            VelocityPattern::BigBang => (
+                BIG_BANG_ENERGY * speed * theta.cos(),
+                BIG_BANG_ENERGY * speed * theta.sin(),
+            ),
+        }
+    }
+
+    /// fn place
+    ///
+    /// moves the position (x, y) of a black hole sampled around the cluster center
+    /// (cx, cy) according to the pattern: a BigBang packs it toward that center, every
+    /// other pattern leaves it alone
+    pub fn place(&self, x: f32, y: f32, (cx, cy): (f32, f32)) -> (f32, f32) {
+        match self {
+            VelocityPattern::BigBang => (
+                cx + BIG_BANG_COMPRESSION * (x - cx),
+                cy + BIG_BANG_COMPRESSION * (y - cy),
+            ),
+            _ => (x, y),
+        }
+    }
+
+    /// fn origin
+    ///
+    /// returns the point the velocity of a black hole sampled around the cluster center
+    /// is directed about (see fn direct): a BigBang bursts out of every cluster's own
+    /// center, every other pattern is directed about the center of the universe
+    pub fn origin(&self, center: (f32, f32)) -> (f32, f32) {
+        match self {
+            VelocityPattern::BigBang => center,
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// fn time_scale
+    ///
+    /// returns the rate [0-1] at which the universe's clock runs since_launch seconds after
+    /// launch. A BigBang eases in from a standstill over BIG_BANG_SECS (a smoothstep) so the
+    /// cluster visibly bursts apart; scaling the clock rather than the velocities keeps the
+    /// dynamics intact. Every other pattern always runs at full speed
+    pub fn time_scale(&self, since_launch: f64) -> f32 {
+        match self {
+            VelocityPattern::BigBang => {
+                let t = (since_launch / BIG_BANG_SECS).clamp(0.0, 1.0) as f32;
+                t * t * (3.0 - 2.0 * t)
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+/// ClusterCount struct: Resource
+///
+/// The number of clusters (galaxies) the black holes are spread between, from 1 to
+/// MAX_CLUSTERS. A single cluster sits at the center of the universe; several clusters
+/// sit evenly spaced on a ring around the center. The Density slider sets how tight
+/// every cluster is, so dense settings give distinct galaxies
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClusterCount(pub u8);
+
+impl ClusterCount {
+    /// the largest number of clusters
+    pub const MAX_CLUSTERS: u8 = 3;
+
+    /// fn next returns the count following self, cycling back to 1
+    pub fn next(&self) -> Self {
+        ClusterCount(self.0 % ClusterCount::MAX_CLUSTERS + 1)
+    }
+
+    /// fn centers
+    ///
+    /// returns the center of every cluster. The ring of several clusters has radius
+    /// UNIVERSE_SIZE / 4 and is rotated by an angle drawn from the seed
+    pub fn centers(&self, seed: u64) -> Vec<(f32, f32)> {
+        let count = self.0.clamp(1, ClusterCount::MAX_CLUSTERS);
+        if count == 1 {
+            return vec![(0.0, 0.0)];
+        }
+
+        let radius = UNIVERSE_SIZE / 4.0;
+        let phase = StdRng::seed_from_u64(seed).random_range(0.0..TAU);
+
+        (0..count)
+            .map(|i| {
+                let theta = phase + TAU * i as f32 / count as f32;
+                (radius * theta.cos(), radius * theta.sin())
+            })
+            .collect()
+    }
+}
+
+/// Default is a single central cluster
+impl Default for ClusterCount {
+    fn default() -> Self {
+        ClusterCount(1)
+    }
+}
+
+/// ClusterLabel struct: Component
+///
+/// Used for querying the Bevy Text entity which displays the current ClusterCount
+#[derive(Component)]
+pub struct ClusterLabel;
+
+/// PatternLabel struct: Component
+///
+/// Used for querying the Bevy Text entity which displays the current VelocityPattern
+#[derive(Component)]
+pub struct PatternLabel;
+
+/// UniverseSettings struct
+///
+/// The slider values [0-1] which shape the generated universe
+/// - count = number of black holes (see count_from_slider)
+/// - mass = mean black hole mass
+/// - velocity = spread of the black hole velocities
+/// - density = how tightly the black holes are grouped around the center
+/// - pattern = how the black hole velocities are directed (see VelocityPattern)
+/// - clusters = how many clusters the black holes are grouped into (see ClusterCount)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UniverseSettings {
+    pub count: f32,
+    pub mass: f32,
+    pub velocity: f32,
+    pub density: f32,
+    pub pattern: VelocityPattern,
+    pub clusters: ClusterCount,
+}
+
+/// Default matches the default slider positions: all half full
+impl Default for UniverseSettings {
+    fn default() -> Self {
+        UniverseSettings {
+            count: 0.5,
+            mass: 0.5,
+            velocity: 0.5,
+            density: 0.5,
+            pattern: VelocityPattern::Random,
+            clusters: ClusterCount(1),
+        }
+    }
+}
+
+impl UniverseSettings {
+    /// fn from_sliders
+    ///
+    /// gathers the settings from the slider bars, the chosen VelocityPattern and ClusterCount;
+    /// sliders which do not shape the universe (e.g. Launch) are ignored and a missing
+    /// slider keeps its default (half full) position, see missing_sliders
+    pub fn from_sliders<'a>(
+        sliders: impl IntoIterator<Item = (&'a SliderValue, &'a SliderType)>,
+        pattern: VelocityPattern,
+        clusters: ClusterCount,
+    ) -> Self {
+        let mut settings = UniverseSettings {
+            pattern,
+            clusters,
+            ..default()
+        };
+
+        for (slider_value, slider_type) in sliders {
+            match slider_type {
+                SliderType::Count => settings.count = slider_value.value,
+                SliderType::Mass => settings.mass = slider_value.value,
+                SliderType::Velocity => settings.velocity = slider_value.value,
+                SliderType::Density => settings.density = slider_value.value,
+                SliderType::Launch => {} //planet setting, see fn update_launch_config
+                SliderType::Softening => {} //physics setting, see fn update_softening_setting
+                SliderType::TimeScale => {} //clock setting, see fn update_time_scale_setting
+            }
+        }
+
+        settings
+    }
+
+    /// fn missing_sliders
+    ///
+    /// returns the names of the universe sliders (Count, Mass, Velocity, Density) absent
+    /// from sliders, e.g. when the HUD has not been spawned. from_sliders falls back to
+    /// the default position for each of them
+    pub fn missing_sliders<'a>(
+        sliders: impl IntoIterator<Item = (&'a SliderValue, &'a SliderType)>,
+    ) -> Vec<&'static str> {
+        let mut found = [false; 4];
+        for (_, slider_type) in sliders {
+            match slider_type {
+                SliderType::Count => found[0] = true,
+                SliderType::Mass => found[1] = true,
+                SliderType::Velocity => found[2] = true,
+                SliderType::Density => found[3] = true,
+                SliderType::Launch | SliderType::Softening | SliderType::TimeScale => {}
+            }
+        }
+
+        ["Count", "Mass", "Velocity", "Density"]
+            .into_iter()
+            .zip(found)
+            .filter_map(|(name, found)| (!found).then_some(name))
+            .collect()
+    }
+
+    /// fn black_holes
+    ///
+    /// generates the black holes described by these settings. The same seed and
+    /// settings always produce the same black holes, in the same order. Slider values
+    /// outside of [0-1] (e.g. from a hand edited recording) are clamped to the bar, so
+    /// the position and velocity spreads never reach the Gauss panic at a zero std
+    pub fn black_holes(&self, seed: u64) -> Vec<Movable> {
+        let density = self.density.clamp(0.0, 1.0);
+        //use 1-slider value so that max on the bar squeezes the universe the most
+        let bh_pos_std = (1.0 - density + POSSTDEVMIN) * UNIVERSE_SIZE / 2.0; //universesize/2 is max - basically fills the universe
+
+        //the cluster layout has its own stream so the count of clusters never shifts the others
+        let mut position_rand = GaussMixture::seeded(
+            self.clusters.centers(seed.wrapping_add(3)),
+            bh_pos_std,
+            GaussBoundary::WrapBoth((-UNIVERSE_SIZE / 2.0, UNIVERSE_SIZE / 2.0)),
+            seed,
+        );
+
+        let (mut bh_vel_rand, mut bh_mass_rand) = self.samplers(seed);
+
+        let mut black_holes: Vec<Movable> = Vec::new();
+        for _ in 0..count_from_slider(self.count) {
+            let ((x, y), center) = position_rand.sample_with_center();
+            let (x, y) = self.pattern.place(x, y, center);
+            let (vx, vy) = (bh_vel_rand.sample(), bh_vel_rand.sample());
+
+            let mut black_hole = Movable::new(&ObjectType::BlackHole)
+                .set_position(x, y)
+                .set_mass(bh_mass_rand.sample())
+                .build();
+            if self.pattern == VelocityPattern::BigBang {
+                UniverseSettings::clear_overlaps(&mut black_hole, center, &black_holes);
+            }
+
+            let (ox, oy) = self.pattern.origin(center);
+            let (vx, vy) = self.pattern.direct(
+                black_hole.position.x - ox,
+                black_hole.position.y - oy,
+                vx,
+                vy,
+            );
+            black_hole.set_velocity(vx, vy);
+            black_holes.push(black_hole);
+        }
+
+        black_holes
+    }
+
+    /// fn clear_overlaps: private!
+    ///
+    /// pushes black_hole away from the center (cx, cy) along the line through both until
+    /// it overlaps none of others (see CollisionDetection::contact_distance). A black hole
+    /// on the center is pushed along +x. Leaving each disc it overlaps by its far side
+    /// never re-enters it, so this ends after at most one push per other
+    fn clear_overlaps(black_hole: &mut Movable, (cx, cy): (f32, f32), others: &[Movable]) {
+        let (dx, dy) = (black_hole.position.x - cx, black_hole.position.y - cy);
+        let mut reach = dx.hypot(dy);
+        let (ux, uy) = if reach == 0.0 {
+            (1.0, 0.0)
+        } else {
+            (dx / reach, dy / reach)
+        };
+
+        loop {
+            let (x, y) = (cx + ux * reach, cy + uy * reach);
+            let overlapped = others.iter().find(|other| {
+                (other.position.x - x).hypot(other.position.y - y)
+                    <= black_hole.contact_distance(*other)
+            });
+            let Some(other) = overlapped else {
+                break;
+            };
+
+            //the far point of the line at contact_distance from other, plus a sliver
+            let contact = black_hole.contact_distance(other);
+            let (ox, oy) = (other.position.x - cx, other.position.y - cy);
+            let along = ox * ux + oy * uy;
+            let across = ox * uy - oy * ux;
+            let exit = along + (contact * contact - across * across).max(0.0).sqrt();
+            reach = exit.max(reach) + 1e-3 * contact;
+        }
+
+        black_hole.position.x = cx + ux * reach;
+        black_hole.position.y = cy + uy * reach;
+        black_hole.position.x_prev = black_hole.position.x;
+        black_hole.position.y_prev = black_hole.position.y;
+    }
+
+    /// fn scatter
+    ///
+    /// returns SCATTER_COUNT black holes scattered SCATTER_SPREAD around (x, y), with
+    /// velocities and masses drawn exactly as fn black_holes draws them from the Velocity
+    /// and Mass sliders. The VelocityPattern is left out: it is directed about the center
+    /// of the universe, not of the scattered cluster. Positions are not wrapped
+    pub fn scatter(&self, x: f32, y: f32, seed: u64) -> Vec<Movable> {
+        let mut position_rand =
+            GaussMixture::seeded(vec![(x, y)], SCATTER_SPREAD, GaussBoundary::None, seed);
+        let (mut bh_vel_rand, mut bh_mass_rand) = self.samplers(seed);
+
+        (0..SCATTER_COUNT)
+            .map(|_| {
+                let (x, y) = position_rand.sample();
+                Movable::new(&ObjectType::BlackHole)
+                    .set_position(x, y)
+                    .set_velocity(bh_vel_rand.sample(), bh_vel_rand.sample())
+                    .set_mass(bh_mass_rand.sample())
+                    .build()
+            })
+            .collect()
+    }
+
+    /// fn samplers: private!
+    ///
+    /// returns the velocity and mass samplers (in that order) of the Velocity and Mass
+    /// sliders, each on its own stream of seed
+    fn samplers(&self, seed: u64) -> (Gauss, Gauss) {
+        let (mass, velocity) = (self.mass.clamp(0.0, 1.0), self.velocity.clamp(0.0, 1.0));
+        let bh_mass_mean = (BLACKHOLE_MASS_RNG.upper + BLACKHOLE_MASS_RNG.lower) / 2.0;
+        let bh_mass = mass * bh_mass_mean;
+        let bh_vel = (velocity + VELSTDEVMIN)
+            * (BLACKHOLE_VEL_RNG.upper.abs() + BLACKHOLE_VEL_RNG.lower.abs())
+            / 2.0;
+
+        let bh_vel_rand = Gauss::seeded(
+            0.0,
+            bh_vel,
+            GaussBoundary::ClampBoth((BLACKHOLE_VEL_RNG.lower, BLACKHOLE_VEL_RNG.upper)),
+            seed.wrapping_add(1),
+        );
+
+        let bh_mass_rand = Gauss::seeded(
+            bh_mass,
+            BLACKHOLE_MASS_RNG.upper / 4.0,
+            GaussBoundary::ClampBoth((BLACKHOLE_MASS_RNG.lower, BLACKHOLE_MASS_RNG.upper)),
+            seed.wrapping_add(2),
+        );
+
+        (bh_vel_rand, bh_mass_rand)
+    }
+}
+
+/// Scatter struct
+///
+/// One Shift + click of the scatter tool: the cluster is drawn again from these (see
+/// UniverseSettings::scatter) with whatever the Velocity and Mass sliders currently are
+/// - position = where the click landed, in World coordinates
+/// - seed = the seed the cluster was drawn with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scatter {
+    pub position: Vec2,
+    pub seed: u64,
+}
+
+/// UniverseLayout struct: Resource
+///
+/// Tracks the seed used to generate the universe and which settings the black holes
+/// currently on the field were generated from, so that the field is only regenerated
+/// when the seed or a slider has actually changed. A universe handed over as a list of
+/// objects (see NewGameConfig::Objects) is remembered as placed, in place of the
+/// generated black holes, until a slider or the seed changes.
+///
+/// The clusters scattered on a generated field are kept as scatters and stacked on top of
+/// it every time it is generated again (a slider edit, a restart or a replay), until the
+/// seed changes.
+///
+/// universe is the size of the field: black holes are generated in the square
+/// UNIVERSE_SIZE universe and stretched to fit it (see UniverseConfig::fit)
+#[derive(Resource)]
+pub struct UniverseLayout {
+    pub seed: u64,
+    pub universe: UniverseConfig,
+    generated: Option<(u64, UniverseSettings)>,
+    placed: Option<Vec<Movable>>,
+    scatters: Vec<Scatter>,
+}
+
+/// Default draws a random seed for the default square universe
+impl Default for UniverseLayout {
+    fn default() -> Self {
+        UniverseLayout::seeded(rand::random())
+    }
+}
+
+impl UniverseLayout {
+    /// fn seeded returns a layout which generates its first universe from seed
+    pub fn seeded(seed: u64) -> Self {
+        UniverseLayout {
+            seed,
+            universe: UniverseConfig::default(),
+            generated: None,
+            placed: None,
+            scatters: Vec::new(),
+        }
+    }
+
+    /// fn reseed draws a new random seed: a different universe with the same settings,
+    /// without the scatters of the last one
+    pub fn reseed(&mut self) {
+        self.seed = rand::random();
+        self.scatters.clear();
+    }
+
+    /// fn is_stale returns true if the field no longer matches the seed and settings
+    pub fn is_stale(&self, settings: &UniverseSettings) -> bool {
+        self.generated != Some((self.seed, *settings))
+    }
+
+    /// fn generated returns the seed and settings the field was generated from. None for
+    /// a placed field (see fn place), which the seed and settings can not reproduce
+    pub fn generated(&self) -> Option<(u64, UniverseSettings)> {
+        match self.placed {
+            Some(_) => None,
+            None => self.generated,
+        }
+    }
+
+    /// fn scatters returns the clusters scattered on the generated field, in the order
+    /// they were scattered
+    pub fn scatters(&self) -> &[Scatter] {
+        &self.scatters
+    }
+
+    /// fn black_holes regenerates the black holes currently on the field exactly
+    /// as they were generated (or placed), i.e. before any of them were nudged or moved
+    pub fn black_holes(&self) -> Option<Vec<Movable>> {
+        if let Some(placed) = &self.placed {
+            return Some(
+                placed
+                    .iter()
+                    .filter(|object| object.otype == ObjectType::BlackHole)
+                    .map(Movable::build)
+                    .collect(),
+            );
+        }
+
+        self.generated
+            .map(|(seed, settings)| self.field(seed, &settings))
+    }
+
+    /// fn generate returns the black holes for the current seed and settings, followed by
+    /// the scatters, remembering them as the ones now on the field
+    pub fn generate(&mut self, settings: &UniverseSettings) -> Vec<Movable> {
+        self.generated = Some((self.seed, *settings));
+        self.placed = None;
+        self.field(self.seed, settings)
+    }
+
+    /// fn field: private!
+    ///
+    /// returns the black holes generated from seed and settings, fitted to the field,
+    /// followed by the cluster of every scatter drawn with settings
+    fn field(&self, seed: u64, settings: &UniverseSettings) -> Vec<Movable> {
+        let mut black_holes = self.fit(settings.black_holes(seed));
+        for scatter in &self.scatters {
+            black_holes.extend(self.scattered(settings, scatter));
+        }
+        black_holes
+    }
+
+    /// fn scattered: private!
+    ///
+    /// returns the cluster of one scatter drawn with settings, wrapped onto the field
+    fn scattered(&self, settings: &UniverseSettings, scatter: &Scatter) -> Vec<Movable> {
+        let mut black_holes =
+            settings.scatter(scatter.position.x, scatter.position.y, scatter.seed);
+        for black_hole in &mut black_holes {
+            black_hole.wrap(&self.universe);
+        }
+        black_holes
+    }
+
+    /// fn fit: private!
+    ///
+    /// stretches black holes generated in the square UNIVERSE_SIZE universe onto the field,
+    /// clamping their radii to it (see Movable::clamp_radius)
+    fn fit(&self, mut black_holes: Vec<Movable>) -> Vec<Movable> {
+        for black_hole in &mut black_holes {
+            black_hole.clamp_radius(&self.universe);
+            if self.universe == UniverseConfig::default() {
+                continue;
+            }
+
+            let position = self
+                .universe
+                .fit(Vec2::new(black_hole.position.x, black_hole.position.y));
+            black_hole.position.x = position.x;
+            black_hole.position.x_prev = position.x;
+            black_hole.position.y = position.y;
+            black_hole.position.y_prev = position.y;
+        }
+        black_holes
+    }
+
+    /// fn place remembers objects as the ones now on the field in place of a generated
+    /// universe. settings are the current slider positions: the field is kept until
+    /// they (or the seed) change
+    pub fn place(&mut self, settings: &UniverseSettings, objects: &[Movable]) {
+        self.generated = Some((self.seed, *settings));
+        self.placed = Some(objects.iter().map(Movable::build).collect());
+        self.scatters.clear();
+    }
+
+    /// fn scatter returns the cluster of scatter drawn with settings (see
+    /// UniverseSettings::scatter) and adds it to the field: a generated field keeps the
+    /// scatter itself, a placed field the cluster among its placed objects
+    pub fn scatter(&mut self, settings: &UniverseSettings, scatter: Scatter) -> Vec<Movable> {
+        let black_holes = self.scattered(settings, &scatter);
+        match &mut self.placed {
+            Some(placed) => placed.extend(black_holes.iter().map(Movable::build)),
+            None => self.scatters.push(scatter),
+        }
+        black_holes
+    }
+
+    /// fn again returns the NewGameConfig which rebuilds the universe now on the field
+    /// as it started: the placed objects, or the seed, settings and scatters it was
+    /// generated from. None if nothing has been generated yet
+    pub fn again(&self) -> Option<NewGameConfig> {
+        if let Some(placed) = &self.placed {
+            return Some(NewGameConfig::Objects(
+                placed.iter().map(Movable::build).collect(),
+            ));
+        }
+
+        self.generated
+            .map(|(seed, settings)| NewGameConfig::Generated {
+                seed,
+                settings,
+                scatters: self.scatters.clone(),
+            })
+    }
+}
+
+/// NewGameConfig enum: Message
+///
+/// Clears the playing field and starts a new game from (see fn apply_new_game in plugins.rs)
+/// 1. Generated - the black holes generated by seed and settings, which the sliders,
+///    VelocityPattern and ClusterCount are moved to, with the scatters on top
+/// 2. Objects - exactly these objects, e.g. a preset (see `BlackHoleUniverse::with_objects`)
+///
+/// Written on restart, when a recording is loaded for replay and at startup
+#[derive(Message, Debug)]
+pub enum NewGameConfig {
+    Generated {
+        seed: u64,
+        settings: UniverseSettings,
+        scatters: Vec<Scatter>,
+    },
+    Objects(Vec<Movable>),
+}
+
+/// StartingUniverse struct: Resource
+///
+/// The objects the first game starts with in place of a generated universe, see
+/// `BlackHoleUniverse::with_objects`. Emptied once the first game is set up
+#[derive(Resource, Default)]
+pub struct StartingUniverse(pub Option<Vec<Movable>>);
+
+impl NewGameConfig {
+    /// fn objects
+    ///
+    /// returns the objects the new game starts with, remembering them in layout as the
+    /// ones on the field. current are the current slider positions, which a list of
+    /// Objects leaves alone
+    pub fn objects(&self, layout: &mut UniverseLayout, current: &UniverseSettings) -> Vec<Movable> {
+        match self {
+            NewGameConfig::Generated {
+                seed,
+                settings,
+                scatters,
+            } => {
+                layout.seed = *seed;
+                layout.scatters = scatters.clone();
+                layout.generate(settings)
+            }
+            NewGameConfig::Objects(objects) => {
+                layout.place(current, objects);
+                objects
+                    .iter()
+                    .map(|object| object.build().clamp_radius(&layout.universe).build())
+                    .collect()
+            }
+        }
+    }
+}
+
+/// fn test_reproducible ensures one seed always generates the same universe, and
+/// that a single slider only changes its own attribute
+#[test]
+fn test_reproducible() {
+    let settings = UniverseSettings::default();
+    let one = settings.black_holes(7);
+    let two = settings.black_holes(7);
+
+    assert_eq!(one.len(), count_from_slider(settings.count) as usize);
+    for (a, b) in one.iter().zip(two.iter()) {
+        assert_eq!((a.position.x, a.position.y), (b.position.x, b.position.y));
+        assert_eq!((a.size.mass, a.size.radius), (b.size.mass, b.size.radius));
+        assert_eq!(
+            (a.velocity.vx, a.velocity.vy),
+            (b.velocity.vx, b.velocity.vy)
+        );
+    }
+
+    let heavier = UniverseSettings {
+        mass: 0.9,
+        ..settings
+    }
+    .black_holes(7);
+    for (a, b) in one.iter().zip(heavier.iter()) {
+        assert_eq!((a.position.x, a.position.y), (b.position.x, b.position.y));
+        assert_eq!(
+            (a.velocity.vx, a.velocity.vy),
+            (b.velocity.vx, b.velocity.vy)
+        );
+    }
+
+    let mut layout = UniverseLayout::default();
+    assert!(layout.is_stale(&settings));
+    layout.generate(&settings);
+    assert!(!layout.is_stale(&settings));
+    layout.reseed();
+    assert!(layout.is_stale(&settings));
+}
+
+/// fn test_velocity_patterns ensures the patterns keep the sampled speeds and point
+/// tangentially (counter-clockwise) or radially outward
+#[test]
+fn test_velocity_patterns() {
+    let random = UniverseSettings::default().black_holes(3);
+
+    for pattern in [VelocityPattern::Tangential, VelocityPattern::Radial] {
+        let directed = UniverseSettings {
+            pattern,
+            ..default()
+        }
+        .black_holes(3);
+
+        for (r, d) in random.iter().zip(directed.iter()) {
+            let position = Vec2::new(d.position.x, d.position.y);
+            let velocity = Vec2::new(d.velocity.vx, d.velocity.vy);
+            let speed = Vec2::new(r.velocity.vx, r.velocity.vy).length();
+
+            assert_eq!((r.position.x, r.position.y), (d.position.x, d.position.y));
+            assert!((velocity.length() - speed).abs() <= 1e-2 * speed.max(1.0));
+
+            let along = position
+                .normalize_or_zero()
+                .dot(velocity.normalize_or_zero());
+            let across = position
+                .normalize_or_zero()
+                .perp_dot(velocity.normalize_or_zero());
+            match pattern {
+                VelocityPattern::Radial => assert!(velocity.length() < 1e-3 || along > 0.99),
+                _ => assert!(velocity.length() < 1e-3 || across > 0.99),
+            }
+        }
+    }
+
+    for pattern in [
+        VelocityPattern::Random,
+        VelocityPattern::Tangential,
+        VelocityPattern::Radial,
+        VelocityPattern::BigBang,
+    ] {
+        assert_eq!(VelocityPattern::from_name(pattern.name()), Some(pattern));
+        assert_ne!(pattern.next(), pattern);
+    }
+}
+
+/// fn test_big_bang ensures a BigBang packs the black holes toward the center, even the
+/// most (and heaviest) of them at the highest density without any two overlapping,
+/// bursts them outward within MAXVELOCITY and eases its clock in
+#[test]
+fn test_big_bang() {
+    let settings = UniverseSettings {
+        count: 1.0,
+        mass: 1.0,
+        velocity: 1.0,
+        density: 1.0,
+        ..default()
+    };
+    let radial = UniverseSettings {
+        pattern: VelocityPattern::Radial,
+        ..settings
+    }
+    .black_holes(5);
+    let bang = UniverseSettings {
+        pattern: VelocityPattern::BigBang,
+        ..settings
+    }
+    .black_holes(5);
+    assert_eq!(bang.len(), radial.len());
+
+    for (r, b) in radial.iter().zip(bang.iter()) {
+        let sampled = Vec2::new(r.position.x, r.position.y);
+        let position = Vec2::new(b.position.x, b.position.y);
+        let velocity = Vec2::new(b.velocity.vx, b.velocity.vy);
+
+        //packed toward the center, and only ever pushed further out along the same line
+        assert!(position.length() >= BIG_BANG_COMPRESSION * sampled.length() - 1e-2);
+        assert!(sampled.length() < 1.0 || position.normalize().dot(sampled.normalize()) > 0.999);
+
+        assert!(velocity.length() <= Movable::MAXVELOCITY);
+        assert!(velocity.length() >= Vec2::new(r.velocity.vx, r.velocity.vy).length());
+        assert!(velocity.length() < 1e-3 || position.normalize().dot(velocity.normalize()) > 0.99);
+    }
+    for (i, one) in bang.iter().enumerate() {
+        for two in &bang[i + 1..] {
+            let distance = (one.position.x - two.position.x).hypot(one.position.y - two.position.y);
+            assert!(distance > one.contact_distance(two));
+        }
+    }
+
+    let pattern = VelocityPattern::BigBang;
+    assert_eq!(pattern.time_scale(0.0), 0.0);
+    assert!(pattern.time_scale(BIG_BANG_SECS / 4.0) < pattern.time_scale(BIG_BANG_SECS / 2.0));
+    assert_eq!(pattern.time_scale(BIG_BANG_SECS), 1.0);
+    assert_eq!(VelocityPattern::Radial.time_scale(0.0), 1.0);
+}
+
+/// fn test_big_bang_clusters ensures a BigBang of several clusters packs every black
+/// hole toward its own cluster's center, rather than all of them onto the center of
+/// the universe, and bursts it out of that center
+#[test]
+fn test_big_bang_clusters() {
+    let settings = UniverseSettings {
+        count: 1.0,
+        density: 1.0,
+        pattern: VelocityPattern::BigBang,
+        clusters: ClusterCount(3),
+        ..default()
+    };
+    let centers = settings.clusters.centers(11u64.wrapping_add(3));
+    let ring = Vec2::new(centers[0].0, centers[0].1).length();
+
+    let mut members = [0; 3];
+    for black_hole in settings.black_holes(11) {
+        let (nearest, offset) = centers
+            .iter()
+            .map(|(x, y)| Vec2::new(black_hole.position.x - x, black_hole.position.y - y))
+            .enumerate()
+            .min_by(|a, b| a.1.length().total_cmp(&b.1.length()))
+            .unwrap();
+        assert!(offset.length() < ring / 4.0);
+        members[nearest] += 1;
+
+        let velocity = Vec2::new(black_hole.velocity.vx, black_hole.velocity.vy);
+        assert!(
+            velocity.length() < 1e-3
+                || offset.length() < 1.0
+                || offset.normalize().dot(velocity.normalize()) > 0.99
+        );
+    }
+    assert!(members.iter().all(|&count| count > 0));
+}
+
+/// fn test_clusters ensures that several clusters each receive black holes near their
+/// own center and that a single cluster is the original centered universe
+#[test]
+fn test_clusters() {
+    assert_eq!(ClusterCount::default().centers(5), vec![(0.0, 0.0)]);
+    assert_eq!(
+        ClusterCount(ClusterCount::MAX_CLUSTERS).next(),
+        ClusterCount(1)
+    );
+
+    let settings = UniverseSettings {
+        count: 1.0,
+        density: 1.0,
+        clusters: ClusterCount(3),
+        ..default()
+    };
+    let centers = settings.clusters.centers(11u64.wrapping_add(3));
+    assert_eq!(centers.len(), 3);
+
+    let mut members = [0; 3];
+    for black_hole in settings.black_holes(11) {
+        let (nearest, distance) = centers
+            .iter()
+            .map(|(x, y)| (black_hole.position.x - x).hypot(black_hole.position.y - y))
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        //5 standard deviations of the tightest grouping
+        assert!(distance < 5.0 * POSSTDEVMIN * UNIVERSE_SIZE / 2.0);
+        members[nearest] += 1;
+    }
+    assert!(members.iter().all(|&count| count > 0));
+}
+
+/// fn test_missing_sliders ensures an empty slider query (no HUD) yields the default
+/// universe rather than a panic, and that out of range slider values are clamped
+#[test]
+fn test_missing_sliders() {
+    let none: [(&SliderValue, &SliderType); 0] = [];
+    assert_eq!(
+        UniverseSettings::missing_sliders(none),
+        vec!["Count", "Mass", "Velocity", "Density"]
+    );
+
+    let settings = UniverseSettings::from_sliders(none, VelocityPattern::Random, ClusterCount(1));
+    assert_eq!(settings, UniverseSettings::default());
+    assert_eq!(
+        settings.black_holes(3).len(),
+        count_from_slider(settings.count) as usize
+    );
+
+    let density = SliderValue {
+        value: 0.7,
+        prev_value: 0.7,
+    };
+    assert_eq!(
+        UniverseSettings::missing_sliders([(&density, &SliderType::Density)]),
+        vec!["Count", "Mass", "Velocity"]
+    );
+
+    let extreme = UniverseSettings {
+        velocity: -3.0,
+        density: 4.0,
+        ..default()
+    };
+    assert!(!extreme.black_holes(3).is_empty());
+}
+
+/// fn test_new_game_objects ensures a new game from a list of objects starts with
+/// exactly those objects, that they replace the generated field until a slider changes,
+/// and that restarting rebuilds them (or the generated universe) as they started
+#[test]
+fn test_new_game_objects() {
+    let settings = UniverseSettings::default();
+    let objects = vec![
+        Movable::new(&ObjectType::BlackHole)
+            .set_position(-100.0, 20.0)
+            .set_velocity(3.0, -4.0)
+            .set_size(12.0, 40.0)
+            .build(),
+        Movable::new(&ObjectType::BlackHole)
+            .set_position(250.0, -75.0)
+            .set_mass(90.0)
+            .build(),
+        Movable::new(&ObjectType::World)
+            .set_position(0.0, 300.0)
+            .build(),
+    ];
+    let same = |a: &Movable, b: &Movable| {
+        assert_eq!(a.get_id(), b.get_id());
+        assert_eq!(a.otype, b.otype);
+        assert_eq!((a.position.x, a.position.y), (b.position.x, b.position.y));
+        assert_eq!(
+            (a.velocity.vx, a.velocity.vy),
+            (b.velocity.vx, b.velocity.vy)
+        );
+        assert_eq!((a.size.mass, a.size.radius), (b.size.mass, b.size.radius));
+    };
+
+    let mut layout = UniverseLayout::seeded(7);
+    let config = NewGameConfig::Objects(objects.iter().map(Movable::build).collect());
+    let started = config.objects(&mut layout, &settings);
+    assert_eq!(started.len(), objects.len());
+    for (a, b) in started.iter().zip(&objects) {
+        same(a, b);
+    }
+
+    //the placed black holes, not the seeded ones, are the field
+    assert!(!layout.is_stale(&settings));
+    let black_holes = layout.black_holes().unwrap();
+    assert_eq!(black_holes.len(), 2);
+    same(&black_holes[1], &objects[1]);
+
+    let Some(NewGameConfig::Objects(again)) = layout.again() else {
+        panic!("restarting a placed universe must place it again");
+    };
+    assert_eq!(again.len(), objects.len());
+    same(&again[2], &objects[2]);
+
+    //a slider edit regenerates the field from the seed
+    let heavier = UniverseSettings {
+        mass: 0.9,
+        ..settings
+    };
+    assert!(layout.is_stale(&heavier));
+    let generated = layout.generate(&heavier);
+    assert_eq!(layout.black_holes().unwrap().len(), generated.len());
+    assert!(matches!(
+        layout.again(),
+        Some(NewGameConfig::Generated { seed: 7, settings, .. }) if settings == heavier
+    ));
+}
+
+/// fn test_scatter ensures a scattered cluster gathers around the click with the slider
+/// masses, and that the scatters stack on the generated field: a slider edit draws them
+/// again, restarts rebuild them and a new seed drops them
+#[test]
+fn test_scatter() {
+    let settings = UniverseSettings {
+        mass: 1.0,
+        ..default()
+    };
+    let scattered = settings.scatter(4_000.0, -2_000.0, 5);
+    assert_eq!(scattered.len(), SCATTER_COUNT);
+    for black_hole in &scattered {
+        let distance = (black_hole.position.x - 4_000.0).hypot(black_hole.position.y + 2_000.0);
+        assert!(distance < 5.0 * SCATTER_SPREAD);
+        assert!(black_hole.size.mass >= BLACKHOLE_MASS_RNG.lower);
+        assert!(black_hole.size.mass <= BLACKHOLE_MASS_RNG.upper);
+    }
+
+    let mut layout = UniverseLayout::seeded(9);
+    layout.generate(&settings);
+    assert!(layout.generated().is_some());
+
+    let scatter = Scatter {
+        position: Vec2::new(4_000.0, -2_000.0),
+        seed: 5,
+    };
+    let added = layout.scatter(&settings, scatter);
+    assert_eq!(added.len(), SCATTER_COUNT);
+    layout.scatter(
+        &settings,
+        Scatter {
+            position: Vec2::new(-3_000.0, 1_000.0),
+            seed: 6,
+        },
+    );
+    assert!(!layout.is_stale(&settings));
+    assert!(layout.generated().is_some());
+    assert_eq!(layout.scatters().len(), 2);
+    let count = count_from_slider(settings.count) as usize;
+    assert_eq!(
+        layout.black_holes().map(|black_holes| black_holes.len()),
+        Some(count + 2 * SCATTER_COUNT)
+    );
+
+    //a slider edit keeps both scatters, at the same places
+    let lighter = UniverseSettings {
+        mass: 0.2,
+        ..settings
+    };
+    let field = layout.generate(&lighter);
+    assert_eq!(field.len(), count + 2 * SCATTER_COUNT);
+    assert_eq!(field[count].position.x, added[0].position.x);
+    assert_eq!(field[count].position.y, added[0].position.y);
+
+    //a restart rebuilds them
+    let again = layout.again().unwrap();
+    assert!(matches!(&again, NewGameConfig::Generated { scatters, .. } if scatters.len() == 2));
+    let mut restarted = UniverseLayout::seeded(1);
+    assert_eq!(again.objects(&mut restarted, &settings).len(), field.len());
+    assert_eq!(restarted.scatters(), layout.scatters());
+
+    //a new seed drops them
+    layout.reseed();
+    assert!(layout.scatters().is_empty());
+    assert_eq!(layout.generate(&lighter).len(), count);
+}
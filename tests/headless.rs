@@ -0,0 +1,195 @@
+//! Integration tests of the simulation (the PhysicsPlugin) without a window or a GPU
+//! (see objects::headless)
+
+use bevy::prelude::*;
+use black_hole_universe::objects::PhysicsPlugin;
+use black_hole_universe::objects::gamestate::{GameOver, Merge};
+use black_hole_universe::objects::headless::HeadlessUniverse;
+use black_hole_universe::objects::history::MergeHistory;
+use black_hole_universe::objects::movables::{Movable, ObjectType};
+use black_hole_universe::objects::physics::PHYSICS_HZ;
+use black_hole_universe::objects::universe::UniverseSettings;
+
+/// Received struct: Resource
+///
+/// The messages read by fn receive, as an embedding app would read them
+#[derive(Resource, Default)]
+struct Received {
+    game_overs: Vec<GameOver>,
+    merges: Vec<Merge>,
+}
+
+/// Schedule: Update Bevy System
+///
+/// An embedding app's own readers of the GameOver and Merge messages
+fn receive(
+    mut game_over: MessageReader<GameOver>,
+    mut merges: MessageReader<Merge>,
+    mut received: ResMut<Received>,
+) {
+    received.game_overs.extend(game_over.read().copied());
+    received.merges.extend(merges.read().copied());
+}
+
+/// fn listening returns a HeadlessUniverse of the default PhysicsPlugin which also
+/// runs fn receive
+fn listening() -> HeadlessUniverse {
+    let mut universe = HeadlessUniverse::new(PhysicsPlugin::default());
+    universe
+        .app
+        .init_resource::<Received>()
+        .add_systems(Update, receive);
+    universe
+}
+
+/// fn test_head_on_merge ensures two black holes launched at each other close in over
+/// several frames and then merge, through the plugin's own systems, into a single
+/// black hole carrying both masses at their center of mass
+#[test]
+fn test_head_on_merge() {
+    let at = |x: f32, vx: f32| {
+        Movable::new(&ObjectType::BlackHole)
+            .set_position(x, 0.0)
+            .set_velocity(vx, 0.0)
+            .set_mass(10.0)
+            .build()
+    };
+    let left = at(-1_000.0, 500.0);
+    let right = at(1_000.0, -500.0);
+    let ids = vec![left.get_id(), right.get_id()];
+
+    let mut universe = HeadlessUniverse::new(PhysicsPlugin::default());
+    universe.launch([left, right]);
+
+    universe.advance(10);
+    let approaching = universe.objects();
+    assert_eq!(approaching.len(), 2);
+    assert!(approaching[0].position.x > -1_000.0 && approaching[1].position.x < 1_000.0);
+    assert!(
+        approaching[0].velocity.vx >= 500.0,
+        "gravity only speeds up the approach"
+    );
+
+    universe.advance(3 * PHYSICS_HZ as usize);
+    let merged = universe.objects();
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].otype, ObjectType::BlackHole);
+    assert_eq!(merged[0].size.mass, 20.0);
+    assert!(merged[0].velocity.vx.abs() < 1e-3);
+
+    let history = universe.app.world().resource::<MergeHistory>();
+    assert_eq!(history.records.len(), 1);
+    assert_eq!(history.records[0].parent_ids, ids);
+}
+
+/// fn test_game_over_message ensures the merge ending a universe of two black holes
+/// reaches a separate MessageReader as a single Merge and then a single GameOver
+/// carrying the run's stats
+#[test]
+fn test_game_over_message() {
+    let at = |x: f32, vx: f32| {
+        Movable::new(&ObjectType::BlackHole)
+            .set_position(x, 0.0)
+            .set_velocity(vx, 0.0)
+            .set_mass(10.0)
+            .build()
+    };
+
+    let mut universe = listening();
+    universe.launch([at(-1_000.0, 500.0), at(1_000.0, -500.0)]);
+
+    universe.advance(10);
+    assert!(
+        universe
+            .app
+            .world()
+            .resource::<Received>()
+            .game_overs
+            .is_empty()
+    );
+
+    universe.advance(3 * PHYSICS_HZ as usize);
+    let received = universe.app.world().resource::<Received>();
+    assert_eq!(received.merges.len(), 1);
+    assert_eq!(received.merges[0].result_mass, 20.0);
+    assert_eq!(received.game_overs.len(), 1);
+
+    let over = received.game_overs[0];
+    assert_eq!(over.peak_count, 2);
+    assert!(!over.planet_survived, "there was no planet to survive");
+    assert!(over.survival_time > 10.0 / PHYSICS_HZ && over.survival_time < 3.0);
+}
+
+/// fn test_split_is_no_merge ensures two planets colliding head on split into
+/// fragments, each recorded in the MergeHistory, without writing a Merge message
+/// or ending the game
+#[test]
+fn test_split_is_no_merge() {
+    let at = |x: f32, vx: f32| {
+        Movable::new(&ObjectType::World)
+            .set_position(x, 0.0)
+            .set_velocity(vx, 0.0)
+            .set_size(1.0, 50.0)
+            .build()
+    };
+
+    let mut universe = listening();
+    universe.launch([at(-1_000.0, 500.0), at(1_000.0, -500.0)]);
+    universe.advance(3 * PHYSICS_HZ as usize);
+
+    let fragments = universe.objects();
+    assert_eq!(fragments.len(), 4);
+    assert!(fragments.iter().all(|f| f.otype == ObjectType::World));
+
+    let world = universe.app.world();
+    assert_eq!(world.resource::<MergeHistory>().records.len(), 4);
+    let received = world.resource::<Received>();
+    assert!(received.merges.is_empty());
+    assert!(received.game_overs.is_empty());
+}
+
+/// fn test_frame_rate_independent ensures the plugin's FixedUpdate evolves a generated
+/// universe identically whether the display runs at 30fps (several physics steps per
+/// frame) or at 144fps (at most one), once both have run the same number of steps
+#[test]
+fn test_frame_rate_independent() {
+    let launched = |fps: f64| {
+        let mut universe = HeadlessUniverse::at_frame_rate(PhysicsPlugin::default(), fps);
+        universe.launch(UniverseSettings::default().black_holes(11));
+        let launch = universe.fixed_elapsed();
+        (universe, launch)
+    };
+
+    let (mut slow, slow_launch) = launched(30.0);
+    slow.advance(60);
+    let ran = slow.fixed_elapsed() - slow_launch;
+    assert!((ran.as_secs_f64() - 2.0).abs() <= 1.0 / PHYSICS_HZ);
+
+    //a 144fps frame is shorter than a physics step: it never runs more than one
+    let (mut fast, fast_launch) = launched(144.0);
+    while fast.fixed_elapsed() - fast_launch < ran {
+        fast.advance(1);
+    }
+    assert_eq!(fast.fixed_elapsed() - fast_launch, ran);
+
+    let (slow, fast) = (slow.objects(), fast.objects());
+    assert!(!slow.is_empty());
+    assert_eq!(slow.len(), fast.len());
+    for (one, two) in slow.iter().zip(fast.iter()) {
+        assert_eq!(
+            (
+                one.position.x,
+                one.position.y,
+                one.velocity.vx,
+                one.velocity.vy
+            ),
+            (
+                two.position.x,
+                two.position.y,
+                two.velocity.vx,
+                two.velocity.vy
+            )
+        );
+        assert_eq!(one.size.mass, two.size.mass);
+    }
+}